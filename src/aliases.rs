@@ -0,0 +1,162 @@
+use std::collections::BTreeSet;
+
+use super::{MsgDictionary, MsgLine};
+
+/// Comment line declaring an alias when parsing, e.g. `#= alias 1234 -> 5678`.
+const ANNOTATION: &str = "= alias";
+
+impl MsgDictionary {
+    /// Redirects lookups of `from` to `to`, e.g. after renumbering. Every read of a
+    /// dictionary follows aliases: getters, [`count`](Self::count),
+    /// [`crate::MsgProvider`] and [`crate::MsgRead`].
+    ///
+    /// An index with values of its own isn't redirected. Fails if the alias would
+    /// close a cycle.
+    pub fn add_alias(&mut self, from: u32, to: u32) -> Result<(), String> {
+        let mut chain = vec![from, to];
+        let mut next = to;
+        while next != from {
+            match self.aliases.get(&next) {
+                Some(&target) => {
+                    next = target;
+                    chain.push(next);
+                }
+                None => {
+                    self.aliases.insert(from, to);
+                    return Ok(());
+                }
+            }
+        }
+        let chain: Vec<_> = chain.iter().map(u32::to_string).collect();
+        Err(format!("Alias cycle: {}", chain.join(" -> ")))
+    }
+
+    pub fn remove_alias(&mut self, from: u32) -> Option<u32> {
+        self.aliases.remove(&from)
+    }
+
+    /// Direct target of the alias `from`.
+    pub fn alias(&self, from: u32) -> Option<u32> {
+        self.aliases.get(&from).copied()
+    }
+
+    /// Aliases as `(from, to)` pairs in `from` order.
+    pub fn aliases(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.aliases.iter().map(|(&from, &to)| (from, to))
+    }
+
+    /// Index lookups of `index` end up at, following aliases of indices without values.
+    pub fn resolve_alias(&self, mut index: u32) -> u32 {
+        while self.index_to_line.range(index).next().is_none() {
+            match self.aliases.get(&index) {
+                Some(&target) => index = target,
+                None => break,
+            }
+        }
+        index
+    }
+
+    /// Values read for `index`, following aliases.
+    pub(crate) fn resolved_range(
+        &self,
+        index: u32,
+    ) -> impl DoubleEndedIterator<Item = (&(u32, u32), &MsgLine)> + '_ {
+        let index = if self.aliases.is_empty() {
+            index
+        } else {
+            self.resolve_alias(index)
+        };
+        self.index_to_line.range(index)
+    }
+
+    /// Indices with values of their own or through an alias, in ascending order.
+    pub(crate) fn readable_indices(&self) -> impl Iterator<Item = u32> + '_ {
+        let mut indices: BTreeSet<u32> = self.indices().collect();
+        indices.extend(
+            self.aliases
+                .keys()
+                .copied()
+                .filter(|&from| self.resolved_range(from).next().is_some()),
+        );
+        indices.into_iter()
+    }
+}
+
+/// `(from, to)` of a comment holding the alias annotation.
+pub(crate) fn annotation(comment: &[u8]) -> Option<(u32, u32)> {
+    let comment = String::from_utf8_lossy(comment);
+    let rest = comment.trim().strip_prefix(ANNOTATION)?;
+    let (from, to) = rest.split_once("->")?;
+    Some((from.trim().parse().ok()?, to.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn parse_annotation() {
+        assert_eq!(annotation(b"= alias 1234 -> 5678"), Some((1234, 5678)));
+        assert_eq!(annotation(b"= alias 1234"), None);
+        assert_eq!(annotation(b"alias 1 -> 2"), None);
+    }
+
+    #[test]
+    fn redirects_lookups() {
+        let mut dict =
+            parse_msg(b"#= alias 1 -> 2\n#= alias 2 -> 3\n{3}{}{New}\n{4}{}{Own}\n").unwrap();
+        assert_eq!(dict.aliases().collect::<Vec<_>>(), vec![(1, 2), (2, 3)]);
+        assert_eq!(dict.get_first_string(1), Some("New"));
+        assert_eq!(dict.resolve_alias(1), 3);
+
+        dict.add_alias(4, 3).unwrap();
+        assert_eq!(dict.get_first_string(4), Some("Own"));
+        assert_eq!(
+            dict.add_alias(3, 1),
+            Err("Alias cycle: 3 -> 1 -> 2 -> 3".into())
+        );
+        assert_eq!(dict.add_alias(5, 5), Err("Alias cycle: 5 -> 5".into()));
+
+        let written = dict.to_msg_bytes();
+        assert!(written.starts_with(b"#= alias 1 -> 2\n#= alias 2 -> 3\n#= alias 4 -> 3\n"));
+        assert_eq!(
+            parse_msg(&written).unwrap().aliases().count(),
+            dict.aliases().count()
+        );
+        assert!(parse_msg(b"#= alias 1 -> 1\n").is_err());
+    }
+
+    #[test]
+    fn every_read_follows_aliases() {
+        use crate::{FallbackChain, MsgProvider, MsgRead};
+
+        let russ =
+            parse_msg("#= alias 1 -> 2\n{2}{}{Привет}\n{2}{}{Здравствуй}".as_bytes()).unwrap();
+        let engl = parse_msg(b"{1}{}{Hello}\n{3}{}{Bye}").unwrap();
+        assert_eq!(russ.count(1), 2);
+        assert!(russ.has(1));
+        assert_eq!(russ.get_last_string(1), Some("Здравствуй"));
+        assert_eq!(russ.get_all_strings(1).count(), 2);
+        assert_eq!(MsgRead::get_string(&russ, 1, 1), Some("Здравствуй"));
+
+        let chain = FallbackChain::new(vec![&russ, &engl]);
+        assert_eq!(chain.get_str(1), Some("Привет"));
+        assert_eq!(chain.get_str_nth(1, 1), Some("Здравствуй"));
+        assert_eq!(chain.get_str(3), Some("Bye"));
+
+        assert_eq!(russ.max_sub_index(1), Some(1));
+        assert_eq!(
+            russ.iter_first_strings().collect::<Vec<_>>(),
+            vec![(1, "Привет"), (2, "Привет")]
+        );
+        let other = parse_msg(b"#= alias 7 -> 2\n{1}{}{a}\n").unwrap();
+        assert_eq!(russ.intersection_keys(&engl).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(engl.intersection_keys(&other).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(russ.intersection_keys(&other).collect::<Vec<_>>(), vec![1]);
+
+        let mut aliased = parse_msg(b"#= alias 1 -> 2\n{2}{}{a}\n").unwrap();
+        aliased.insert(1, MsgLine::String("own".into()));
+        assert_eq!(aliased.get_first_string(1), Some("own"));
+    }
+}
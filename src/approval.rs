@@ -0,0 +1,229 @@
+use std::{fmt, fmt::Write};
+
+use super::MsgDictionary;
+
+/// Trailing comment part that sets an entry's review state, e.g.
+/// `{100}{}{Привет}# state: reviewed`.
+pub(crate) const ANNOTATION: &str = "state:";
+
+/// Where a translated entry is in the review workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReviewState {
+    Untranslated,
+    Translated,
+    Reviewed,
+    Approved,
+}
+
+impl ReviewState {
+    pub const ALL: [ReviewState; 4] = [
+        ReviewState::Untranslated,
+        ReviewState::Translated,
+        ReviewState::Reviewed,
+        ReviewState::Approved,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReviewState::Untranslated => "untranslated",
+            ReviewState::Translated => "translated",
+            ReviewState::Reviewed => "reviewed",
+            ReviewState::Approved => "approved",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|state| state.as_str() == name)
+    }
+}
+
+impl fmt::Display for ReviewState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Entries of a translation by review state, see [`StateCoverage::of`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCoverage {
+    pub untranslated: usize,
+    pub translated: usize,
+    pub reviewed: usize,
+    pub approved: usize,
+}
+
+impl StateCoverage {
+    /// Counts every key of `base` by its state in `translation`.
+    ///
+    /// Keys without a recorded state are untranslated if missing or equal to the
+    /// base value, translated otherwise.
+    pub fn of(base: &MsgDictionary, translation: &MsgDictionary) -> Self {
+        let mut coverage = Self::default();
+        for (&(index, sub_index), base_value) in base.index_to_line.iter() {
+            let state =
+                match translation.index_to_line.get(&(index, sub_index)) {
+                    None => ReviewState::Untranslated,
+                    Some(value) => translation.review_state(index, sub_index).unwrap_or(
+                        if value == base_value {
+                            ReviewState::Untranslated
+                        } else {
+                            ReviewState::Translated
+                        },
+                    ),
+                };
+            *coverage.count_mut(state) += 1;
+        }
+        coverage
+    }
+
+    pub fn count(&self, state: ReviewState) -> usize {
+        match state {
+            ReviewState::Untranslated => self.untranslated,
+            ReviewState::Translated => self.translated,
+            ReviewState::Reviewed => self.reviewed,
+            ReviewState::Approved => self.approved,
+        }
+    }
+
+    fn count_mut(&mut self, state: ReviewState) -> &mut usize {
+        match state {
+            ReviewState::Untranslated => &mut self.untranslated,
+            ReviewState::Translated => &mut self.translated,
+            ReviewState::Reviewed => &mut self.reviewed,
+            ReviewState::Approved => &mut self.approved,
+        }
+    }
+
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        let mut sum = *self;
+        for state in ReviewState::ALL {
+            *sum.count_mut(state) += other.count(state);
+        }
+        sum
+    }
+}
+
+impl MsgDictionary {
+    /// Review state recorded for an entry, by annotation, sidecar or
+    /// [`set_review_state`](Self::set_review_state).
+    pub fn review_state(&self, index: u32, sub_index: u32) -> Option<ReviewState> {
+        self.review_states.get(&(index, sub_index)).copied()
+    }
+
+    pub fn set_review_state(&mut self, index: u32, sub_index: u32, state: ReviewState) {
+        self.review_states.insert((index, sub_index), state);
+    }
+
+    pub fn clear_review_state(&mut self, index: u32, sub_index: u32) -> Option<ReviewState> {
+        self.review_states.remove(&(index, sub_index))
+    }
+
+    /// Keys recorded with `state`, in key order.
+    pub fn keys_in_state(&self, state: ReviewState) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.review_states
+            .iter()
+            .filter(move |(_key, recorded)| **recorded == state)
+            .map(|(key, _recorded)| *key)
+    }
+
+    /// Recorded states as `index:sub_index state` lines, for storing next to the MSG file.
+    pub fn review_states_sidecar(&self) -> String {
+        let mut out = String::new();
+        for ((index, sub_index), state) in &self.review_states {
+            let _ = writeln!(out, "{}:{} {}", index, sub_index, state);
+        }
+        out
+    }
+
+    /// Records states from [`review_states_sidecar`](Self::review_states_sidecar) output.
+    pub fn load_review_states_sidecar(&mut self, sidecar: &str) -> Result<(), String> {
+        for (number, line) in sidecar.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let malformed = || format!("Malformed review state line {}: {}", number + 1, line);
+            let (key, state) = line.split_once(' ').ok_or_else(malformed)?;
+            let (index, sub_index) = key.split_once(':').ok_or_else(malformed)?;
+            let index = index.parse().map_err(|_| malformed())?;
+            let sub_index = sub_index.parse().map_err(|_| malformed())?;
+            let state = ReviewState::from_name(state.trim()).ok_or_else(malformed)?;
+            self.set_review_state(index, sub_index, state);
+        }
+        Ok(())
+    }
+}
+
+/// State of a trailing comment holding the state annotation.
+pub(crate) fn annotation(comment: &[u8]) -> Option<ReviewState> {
+    let comment = String::from_utf8_lossy(comment);
+    ReviewState::from_name(comment.trim().strip_prefix(ANNOTATION)?.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn states_from_annotations_and_sidecar() {
+        let mut dict =
+            parse_msg(b"{1}{}{A}# state: approved\n{2}{}{B}# state: bogus\n{3}{}{C}\n").unwrap();
+        assert_eq!(dict.review_state(1, 0), Some(ReviewState::Approved));
+        assert_eq!(dict.review_state(2, 0), None);
+
+        dict.set_review_state(3, 0, ReviewState::Reviewed);
+        let sidecar = dict.review_states_sidecar();
+        assert_eq!(sidecar, "1:0 approved\n3:0 reviewed\n");
+
+        let mut reloaded = parse_msg(b"{1}{}{A}\n{3}{}{C}\n").unwrap();
+        reloaded.load_review_states_sidecar(&sidecar).unwrap();
+        assert_eq!(
+            reloaded
+                .keys_in_state(ReviewState::Reviewed)
+                .collect::<Vec<_>>(),
+            vec![(3, 0)]
+        );
+        assert!(reloaded.load_review_states_sidecar("1:0 done").is_err());
+    }
+
+    #[test]
+    fn coverage_by_state() {
+        let base = parse_msg(b"{1}{}{a}\n{2}{}{b}\n{3}{}{c}\n{4}{}{d}\n{5}{}{e}\n").unwrap();
+        let translation = parse_msg(
+            b"{1}{}{A}\n{2}{}{b}\n{3}{}{C}# state: reviewed\n{4}{}{D}# state: approved\n",
+        )
+        .unwrap();
+        assert_eq!(
+            StateCoverage::of(&base, &translation),
+            StateCoverage {
+                untranslated: 2,
+                translated: 1,
+                reviewed: 1,
+                approved: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn edited_states_are_saved() {
+        let mut dict =
+            parse_msg(b"{1}{}{A}# hint; tags: ui; state: translated\n{2}{}{B}# state: approved\n")
+                .unwrap();
+        dict.set_review_state(1, 0, ReviewState::Approved);
+        dict.clear_review_state(2, 0);
+        let written = dict.to_msg_bytes();
+        assert_eq!(
+            written,
+            b"{1}{}{A} # hint; tags: ui; state: approved\n{2}{}{B}\n"
+        );
+        let reloaded = parse_msg(&written).unwrap();
+        assert_eq!(reloaded.review_state(1, 0), Some(ReviewState::Approved));
+        assert_eq!(reloaded.review_state(2, 0), None);
+        assert!(reloaded.has_tag(1, 0, "ui"));
+        assert_eq!(reloaded.comment(1, 0), Some("hint"));
+    }
+}
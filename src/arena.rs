@@ -0,0 +1,92 @@
+use std::io::{self, Write};
+
+use bumpalo::{Bump, collections::Vec};
+
+use super::{
+    DocLine, LineDecoder, MsgDictionary, MsgDocument, ParseError,
+    document::{entries, scan_lines, to_dictionary, write_lines},
+};
+
+/// [`MsgDocument`] whose lines live in a [`Bump`] arena, freed all at once when the
+/// arena is dropped or reset.
+///
+/// Made by [`MsgDocument::parse_in`]. Read-only: convert it with
+/// [`to_document`](Self::to_document) to edit.
+#[derive(Debug)]
+pub struct ArenaDocument<'bump> {
+    lines: Vec<'bump, DocLine<&'bump [u8]>>,
+}
+
+impl MsgDocument {
+    /// Like [`parse`](Self::parse), copying every line into `bump` instead of a
+    /// heap allocation of its own.
+    pub fn parse_in<'bump>(
+        input: &[u8],
+        bump: &'bump Bump,
+    ) -> Result<ArenaDocument<'bump>, ParseError> {
+        let mut lines = Vec::new_in(bump);
+        scan_lines(input, |line| {
+            lines.push(line.map_raw(|raw| &*bump.alloc_slice_copy(raw)))
+        })?;
+        Ok(ArenaDocument { lines })
+    }
+}
+
+impl<'bump> ArenaDocument<'bump> {
+    pub fn lines(&self) -> &[DocLine<&'bump [u8]>] {
+        &self.lines
+    }
+
+    /// Entry lines with their keys and values, in file order.
+    pub fn entries(&self) -> impl Iterator<Item = ((u32, u32), &[u8])> {
+        entries(&self.lines)
+    }
+
+    pub fn write_to(&self, out: impl Write) -> io::Result<()> {
+        write_lines(&self.lines, out)
+    }
+
+    pub fn to_dictionary(&self, decoder: LineDecoder) -> Result<MsgDictionary, ParseError> {
+        to_dictionary(&self.lines, decoder)
+    }
+
+    /// Copies the lines out of the arena.
+    pub fn to_document(&self) -> MsgDocument {
+        MsgDocument {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| line.clone().map_raw(Box::from))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_into_arena() {
+        let input = b"# Maps\r\n{15}{}{20car}  # car\n\n{15}{}{23world}";
+        let mut bump = Bump::new();
+        for _ in 0..2 {
+            let doc = MsgDocument::parse_in(input, &bump).unwrap();
+            assert_eq!(doc.lines().len(), 4);
+            assert_eq!(doc.lines()[1].comment(), Some(&b"car"[..]));
+            assert_eq!(
+                doc.entries().collect::<std::vec::Vec<_>>(),
+                vec![((15, 0), &b"20car"[..]), ((15, 1), &b"23world"[..])]
+            );
+            let mut out = vec![];
+            doc.write_to(&mut out).unwrap();
+            assert_eq!(out, input);
+            assert_eq!(doc.to_document(), MsgDocument::parse(input).unwrap());
+            let dict = doc.to_dictionary(LineDecoder::Utf8Strict).unwrap();
+            assert_eq!(dict.get_all_strings(15).count(), 2);
+            drop(doc);
+            bump.reset();
+        }
+        assert!(MsgDocument::parse_in(b"{1}{}{a}\njunk", &bump).is_err());
+    }
+}
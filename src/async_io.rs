@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use super::{LanguagePack, MsgDictionary};
+
+impl MsgDictionary {
+    /// Writes [`to_msg_bytes`](Self::to_msg_bytes) to `path` with `tokio::fs`.
+    pub async fn write_file_async<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        tokio::fs::write(path, self.to_msg_bytes())
+            .await
+            .map_err(|err| format!("IoError: {}", err))
+    }
+}
+
+impl LanguagePack {
+    /// Writes every file into `dir` under its name, creating `dir` if needed.
+    pub async fn save_async<P: AsRef<Path>>(&self, dir: P) -> Result<(), String> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|err| format!("IoError: {}", err))?;
+        for (name, dict) in self.iter() {
+            dict.write_file_async(dir.join(name))
+                .await
+                .map_err(|err| format!("{}: {}", name, err))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LanguagePack, MsgLine, parse_msg};
+
+    #[test]
+    fn save_and_reload() {
+        let mut dict = parse_msg(b"{1}{}{a}\n").unwrap();
+        dict.insert(2, MsgLine::String("b".into()));
+        let mut pack = LanguagePack::new();
+        pack.insert("TEXT.MSG", dict);
+
+        let dir = std::env::temp_dir().join("fo_msg_format_save_async");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(pack.save_async(&dir)).unwrap();
+        assert_eq!(LanguagePack::load_dir(&dir).unwrap(), pack);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,244 @@
+//! `fo_msg review <base.msg> <translated.msg>`: steps through the keys a translation
+//! lacks or left untranslated, editing values inline and writing the file back.
+
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use fo_msg_format::{LineDecoder, MsgDictionary, ReviewKind, ReviewSession, parse_msg_with};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    widgets::{Block, Paragraph, Wrap},
+};
+
+const USAGE: &str = "Usage: fo_msg review [--cp1251] <base.msg> <translated.msg>";
+
+const KEYS: &str = "←/p back  →/n next  Enter/e edit  w write  q quit";
+
+/// How files are read and written.
+#[derive(Clone, Copy)]
+enum Encoding {
+    Utf8,
+    #[cfg(feature = "cp1251")]
+    Cp1251,
+}
+
+impl Encoding {
+    #[cfg(feature = "cp1251")]
+    const CP1251: Option<Encoding> = Some(Encoding::Cp1251);
+    #[cfg(not(feature = "cp1251"))]
+    const CP1251: Option<Encoding> = None;
+
+    fn read(self, path: &Path) -> Result<MsgDictionary, String> {
+        let bytes = std::fs::read(path).map_err(|err| format!("IoError: {}", err))?;
+        let decoder = match self {
+            Encoding::Utf8 => LineDecoder::Utf8Strict,
+            #[cfg(feature = "cp1251")]
+            Encoding::Cp1251 => LineDecoder::Encoding(encoding_rs::WINDOWS_1251),
+        };
+        parse_msg_with(&bytes, decoder).map_err(|err| err.to_string())
+    }
+
+    fn write(self, path: &Path, dict: &MsgDictionary) -> Result<(), String> {
+        let bytes = match self {
+            Encoding::Utf8 => dict.to_msg_bytes(),
+            #[cfg(feature = "cp1251")]
+            Encoding::Cp1251 => dict
+                .to_msg_bytes_encoded(
+                    encoding_rs::WINDOWS_1251,
+                    fo_msg_format::EncodePolicy::Error,
+                )
+                .map_err(|err| err.to_string())?,
+        };
+        std::fs::write(path, bytes).map_err(|err| format!("IoError: {}", err))
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(args.iter().map(String::as_str).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: Vec<&str>) -> Result<(), String> {
+    let encoding = if args.contains(&"--cp1251") {
+        args.retain(|&arg| arg != "--cp1251");
+        Encoding::CP1251.ok_or("--cp1251 needs the cp1251 feature")?
+    } else {
+        Encoding::Utf8
+    };
+    match args[..] {
+        ["review", base, translated] => review(Path::new(base), translated.into(), encoding),
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn review(base: &Path, path: PathBuf, encoding: Encoding) -> Result<(), String> {
+    let base = encoding
+        .read(base)
+        .map_err(|err| format!("{}: {}", base.display(), err))?;
+    // A translation that doesn't exist yet starts empty.
+    let translated = if path.exists() {
+        encoding
+            .read(&path)
+            .map_err(|err| format!("{}: {}", path.display(), err))?
+    } else {
+        MsgDictionary::new()
+    };
+    let session = ReviewSession::new(&base, translated);
+    if session.items().is_empty() {
+        println!("Nothing to review in {}", path.display());
+        return Ok(());
+    }
+    let mut app = App {
+        session,
+        path,
+        encoding,
+        editing: None,
+        dirty: false,
+        confirm_quit: false,
+        status: String::new(),
+    };
+    let mut terminal = ratatui::init();
+    let res = app.run(&mut terminal);
+    ratatui::restore();
+    res.map_err(|err| format!("IoError: {}", err))
+}
+
+struct App {
+    session: ReviewSession,
+    path: PathBuf,
+    encoding: Encoding,
+    /// Value being typed, while editing.
+    editing: Option<String>,
+    /// Edited since the last write.
+    dirty: bool,
+    /// Quitting was asked for with unsaved edits.
+    confirm_quit: bool,
+    status: String,
+}
+
+impl App {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            if let Some(value) = &mut self.editing {
+                match key.code {
+                    KeyCode::Enter => {
+                        let value = std::mem::take(value);
+                        self.editing = None;
+                        self.session.edit(&value);
+                        self.dirty = true;
+                        self.session.advance();
+                    }
+                    KeyCode::Esc => self.editing = None,
+                    KeyCode::Backspace => {
+                        value.pop();
+                    }
+                    KeyCode::Char(ch) => value.push(ch),
+                    _ => {}
+                }
+                continue;
+            }
+            let confirm_quit = std::mem::take(&mut self.confirm_quit);
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc if !self.dirty || confirm_quit => {
+                    return Ok(());
+                }
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    self.confirm_quit = true;
+                    self.status = "Unsaved edits, press w to write or q again to quit".into();
+                }
+                KeyCode::Right | KeyCode::Char('n') => {
+                    self.session.advance();
+                }
+                KeyCode::Left | KeyCode::Char('p') => {
+                    self.session.back();
+                }
+                KeyCode::Enter | KeyCode::Char('e') => {
+                    self.editing = Some(self.session.current_value().unwrap_or_default().into());
+                }
+                KeyCode::Char('w') => self.write(),
+                _ => {}
+            }
+        }
+    }
+
+    fn write(&mut self) {
+        self.status = match self.encoding.write(&self.path, self.session.translated()) {
+            Ok(()) => {
+                self.dirty = false;
+                format!("Wrote {}", self.path.display())
+            }
+            Err(err) => err,
+        };
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, base, value, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let item = match self.session.current() {
+            Some(item) => item,
+            None => return,
+        };
+        let kind = match item.kind {
+            ReviewKind::Missing => "missing",
+            ReviewKind::Untranslated => "untranslated",
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{}/{}  {{{}}}[{}] {}{}",
+                self.session.position() + 1,
+                self.session.items().len(),
+                item.key.0,
+                item.key.1,
+                kind,
+                if self.dirty { "  (unsaved)" } else { "" },
+            )),
+            header,
+        );
+        frame.render_widget(
+            Paragraph::new(item.base.as_str())
+                .block(Block::bordered().title("Base"))
+                .wrap(Wrap { trim: false }),
+            base,
+        );
+        let (title, text) = match &self.editing {
+            Some(value) => ("Translation (Enter to keep, Esc to cancel)", value.as_str()),
+            None => (
+                "Translation",
+                self.session.current_value().unwrap_or_default(),
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(Block::bordered().title(title))
+                .wrap(Wrap { trim: false }),
+            value,
+        );
+        let status = if self.status.is_empty() {
+            KEYS
+        } else {
+            &self.status
+        };
+        frame.render_widget(Paragraph::new(status), footer);
+    }
+}
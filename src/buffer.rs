@@ -0,0 +1,134 @@
+use std::ops::Range;
+
+use super::{Line, LineDecoder, MsgDictionary, MsgRead, ParseError, scanner};
+
+/// Read-only dictionary owning a single copy of the input, with entries stored as
+/// ranges into it instead of individually allocated values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferMsgDictionary {
+    buffer: Box<[u8]>,
+    /// Sorted by `(index, sub_index)`.
+    entries: Vec<((u32, u32), Range<u32>)>,
+}
+
+impl BufferMsgDictionary {
+    pub fn parse(input: &[u8]) -> Result<Self, ParseError> {
+        if input.len() > u32::MAX as usize {
+            return Err(ParseError::Invalid("Input is larger than 4 GiB".into()));
+        }
+        let msg = scanner::tokenize_bytes(input, true)?;
+        let offset = |slice: &[u8]| (slice.as_ptr() as usize - input.as_ptr() as usize) as u32;
+        let mut entries = Vec::with_capacity(msg.lines.len());
+        for line in msg.lines {
+            if let Line::Entry(entry) = line {
+                if !entry.secondary.is_empty() {
+                    return Err(ParseError::syntax_at(
+                        input,
+                        offset(entry.secondary) as usize,
+                        format!("Non-empty secondary key at index {}", entry.index),
+                    ));
+                }
+                let start = offset(entry.value);
+                entries.push(((entry.index, 0), start..start + entry.value.len() as u32));
+            }
+        }
+        // Stable, so values under the same index keep their file order.
+        entries.sort_by_key(|&((index, _sub_index), _)| index);
+        for i in 1..entries.len() {
+            let (previous, _) = entries[i - 1].0;
+            if entries[i].0.0 == previous {
+                entries[i].0.1 = entries[i - 1].0.1 + 1;
+            }
+        }
+        Ok(Self {
+            buffer: input.into(),
+            entries,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        Some(self.value(self.position(index, sub_index)?))
+    }
+
+    pub fn get_first_bytes(&self, index: u32) -> Option<&[u8]> {
+        self.get_bytes(index, 0)
+    }
+
+    /// First value of `index` if it is valid UTF-8.
+    pub fn get_first_string(&self, index: u32) -> Option<&str> {
+        std::str::from_utf8(self.get_first_bytes(index)?).ok()
+    }
+
+    /// Entries in `(index, sub_index)` order.
+    pub fn iter(&self) -> impl Iterator<Item = ((u32, u32), &[u8])> {
+        (0..self.entries.len()).map(move |pos| (self.entries[pos].0, self.value(pos)))
+    }
+
+    /// Decodes every value into a regular [`MsgDictionary`].
+    pub fn to_dictionary(&self, decoder: LineDecoder) -> MsgDictionary {
+        let mut dict = MsgDictionary::new();
+        for ((index, _sub_index), value) in self.iter() {
+            dict.insert(index, decoder.decode(value));
+        }
+        dict
+    }
+
+    /// Position of the entry in key order.
+    pub(crate) fn position(&self, index: u32, sub_index: u32) -> Option<usize> {
+        self.entries
+            .binary_search_by_key(&(index, sub_index), |&(key, _)| key)
+            .ok()
+    }
+
+    pub(crate) fn value(&self, pos: usize) -> &[u8] {
+        let range = &self.entries[pos].1;
+        &self.buffer[range.start as usize..range.end as usize]
+    }
+}
+
+impl MsgRead for BufferMsgDictionary {
+    fn get_string(&self, index: u32, sub_index: u32) -> Option<&str> {
+        std::str::from_utf8(self.get_bytes(index, sub_index)?).ok()
+    }
+
+    fn get_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        BufferMsgDictionary::get_bytes(self, index, sub_index)
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        let start = self.entries.partition_point(|&((key, _), _)| key < index);
+        let end = self.entries.partition_point(|&((key, _), _)| key <= index);
+        (end - start) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg_with;
+
+    #[test]
+    fn matches_dictionary() {
+        let input = b"# header\n{15}{}{car}\n{10}{}{Global map}\n{15}{}{\xff}\n";
+        let dict = BufferMsgDictionary::parse(input).unwrap();
+        assert_eq!(dict.len(), 3);
+        assert_eq!(dict.get_first_string(10), Some("Global map"));
+        assert_eq!(dict.get_bytes(15, 1), Some(&b"\xff"[..]));
+        assert_eq!(dict.get_string(15, 1), None);
+        assert_eq!(MsgRead::count(&dict, 15), 2);
+        assert_eq!(MsgRead::count(&dict, 11), 0);
+        assert_eq!(
+            dict.to_dictionary(LineDecoder::Utf8Strict),
+            parse_msg_with(input, LineDecoder::Utf8Strict).unwrap()
+        );
+        assert!(BufferMsgDictionary::parse(b"{1}{x}{a}\n").is_err());
+    }
+}
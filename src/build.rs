@@ -0,0 +1,63 @@
+//! Helpers for build scripts.
+
+use std::{fmt::Write, path::Path};
+
+use super::{MsgDictionary, parse_file};
+
+/// Parses the MSG file at `path` and writes Rust source embedding it to `out_rs`,
+/// usually a file in `OUT_DIR`:
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     let out_dir = std::env::var("OUT_DIR").unwrap();
+///     fo_msg_format::build::embed("text/FOGAME.MSG", format!("{}/fogame.rs", out_dir)).unwrap();
+/// }
+///
+/// // src/lib.rs
+/// include!(concat!(env!("OUT_DIR"), "/fogame.rs"));
+/// let dict = load_msg();
+/// ```
+///
+/// The source defines `MSG_DATA`, the dictionary as a binary cache, and
+/// `load_msg()` reading it. Also tells cargo to rerun the build script when the
+/// MSG file changes.
+pub fn embed<P: AsRef<Path>, O: AsRef<Path>>(path: P, out_rs: O) -> Result<(), String> {
+    let path = path.as_ref();
+    let dict = parse_file(path).map_err(|err| err.to_string())?;
+    std::fs::write(out_rs, embed_source(&dict)).map_err(|err| format!("IoError: {}", err))?;
+    println!("cargo:rerun-if-changed={}", path.display());
+    Ok(())
+}
+
+fn embed_source(dict: &MsgDictionary) -> String {
+    let mut out = String::from("pub static MSG_DATA: &[u8] = b\"");
+    for byte in dict.to_binary_cache() {
+        out.extend(std::ascii::escape_default(byte).map(char::from));
+    }
+    out.push_str("\";\n\n");
+    let _ = write!(
+        out,
+        "pub fn load_msg() -> fo_msg_format::MsgDictionary {{\n    \
+             fo_msg_format::MsgDictionary::from_binary_cache(MSG_DATA)\n        \
+                 .expect(\"embedded MSG data is valid\")\n\
+         }}\n"
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn embedded_source() {
+        let dict = parse_msg(b"{1}{}{a\"b}\n").unwrap();
+        let source = embed_source(&dict);
+        assert!(source.starts_with(
+            "pub static MSG_DATA: &[u8] = b\"\\x01\\x00\\x00\\x00\\x01\\x00\\x00\\x00\\x03\\x00\\x00\\x00a\\\"b\";\n"
+        ));
+        assert!(source.contains("pub fn load_msg() -> fo_msg_format::MsgDictionary {"));
+    }
+}
@@ -0,0 +1,186 @@
+use std::{fs, path::Path};
+
+use super::{LanguagePack, Manifest, MsgDictionary, ParseError};
+
+const SOURCE_DIR: &str = "source";
+const TRANSLATION_DIR: &str = "translation";
+const NOTES_FILE: &str = "NOTES.txt";
+const MANIFEST_FILE: &str = "MANIFEST";
+
+/// What [`LanguagePack::import_bundle`] merged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BundleImport {
+    /// `(file, index)` of every index taken from the bundle.
+    pub updated: Vec<(String, u32)>,
+    pub notes: String,
+}
+
+fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), ParseError> {
+    fs::write(path, contents).map_err(|err| ParseError::from(err).in_file(path))
+}
+
+fn write_pack(pack: &LanguagePack, dir: &Path) -> Result<(), ParseError> {
+    fs::create_dir_all(dir).map_err(|err| ParseError::from(err).in_file(dir))?;
+    for (name, dict) in pack.iter() {
+        write_file(&dir.join(name), dict.to_msg_bytes())?;
+    }
+    Ok(())
+}
+
+impl LanguagePack {
+    /// Writes a handoff bundle for translators into `dir`: `source/` and
+    /// `translation/` MSG files, `NOTES.txt` and a `MANIFEST` of the source.
+    pub fn export_bundle<P: AsRef<Path>>(
+        &self,
+        source: &LanguagePack,
+        notes: &str,
+        dir: P,
+    ) -> Result<(), ParseError> {
+        let dir = dir.as_ref();
+        write_pack(source, &dir.join(SOURCE_DIR))?;
+        write_pack(self, &dir.join(TRANSLATION_DIR))?;
+        write_file(&dir.join(NOTES_FILE), notes)?;
+        write_file(&dir.join(MANIFEST_FILE), source.manifest().to_string())
+    }
+
+    /// Merges translations from a bundle returned by translators, replacing every
+    /// index the bundle changed.
+    ///
+    /// Fails without changing anything if `source` moved on since the export, or if
+    /// the bundle has files or indices that `source` doesn't.
+    pub fn import_bundle<P: AsRef<Path>>(
+        &mut self,
+        source: &LanguagePack,
+        dir: P,
+    ) -> Result<BundleImport, ParseError> {
+        let dir = dir.as_ref();
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest: Manifest = fs::read_to_string(&manifest_path)
+            .map_err(|err| ParseError::from(err).in_file(&manifest_path))?
+            .parse()
+            .map_err(|err| ParseError::Invalid(err).in_file(&manifest_path))?;
+        if !source.verify(&manifest).is_empty() {
+            return Err(ParseError::Invalid(
+                "Source files changed since the bundle was exported".into(),
+            ));
+        }
+        let notes = fs::read_to_string(dir.join(NOTES_FILE)).unwrap_or_default();
+        let translation_dir = dir.join(TRANSLATION_DIR);
+        let translated = LanguagePack::load_dir(&translation_dir)?;
+
+        let mut updated = vec![];
+        for (name, dict) in translated.iter() {
+            let not_in_source =
+                |message: String| ParseError::Invalid(message).in_file(translation_dir.join(name));
+            let source_dict = source
+                .get(name)
+                .ok_or_else(|| not_in_source("File not in the source".into()))?;
+            let current = self.get(name);
+            for index in dict.indices() {
+                if source_dict.count(index) == 0 {
+                    return Err(not_in_source(format!("Index {} not in the source", index)));
+                }
+                let changed = current.is_none_or(|current| {
+                    !current
+                        .index_to_line
+                        .range(index)
+                        .map(|(_key, value)| value)
+                        .eq(dict.index_to_line.range(index).map(|(_key, value)| value))
+                });
+                if changed {
+                    updated.push((name.to_owned(), index));
+                }
+            }
+        }
+
+        for (name, index) in &updated {
+            let dict = translated.get(name).expect("checked above");
+            if self.get(name).is_none() {
+                self.insert(name.clone(), MsgDictionary::new());
+            }
+            let target = self.get_mut(name).expect("inserted above");
+            target.remove_range(*index..=*index);
+            for (_key, value) in dict.index_to_line.range(*index) {
+                target.insert(*index, value.clone());
+            }
+        }
+        Ok(BundleImport { updated, notes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    fn pack(files: &[(&str, &[u8])]) -> LanguagePack {
+        let mut pack = LanguagePack::new();
+        for &(name, input) in files {
+            pack.insert(name, parse_msg(input).unwrap());
+        }
+        pack
+    }
+
+    #[test]
+    fn export_and_import() {
+        let dir = std::env::temp_dir().join("fo_msg_format_bundle");
+        let _ = fs::remove_dir_all(&dir);
+        let source = pack(&[("FOTEXT.MSG", b"{1}{}{Hello}\n{2}{}{Bye}\n")]);
+        let mut translation = pack(&[("FOTEXT.MSG", b"{1}{}{Privet}\n")]);
+        translation
+            .export_bundle(&source, "Keep it short", &dir)
+            .unwrap();
+
+        // Translators fill in index 2 and send the bundle back.
+        fs::write(
+            dir.join(TRANSLATION_DIR).join("FOTEXT.MSG"),
+            b"{1}{}{Privet}\n{2}{}{Poka}\n",
+        )
+        .unwrap();
+        let import = translation.import_bundle(&source, &dir).unwrap();
+        assert_eq!(import.updated, vec![("FOTEXT.MSG".to_owned(), 2)]);
+        assert_eq!(import.notes, "Keep it short");
+        assert_eq!(
+            translation.get("FOTEXT.MSG").unwrap().get_first_string(2),
+            Some("Poka")
+        );
+
+        fs::write(
+            dir.join(TRANSLATION_DIR).join("FOTEXT.MSG"),
+            b"{3}{}{Extra}\n",
+        )
+        .unwrap();
+        match translation.import_bundle(&source, &dir) {
+            Err(ParseError::File { path, error }) => {
+                assert!(path.ends_with("translation/FOTEXT.MSG"));
+                assert_eq!(error.to_string(), "Index 3 not in the source");
+            }
+            other => panic!("{:?}", other),
+        }
+        let changed_source = pack(&[("FOTEXT.MSG", b"{1}{}{Hi}\n")]);
+        assert!(matches!(
+            translation.import_bundle(&changed_source, &dir),
+            Err(ParseError::Invalid(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn import_last_index() {
+        let dir = std::env::temp_dir().join("fo_msg_format_bundle_last");
+        let _ = fs::remove_dir_all(&dir);
+        let source = pack(&[("FOTEXT.MSG", b"{4294967295}{}{Last}\n")]);
+        let mut translation = pack(&[("FOTEXT.MSG", b"{4294967295}{}{Old}\n")]);
+        translation.export_bundle(&source, "", &dir).unwrap();
+        fs::write(
+            dir.join(TRANSLATION_DIR).join("FOTEXT.MSG"),
+            b"{4294967295}{}{New}\n",
+        )
+        .unwrap();
+        translation.import_bundle(&source, &dir).unwrap();
+        let dict = translation.get("FOTEXT.MSG").unwrap();
+        assert_eq!(dict.get_first_string(u32::MAX), Some("New"));
+        assert_eq!(dict.count(u32::MAX), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
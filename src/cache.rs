@@ -0,0 +1,170 @@
+use super::{MsgDictionary, MsgLine, diff::DiffEntry};
+
+const OP_REMOVE: u8 = 0;
+const OP_SET: u8 = 1;
+
+impl MsgDictionary {
+    /// Serializes entries in key order into this crate's binary cache layout: entry
+    /// count, then for every entry its index, value length and value bytes, all
+    /// numbers `u32` little-endian. Unlike the engine's `FOMsg::GetBinaryData`, the
+    /// result isn't zlib-compressed.
+    pub fn to_binary_cache(&self) -> Vec<u8> {
+        let mut out = vec![];
+        put_u32(&mut out, self.len() as u32);
+        for (&(index, _sub_index), value) in self.index_to_line.iter() {
+            put_u32(&mut out, index);
+            put_value(&mut out, value.bytes());
+        }
+        out
+    }
+
+    /// Reads a binary cache, repeated indices become consecutive sub-indices.
+    ///
+    /// UTF-8 values become strings, others are kept as bytes.
+    pub fn from_binary_cache(data: &[u8]) -> Result<MsgDictionary, String> {
+        let mut reader = Reader(data);
+        let count = reader.u32()?;
+        let mut dict = MsgDictionary::new();
+        for _ in 0..count {
+            let index = reader.u32()?;
+            let value = line(reader.value()?);
+            dict.try_insert(index, value)
+                .map_err(|err| err.to_string())?;
+        }
+        reader.finish()?;
+        Ok(dict)
+    }
+}
+
+/// Compact delta turning binary cache `old` into `new`, see [`apply_cache_delta`].
+///
+/// Layout: operation count, then per operation a tag byte (0 remove, 1 set),
+/// index and sub-index, and for sets the value length and bytes.
+pub fn cache_delta(old: &[u8], new: &[u8]) -> Result<Vec<u8>, String> {
+    let old = MsgDictionary::from_binary_cache(old)?;
+    let new = MsgDictionary::from_binary_cache(new)?;
+    let diff = old.diff(&new);
+    let mut out = vec![];
+    put_u32(&mut out, diff.entries.len() as u32);
+    for entry in &diff.entries {
+        let (index, sub_index) = entry.key();
+        match entry {
+            DiffEntry::Removed { .. } => {
+                out.push(OP_REMOVE);
+                put_u32(&mut out, index);
+                put_u32(&mut out, sub_index);
+            }
+            DiffEntry::Added { new, .. } | DiffEntry::Changed { new, .. } => {
+                out.push(OP_SET);
+                put_u32(&mut out, index);
+                put_u32(&mut out, sub_index);
+                put_value(&mut out, new.bytes());
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Applies a delta made by [`cache_delta`] to binary cache `old`.
+pub fn apply_cache_delta(old: &[u8], delta: &[u8]) -> Result<Vec<u8>, String> {
+    let mut dict = MsgDictionary::from_binary_cache(old)?;
+    let mut reader = Reader(delta);
+    let count = reader.u32()?;
+    for _ in 0..count {
+        let op = reader.take(1)?[0];
+        let key = (reader.u32()?, reader.u32()?);
+        match op {
+            OP_REMOVE => {
+                dict.index_to_line
+                    .remove(&key)
+                    .ok_or_else(|| format!("Delta removes missing entry [{}:{}]", key.0, key.1))?;
+            }
+            OP_SET => {
+                let value = line(reader.value()?);
+                dict.index_to_line.insert(key, value);
+            }
+            op => return Err(format!("Unknown delta operation {}", op)),
+        }
+    }
+    reader.finish()?;
+    Ok(dict.to_binary_cache())
+}
+
+fn line(bytes: &[u8]) -> MsgLine {
+    match std::str::from_utf8(bytes) {
+        Ok(str) => MsgLine::String(str.into()),
+        Err(_) => MsgLine::Bytes(bytes.into()),
+    }
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_value(out: &mut Vec<u8>, value: &[u8]) {
+    put_u32(out, value.len() as u32);
+    out.extend_from_slice(value);
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err("Unexpected end of binary data".into());
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn value(&mut self) -> Result<&'a [u8], String> {
+        let len = self.u32()?;
+        self.take(len as usize)
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        match self.0.len() {
+            0 => Ok(()),
+            len => Err(format!("{} trailing bytes in binary data", len)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn cache_roundtrip() {
+        let dict = parse_msg(b"{1}{}{a}\n{1}{}{bc}\n{7}{}{}\n").unwrap();
+        let cache = dict.to_binary_cache();
+        assert_eq!(&cache[..12], &[3, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(MsgDictionary::from_binary_cache(&cache), Ok(dict));
+        assert!(MsgDictionary::from_binary_cache(&cache[..cache.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn delta_roundtrip() {
+        let mut old_text = String::new();
+        for index in 0..1000 {
+            old_text.push_str(&format!("{{{}}}{{}}{{Line number {}}}\n", index, index));
+        }
+        let new_text =
+            old_text.replace("{5}{}{Line number 5}", "{5}{}{Changed}") + "{2000}{}{New}\n";
+        let old = parse_msg(old_text.as_bytes()).unwrap().to_binary_cache();
+        let new = parse_msg(new_text.as_bytes()).unwrap().to_binary_cache();
+
+        let delta = cache_delta(&old, &new).unwrap();
+        assert!(delta.len() < 64);
+        assert_eq!(apply_cache_delta(&old, &delta), Ok(new.clone()));
+        assert_eq!(cache_delta(&new, &new).unwrap(), vec![0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,257 @@
+use super::{MsgDictionary, MsgLine};
+
+/// CP1251 letters outside of the contiguous `А..я` block, as (upper, lower) pairs.
+const CP1251_CASE_PAIRS: &[(u8, u8)] = &[
+    (0x80, 0x90), // Ђ ђ
+    (0x81, 0x83), // Ѓ ѓ
+    (0x8a, 0x9a), // Љ љ
+    (0x8c, 0x9c), // Њ њ
+    (0x8d, 0x9d), // Ќ ќ
+    (0x8e, 0x9e), // Ћ ћ
+    (0x8f, 0x9f), // Џ џ
+    (0xa1, 0xa2), // Ў ў
+    (0xa3, 0xbc), // Ј ј
+    (0xa5, 0xb4), // Ґ ґ
+    (0xa8, 0xb8), // Ё ё
+    (0xaa, 0xba), // Є є
+    (0xaf, 0xbf), // Ї ї
+    (0xb2, 0xb3), // І і
+    (0xbd, 0xbe), // Ѕ ѕ
+];
+
+pub(crate) fn cp1251_upper(byte: u8) -> u8 {
+    match byte {
+        b'a'..=b'z' | 0xe0..=0xff => byte - 0x20,
+        _ => CP1251_CASE_PAIRS
+            .iter()
+            .find(|&&(_upper, lower)| lower == byte)
+            .map_or(byte, |&(upper, _lower)| upper),
+    }
+}
+
+pub(crate) fn cp1251_lower(byte: u8) -> u8 {
+    match byte {
+        b'A'..=b'Z' | 0xc0..=0xdf => byte + 0x20,
+        _ => CP1251_CASE_PAIRS
+            .iter()
+            .find(|&&(upper, _lower)| upper == byte)
+            .map_or(byte, |&(_upper, lower)| lower),
+    }
+}
+
+fn map_line(line: &MsgLine, string: fn(&str) -> String, byte: fn(u8) -> u8) -> Vec<u8> {
+    match line {
+        MsgLine::String(value) => string(value).into_bytes(),
+        MsgLine::Bytes(bytes) => bytes.iter().copied().map(byte).collect(),
+    }
+}
+
+/// `line` as a string, byte values decoded as CP1251, `None` if they can't be.
+pub(crate) fn cp1251_string(line: &MsgLine) -> Option<std::borrow::Cow<'_, str>> {
+    let bytes = match line {
+        MsgLine::String(value) => return Some((&**value).into()),
+        MsgLine::Bytes(bytes) => bytes,
+    };
+    #[cfg(any(test, feature = "cp1251"))]
+    {
+        encoding_rs::WINDOWS_1251.decode_without_bom_handling_and_without_replacement(bytes)
+    }
+    #[cfg(not(any(test, feature = "cp1251")))]
+    {
+        // ASCII is the same in CP1251, anything else needs the `cp1251` feature.
+        if bytes.is_ascii() {
+            std::str::from_utf8(bytes).ok().map(Into::into)
+        } else {
+            None
+        }
+    }
+}
+
+/// `value` encoded as CP1251, `None` if it can't be.
+pub(crate) fn cp1251_bytes(value: &str) -> Option<Vec<u8>> {
+    #[cfg(any(test, feature = "cp1251"))]
+    {
+        let (bytes, _encoding, had_errors) = encoding_rs::WINDOWS_1251.encode(value);
+        if had_errors {
+            None
+        } else {
+            Some(bytes.into_owned())
+        }
+    }
+    #[cfg(not(any(test, feature = "cp1251")))]
+    {
+        if value.is_ascii() {
+            Some(value.as_bytes().to_vec())
+        } else {
+            None
+        }
+    }
+}
+
+/// `needle` lower-cased and encoded as CP1251, `None` if it can't be encoded.
+fn cp1251_needle(needle: &str) -> Option<Vec<u8>> {
+    let lower = needle.to_lowercase();
+    #[cfg(any(test, feature = "cp1251"))]
+    {
+        let (bytes, _encoding, had_errors) = encoding_rs::WINDOWS_1251.encode(&lower);
+        if had_errors {
+            return None;
+        }
+        Some(bytes.iter().copied().map(cp1251_lower).collect())
+    }
+    #[cfg(not(any(test, feature = "cp1251")))]
+    {
+        // ASCII is the same in CP1251, anything else needs the `cp1251` feature.
+        if lower.is_ascii() {
+            Some(lower.into_bytes())
+        } else {
+            None
+        }
+    }
+}
+
+fn contains_cp1251_lower(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty()
+        || haystack.windows(needle.len()).any(|window| {
+            window
+                .iter()
+                .zip(needle)
+                .all(|(&byte, &lower)| cp1251_lower(byte) == lower)
+        })
+}
+
+fn line_contains(line: &MsgLine, needle: &str, cp1251_needle: Option<&[u8]>) -> bool {
+    match line {
+        MsgLine::String(value) => value.to_lowercase().contains(needle),
+        MsgLine::Bytes(bytes) => {
+            cp1251_needle.is_some_and(|needle| contains_cp1251_lower(bytes, needle))
+        }
+    }
+}
+
+impl MsgDictionary {
+    /// Keys of values containing `needle`, ignoring case. String values are folded
+    /// as Unicode and byte values as CP1251.
+    ///
+    /// Without the `cp1251` feature byte values only match ASCII needles.
+    pub fn find_case_insensitive(&self, needle: &str) -> Vec<(u32, u32)> {
+        let lower = needle.to_lowercase();
+        let bytes = cp1251_needle(needle);
+        self.index_to_line
+            .iter()
+            .filter(|(_key, line)| line_contains(line, &lower, bytes.as_deref()))
+            .map(|(&key, _line)| key)
+            .collect()
+    }
+
+    /// Whether the first value of `index` contains `needle`, ignoring case.
+    pub fn first_contains_case_insensitive(&self, index: u32, needle: &str) -> bool {
+        self.first_line(index).is_some_and(|line| {
+            line_contains(
+                line,
+                &needle.to_lowercase(),
+                cp1251_needle(needle).as_deref(),
+            )
+        })
+    }
+
+    /// Upper-cased first value of `index`, byte values decoded as CP1251.
+    ///
+    /// Without the `cp1251` feature only ASCII byte values are decoded.
+    pub fn get_first_string_upper(&self, index: u32) -> Option<String> {
+        cp1251_string(self.first_line(index)?).map(|value| value.to_uppercase())
+    }
+
+    /// Lower-cased counterpart of [`get_first_string_upper`](Self::get_first_string_upper).
+    pub fn get_first_string_lower(&self, index: u32) -> Option<String> {
+        cp1251_string(self.first_line(index)?).map(|value| value.to_lowercase())
+    }
+
+    /// Upper-cases string values as Unicode and byte values as CP1251.
+    pub fn get_first_bytes_upper(&self, index: u32) -> Option<Vec<u8>> {
+        Some(map_line(
+            self.first_line(index)?,
+            str::to_uppercase,
+            cp1251_upper,
+        ))
+    }
+
+    /// Lower-cases string values as Unicode and byte values as CP1251.
+    pub fn get_first_bytes_lower(&self, index: u32) -> Option<Vec<u8>> {
+        Some(map_line(
+            self.first_line(index)?,
+            str::to_lowercase,
+            cp1251_lower,
+        ))
+    }
+
+    /// First value of `index`, following aliases like the other getters.
+    fn first_line(&self, index: u32) -> Option<&MsgLine> {
+        self.lookup((index, 0), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cp1251_case_roundtrip() {
+        for byte in 0..=255u8 {
+            let upper = cp1251_upper(byte);
+            if upper != byte {
+                assert_eq!(cp1251_lower(upper), byte);
+            }
+        }
+    }
+
+    #[test]
+    fn dictionary_case_getters() {
+        let mut dict = MsgDictionary::new();
+        dict.insert(1, MsgLine::String("Привет, world".into()));
+        // "ёЖик" in CP1251
+        dict.insert(2, MsgLine::Bytes(b"\xb8\xc6\xe8\xea"[..].into()));
+        assert_eq!(
+            dict.get_first_string_upper(1).as_deref(),
+            Some("ПРИВЕТ, WORLD")
+        );
+        assert_eq!(
+            dict.get_first_string_lower(1).as_deref(),
+            Some("привет, world")
+        );
+        assert_eq!(
+            dict.get_first_bytes_upper(2).as_deref(),
+            Some(&b"\xa8\xc6\xc8\xca"[..])
+        );
+        assert_eq!(
+            dict.get_first_bytes_lower(2).as_deref(),
+            Some(&b"\xb8\xe6\xe8\xea"[..])
+        );
+        assert_eq!(dict.get_first_string_upper(2).as_deref(), Some("ЁЖИК"));
+        assert_eq!(dict.get_first_string_lower(2).as_deref(), Some("ёжик"));
+        dict.add_alias(3, 2).unwrap();
+        assert_eq!(dict.get_first_string_upper(3).as_deref(), Some("ЁЖИК"));
+        assert_eq!(
+            dict.get_first_bytes_upper(3).as_deref(),
+            Some(&b"\xa8\xc6\xc8\xca"[..])
+        );
+    }
+
+    #[test]
+    fn case_insensitive_search() {
+        let mut dict = MsgDictionary::new();
+        dict.insert(1, MsgLine::String("Большой Ёж".into()));
+        // "ЁЖИК ест" in CP1251
+        dict.insert(
+            2,
+            MsgLine::Bytes(b"\xa8\xc6\xc8\xca \xe5\xf1\xf2"[..].into()),
+        );
+        dict.insert(3, MsgLine::String("hedgehog".into()));
+        assert_eq!(dict.find_case_insensitive("ёж"), vec![(1, 0), (2, 0)]);
+        assert_eq!(dict.find_case_insensitive("ЕСТ"), vec![(2, 0)]);
+        assert_eq!(dict.find_case_insensitive("HEDGE"), vec![(3, 0)]);
+        assert!(dict.first_contains_case_insensitive(2, "жик"));
+        assert!(!dict.first_contains_case_insensitive(3, "ёж"));
+        assert!(dict.find_case_insensitive("日本").is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt::Write;
+
+use super::MsgDictionary;
+
+impl MsgDictionary {
+    /// Rust source of a function `fn_name(index: u32) -> Option<&'static str>`
+    /// returning the first string value of `index` through a `match`, for tools
+    /// shipping fixed text without parsing it at startup.
+    ///
+    /// Byte values are left out.
+    pub fn to_rust_lookup(&self, fn_name: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "#[allow(clippy::all)]");
+        let _ = writeln!(
+            out,
+            "pub fn {}(index: u32) -> Option<&'static str> {{",
+            fn_name
+        );
+        let _ = writeln!(out, "    match index {{");
+        for (index, value) in self.iter_first_strings() {
+            let _ = writeln!(out, "        {} => Some({:?}),", index, value);
+        }
+        let _ = writeln!(out, "        _ => None,");
+        let _ = writeln!(out, "    }}");
+        let _ = writeln!(out, "}}");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MsgLine, parse_msg};
+
+    #[test]
+    fn match_lookup() {
+        let mut dict =
+            parse_msg("{1}{}{Hello \"world\"}\n{1}{}{Other}\n{20}{}{Привет\n}\n".as_bytes())
+                .unwrap();
+        dict.insert(30, MsgLine::Bytes(b"\xff"[..].into()));
+        assert_eq!(
+            dict.to_rust_lookup("fogame"),
+            "#[allow(clippy::all)]\n\
+             pub fn fogame(index: u32) -> Option<&'static str> {\n    \
+                 match index {\n        \
+                     1 => Some(\"Hello \\\"world\\\"\"),\n        \
+                     20 => Some(\"Привет\\n\"),\n        \
+                     _ => None,\n    \
+                 }\n\
+             }\n"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::cmp::Ordering;
+
+use super::MsgDictionary;
+
+const RUSSIAN_ALPHABET: &str = "абвгдеёжзийклмнопрстуфхцчшщъыьэюя";
+
+/// Order of exported values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Collation {
+    /// Plain byte order.
+    #[default]
+    Bytes,
+    /// Russian alphabetical order: case-insensitive, `ё` right after `е`, digits
+    /// before Latin before Cyrillic letters.
+    Russian,
+}
+
+impl Collation {
+    pub fn compare(self, left: &str, right: &str) -> Ordering {
+        match self {
+            Collation::Bytes => left.cmp(right),
+            Collation::Russian => russian_primary(left)
+                .cmp(russian_primary(right))
+                .then_with(|| russian_secondary(left).cmp(russian_secondary(right)))
+                .then_with(|| russian_tertiary(left).cmp(russian_tertiary(right)))
+                .then_with(|| left.cmp(right)),
+        }
+    }
+}
+
+/// Letters compared without case and with `ё` as `е`.
+fn russian_primary(value: &str) -> impl Iterator<Item = (u8, u32)> + '_ {
+    value.chars().flat_map(char::to_lowercase).map(|ch| {
+        let ch = if ch == 'ё' { 'е' } else { ch };
+        if let Some(rank) = RUSSIAN_ALPHABET.chars().position(|letter| letter == ch) {
+            (3, rank as u32)
+        } else if ch.is_ascii_alphabetic() {
+            (2, ch as u32)
+        } else if ch.is_numeric() {
+            (1, ch.to_digit(10).unwrap_or(ch as u32))
+        } else {
+            (0, ch as u32)
+        }
+    })
+}
+
+/// `е` before `ё`.
+fn russian_secondary(value: &str) -> impl Iterator<Item = bool> + '_ {
+    value.chars().map(|ch| matches!(ch, 'ё' | 'Ё'))
+}
+
+/// Lower case before upper case.
+fn russian_tertiary(value: &str) -> impl Iterator<Item = bool> + '_ {
+    value.chars().map(char::is_uppercase)
+}
+
+impl MsgDictionary {
+    /// String values with their keys, sorted by `collation`, e.g. for glossaries
+    /// and review sheets.
+    pub fn sorted_values(&self, collation: Collation) -> Vec<((u32, u32), &str)> {
+        let mut values: Vec<_> = self
+            .index_to_line
+            .iter()
+            .filter_map(|(&key, value)| Some((key, value.string()?)))
+            .collect();
+        values.sort_by(|(_left_key, left), (_right_key, right)| collation.compare(left, right));
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn russian_order() {
+        let mut words = vec![
+            "ёлка",
+            "Ель",
+            "ель",
+            "ёж",
+            "еж",
+            "яблоко",
+            "Жук",
+            "apple",
+            "10",
+            "ящик",
+        ];
+        words.sort_by(|left, right| Collation::Russian.compare(left, right));
+        assert_eq!(
+            words,
+            vec![
+                "10",
+                "apple",
+                "еж",
+                "ёж",
+                "ёлка",
+                "ель",
+                "Ель",
+                "Жук",
+                "яблоко",
+                "ящик"
+            ]
+        );
+    }
+
+    #[test]
+    fn sorted_export() {
+        let dict = parse_msg("{1}{}{Яма}\n{2}{}{Ёж}\n{3}{}{Банка}\n".as_bytes()).unwrap();
+        let sorted: Vec<_> = dict
+            .sorted_values(Collation::Russian)
+            .into_iter()
+            .map(|(key, _value)| key.0)
+            .collect();
+        assert_eq!(sorted, vec![3, 2, 1]);
+        let bytes: Vec<_> = dict
+            .sorted_values(Collation::Bytes)
+            .into_iter()
+            .map(|(key, _value)| key.0)
+            .collect();
+        assert_eq!(bytes, vec![2, 3, 1]);
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+
+use super::{LineDecoder, MsgType, parse_lines};
+
+/// MSG file of a project, as input to [`find_collisions`].
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectFile<'a> {
+    pub name: &'a str,
+    pub msg_type: MsgType,
+    /// 0 for the base game, higher for overlays applied on top.
+    pub overlay: u32,
+    pub input: &'a [u8],
+}
+
+/// Where a colliding entry is defined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    pub file: String,
+    /// 1-based line of the entry.
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionKind {
+    /// Index defined in several files of one message type and overlay.
+    Duplicate,
+    /// Overlays setting different first values for the same index.
+    Conflict,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub msg_type: MsgType,
+    pub index: u32,
+    pub kind: CollisionKind,
+    pub sources: Vec<Provenance>,
+}
+
+/// First value of an index in one file.
+struct Definition {
+    overlay: u32,
+    file: usize,
+    line: usize,
+    value: Vec<u8>,
+}
+
+/// Indices that collide across `files`, ordered by message type and index.
+///
+/// Overriding a base value from an overlay is expected and isn't reported.
+pub fn find_collisions(files: &[ProjectFile]) -> Result<Vec<Collision>, String> {
+    let mut definitions: BTreeMap<(MsgType, u32), Vec<Definition>> = BTreeMap::new();
+    for (number, file) in files.iter().enumerate() {
+        let entries = parse_lines(file.input, 1..usize::MAX, LineDecoder::RawBytes)
+            .map_err(|err| format!("{}: {}", file.name, err))?;
+        let mut seen = BTreeSet::new();
+        for entry in entries {
+            // Only the first value of each index in a file counts.
+            if !seen.insert(entry.index) {
+                continue;
+            }
+            definitions
+                .entry((file.msg_type, entry.index))
+                .or_default()
+                .push(Definition {
+                    overlay: file.overlay,
+                    file: number,
+                    line: entry.line,
+                    value: entry.value.bytes().to_vec(),
+                });
+        }
+    }
+
+    let provenance = |def: &&Definition| Provenance {
+        file: files[def.file].name.to_owned(),
+        line: def.line,
+    };
+    let mut collisions = vec![];
+    for ((msg_type, index), defs) in definitions {
+        let mut layers: BTreeMap<u32, Vec<&Definition>> = BTreeMap::new();
+        for def in &defs {
+            layers.entry(def.overlay).or_default().push(def);
+        }
+        for layer in layers.values().filter(|layer| layer.len() > 1) {
+            collisions.push(Collision {
+                msg_type,
+                index,
+                kind: CollisionKind::Duplicate,
+                sources: layer.iter().map(provenance).collect(),
+            });
+        }
+        let overlays: Vec<_> = defs.iter().filter(|def| def.overlay > 0).collect();
+        if overlays.iter().any(|def| def.value != overlays[0].value) {
+            collisions.push(Collision {
+                msg_type,
+                index,
+                kind: CollisionKind::Conflict,
+                sources: overlays.iter().map(provenance).collect(),
+            });
+        }
+    }
+    Ok(collisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file<'a>(
+        name: &'a str,
+        msg_type: MsgType,
+        overlay: u32,
+        input: &'a [u8],
+    ) -> ProjectFile<'a> {
+        ProjectFile {
+            name,
+            msg_type,
+            overlay,
+            input,
+        }
+    }
+
+    #[test]
+    fn duplicates_and_conflicts() {
+        let files = [
+            file(
+                "base/FOTEXT.MSG",
+                MsgType::Text,
+                0,
+                b"{1}{}{a}\n{2}{}{c}\n{1}{}{b}\n",
+            ),
+            file("base/extra.msg", MsgType::Text, 0, b"\n{2}{}{c}\n"),
+            file("base/FOGM.MSG", MsgType::GlobalMap, 0, b"{1}{}{map}\n"),
+            file("mod_a/FOTEXT.MSG", MsgType::Text, 1, b"{1}{}{A}\n"),
+            file(
+                "mod_b/FOTEXT.MSG",
+                MsgType::Text,
+                2,
+                b"{1}{}{B}\n{2}{}{c}\n",
+            ),
+        ];
+        let at = |file: &str, line| Provenance {
+            file: file.into(),
+            line,
+        };
+        assert_eq!(
+            find_collisions(&files).unwrap(),
+            vec![
+                Collision {
+                    msg_type: MsgType::Text,
+                    index: 1,
+                    kind: CollisionKind::Conflict,
+                    sources: vec![at("mod_a/FOTEXT.MSG", 1), at("mod_b/FOTEXT.MSG", 1)],
+                },
+                Collision {
+                    msg_type: MsgType::Text,
+                    index: 2,
+                    kind: CollisionKind::Duplicate,
+                    sources: vec![at("base/FOTEXT.MSG", 2), at("base/extra.msg", 2)],
+                },
+            ]
+        );
+    }
+}
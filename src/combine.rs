@@ -0,0 +1,91 @@
+use std::collections::btree_map::BTreeMap;
+
+use super::{MsgDictionary, MsgLine};
+
+/// Several files in one lookup keyed by `(tag, index, sub_index)`, see [`combine`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombinedMsg<T> {
+    entries: BTreeMap<(T, u32, u32), MsgLine>,
+}
+
+/// Merges `files` into one [`CombinedMsg`], keeping each value's file tag.
+pub fn combine<T: Ord + Clone>(files: &[(T, MsgDictionary)]) -> CombinedMsg<T> {
+    let mut entries = BTreeMap::new();
+    for (tag, dict) in files {
+        for (&(index, sub_index), value) in dict.index_to_line.iter() {
+            entries.insert((tag.clone(), index, sub_index), value.clone());
+        }
+    }
+    CombinedMsg { entries }
+}
+
+impl<T: Ord + Clone> CombinedMsg<T> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get_first_string(&self, tag: &T, index: u32) -> Option<&str> {
+        self.entries.get(&(tag.clone(), index, 0))?.string()
+    }
+
+    /// Every entry with its tag, ordered by tag then key.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, (u32, u32), &MsgLine)> {
+        self.entries
+            .iter()
+            .map(|((tag, index, sub_index), value)| (tag, (*index, *sub_index), value))
+    }
+
+    /// Keys of string values containing `needle`, across all files.
+    pub fn find(&self, needle: &str) -> Vec<(&T, u32, u32)> {
+        self.iter()
+            .filter(|(_tag, _key, value)| {
+                value.string().is_some_and(|value| value.contains(needle))
+            })
+            .map(|(tag, (index, sub_index), _value)| (tag, index, sub_index))
+            .collect()
+    }
+
+    /// Splits back into one dictionary per tag.
+    pub fn split(self) -> Vec<(T, MsgDictionary)> {
+        let mut files: Vec<(T, MsgDictionary)> = vec![];
+        for ((tag, index, _sub_index), value) in self.entries {
+            match files.last_mut() {
+                Some((last, dict)) if *last == tag => dict.insert(index, value),
+                _ => {
+                    let mut dict = MsgDictionary::new();
+                    dict.insert(index, value);
+                    files.push((tag, dict));
+                }
+            }
+        }
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn combine_and_split() {
+        let files = vec![
+            ("FOTEXT", parse_msg(b"{1}{}{Hello}\n{1}{}{Hi}\n").unwrap()),
+            ("FOGM", parse_msg(b"{1}{}{Hello map}\n").unwrap()),
+        ];
+        let combined = combine(&files);
+        assert_eq!(combined.len(), 3);
+        assert_eq!(combined.get_first_string(&"FOGM", 1), Some("Hello map"));
+        assert_eq!(
+            combined.find("Hello"),
+            vec![(&"FOGM", 1, 0), (&"FOTEXT", 1, 0)]
+        );
+        let mut split = combined.split();
+        split.sort_by_key(|&(tag, _)| std::cmp::Reverse(tag));
+        assert_eq!(split, files);
+    }
+}
@@ -0,0 +1,125 @@
+use std::borrow::Cow;
+
+use super::{MsgDictionary, MsgLine, approval, deprecation, tags};
+
+/// Separates parts of a trailing comment, so one comment can hold free text and
+/// several annotations, e.g. `# keep it short; tags: ui; state: reviewed`.
+const SEPARATOR: u8 = b';';
+
+impl MsgDictionary {
+    /// Comment written after an entry on its line, e.g. a translator hint, without
+    /// the `#` or `//` marker. Decoded like the entry's value, so `None` for a comment
+    /// kept as bytes.
+    pub fn comment(&self, index: u32, sub_index: u32) -> Option<&str> {
+        self.comments.get(&(index, sub_index))?.string()
+    }
+
+    /// Comment of an entry as written, or UTF-8 if it was set as a string.
+    pub fn comment_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        self.comments.get(&(index, sub_index)).map(MsgLine::bytes)
+    }
+
+    /// Sets the trailing comment of an entry, written back by [`MsgDictionary::write_to`].
+    pub fn set_comment(&mut self, index: u32, sub_index: u32, comment: &str) -> Result<(), String> {
+        if comment.contains(['\n', '\r']) {
+            return Err("Comment must fit on one line".into());
+        }
+        self.comments.insert(
+            (index, sub_index),
+            MsgLine::String(comment.trim_end().into()),
+        );
+        Ok(())
+    }
+
+    pub fn remove_comment(&mut self, index: u32, sub_index: u32) -> Option<MsgLine> {
+        self.comments.remove(&(index, sub_index))
+    }
+
+    /// Annotations the writer appends to an entry's comment, regenerated from the
+    /// dictionary so edits are saved.
+    pub(crate) fn annotations(&self, index: u32, sub_index: u32) -> Vec<String> {
+        let mut annotations = vec![];
+        if let Some(note) = self.deprecation(index).filter(|_| sub_index == 0) {
+            annotations.push(match note {
+                "" => deprecation::ANNOTATION.to_owned(),
+                note => format!("{}: {}", deprecation::ANNOTATION, note),
+            });
+        }
+        let tags: Vec<&str> = self.tags(index, sub_index).collect();
+        if !tags.is_empty() {
+            annotations.push(format!("{} {}", tags::ANNOTATION, tags.join(", ")));
+        }
+        if let Some(state) = self.review_state(index, sub_index) {
+            annotations.push(format!("{} {}", approval::ANNOTATION, state));
+        }
+        annotations
+    }
+}
+
+/// Splits a trailing comment into its free text and its annotation parts.
+pub(crate) fn split_annotations(comment: &[u8]) -> (Cow<'_, [u8]>, Vec<&[u8]>) {
+    let (annotations, text): (Vec<&[u8]>, Vec<&[u8]>) = comment
+        .split(|&byte| byte == SEPARATOR)
+        .map(<[u8]>::trim_ascii)
+        .partition(|part| is_annotation(part));
+    if annotations.is_empty() {
+        return (Cow::Borrowed(comment.trim_ascii()), annotations);
+    }
+    let text = text
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(&b"; "[..]);
+    (Cow::Owned(text), annotations)
+}
+
+fn is_annotation(part: &[u8]) -> bool {
+    deprecation::annotation(part).is_some()
+        || tags::annotation(part).is_some()
+        || approval::annotation(part).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::WINDOWS_1251;
+
+    use crate::{EncodePolicy, LineDecoder, parse_msg, parse_msg_with};
+
+    #[test]
+    fn trailing_comments_roundtrip() {
+        let input = b"{1}{}{Hello} # keep it short\n{1}{}{Hi}\n{2}{}{Bye}// informal\n";
+        let mut dict = parse_msg(input).unwrap();
+        assert_eq!(dict.comment(1, 0), Some("keep it short"));
+        assert_eq!(dict.comment(1, 1), None);
+        assert_eq!(dict.comment(2, 0), Some("informal"));
+
+        dict.set_comment(1, 1, "greeting").unwrap();
+        assert!(dict.set_comment(1, 1, "a\nb").is_err());
+        dict.remove_comment(2, 0);
+        let written = dict.to_msg_bytes();
+        assert_eq!(
+            written,
+            b"{1}{}{Hello} # keep it short\n{1}{}{Hi} # greeting\n{2}{}{Bye}\n"
+        );
+        assert_eq!(parse_msg(&written).unwrap().comment(1, 1), Some("greeting"));
+    }
+
+    #[test]
+    fn cp1251_comments_roundtrip() {
+        // {1}{}{Привет} # для детей
+        let input = b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2} # \xe4\xeb\xff \xe4\xe5\xf2\xe5\xe9\n";
+        let raw = parse_msg(input).unwrap();
+        assert_eq!(raw.comment(1, 0), None);
+        assert_eq!(raw.comment_bytes(1, 0), Some(&input[16..25]));
+        assert_eq!(raw.to_msg_bytes(), input);
+
+        let decoded = parse_msg_with(input, LineDecoder::Encoding(WINDOWS_1251)).unwrap();
+        assert_eq!(decoded.comment(1, 0), Some("для детей"));
+        assert_eq!(
+            decoded
+                .to_msg_bytes_encoded(WINDOWS_1251, EncodePolicy::Error)
+                .unwrap(),
+            input
+        );
+    }
+}
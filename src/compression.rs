@@ -0,0 +1,83 @@
+use std::{io, path::Path};
+
+use super::ParseError;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Reads a file, transparently decompressing gzip or zstd content.
+pub(crate) fn read_file<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, ParseError> {
+    let bytes = std::fs::read(path)?;
+    decompress(bytes).map_err(|err| ParseError::Io(io::Error::new(io::ErrorKind::InvalidData, err)))
+}
+
+/// Decompresses `bytes` if they start with gzip or zstd magic, otherwise returns them as is.
+pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    if bytes.starts_with(GZIP_MAGIC) {
+        gunzip(&bytes)
+    } else if bytes.starts_with(ZSTD_MAGIC) {
+        unzstd(&bytes)
+    } else {
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    flate2::read::MultiGzDecoder::new(bytes)
+        .read_to_end(&mut out)
+        .map_err(|err| format!("GzipError: {}", err))?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    Err("Input is gzip-compressed, enable the `gzip` feature".into())
+}
+
+#[cfg(feature = "zstd")]
+fn unzstd(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    zstd::stream::decode_all(bytes).map_err(|err| format!("ZstdError: {}", err))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn unzstd(_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    Err("Input is zstd-compressed, enable the `zstd` feature".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_input_passes_through() {
+        assert_eq!(decompress(b"{1}{}{a}".to_vec()).unwrap(), b"{1}{}{a}");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_input() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"{1}{}{a}").unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(decompress(compressed).unwrap(), b"{1}{}{a}");
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_input() {
+        let compressed = zstd::stream::encode_all(&b"{1}{}{a}"[..], 0).unwrap();
+        assert_eq!(decompress(compressed).unwrap(), b"{1}{}{a}");
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn gzip_requires_feature() {
+        assert!(decompress(vec![0x1f, 0x8b, 0x08]).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+};
+
+#[cfg(any(test, feature = "cp1251"))]
+use super::EncodePolicy;
+use super::{LineDecoder, MsgDictionary, ParseError, compression::read_file, parse_msg_with};
+
+/// Files [`convert_tree`] reads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputSpec {
+    /// `*.msg` files, optionally gzip or zstd compressed, decoded with the decoder.
+    Msg(LineDecoder),
+    /// `*.msgbin` files from [`MsgDictionary::to_binary_cache`].
+    BinaryCache,
+}
+
+/// Files [`convert_tree`] writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputSpec {
+    /// `*.msg` files, string values as UTF-8.
+    Msg,
+    /// `*.msg` files, string values encoded with the encoding. Files the policy
+    /// rejects are reported as failed.
+    #[cfg(any(test, feature = "cp1251"))]
+    MsgEncoded(&'static encoding_rs::Encoding, EncodePolicy),
+    /// `*.msgbin` binary caches.
+    BinaryCache,
+}
+
+const MSG_EXTENSION: &str = "msg";
+const CACHE_EXTENSION: &str = "msgbin";
+
+/// Outcome of one file of [`convert_tree`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeConversion {
+    /// Path relative to the source directory.
+    pub path: PathBuf,
+    /// Number of entries converted.
+    pub outcome: Result<usize, String>,
+}
+
+impl InputSpec {
+    fn extension(self) -> &'static str {
+        match self {
+            InputSpec::Msg(_decoder) => MSG_EXTENSION,
+            InputSpec::BinaryCache => CACHE_EXTENSION,
+        }
+    }
+
+    fn read(self, path: &Path) -> Result<MsgDictionary, String> {
+        let bytes = read_file(path).map_err(|err| err.to_string())?;
+        match self {
+            InputSpec::Msg(decoder) => {
+                parse_msg_with(&bytes, decoder).map_err(|err| err.to_string())
+            }
+            InputSpec::BinaryCache => MsgDictionary::from_binary_cache(&bytes),
+        }
+    }
+}
+
+impl OutputSpec {
+    fn extension(self) -> &'static str {
+        match self {
+            OutputSpec::Msg => MSG_EXTENSION,
+            #[cfg(any(test, feature = "cp1251"))]
+            OutputSpec::MsgEncoded(..) => MSG_EXTENSION,
+            OutputSpec::BinaryCache => CACHE_EXTENSION,
+        }
+    }
+
+    fn write(self, dict: &MsgDictionary, path: &Path) -> Result<(), String> {
+        let bytes = match self {
+            OutputSpec::Msg => dict.to_msg_bytes(),
+            #[cfg(any(test, feature = "cp1251"))]
+            OutputSpec::MsgEncoded(encoding, policy) => dict
+                .to_msg_bytes_encoded(encoding, policy)
+                .map_err(|issue| issue.to_string())?,
+            OutputSpec::BinaryCache => dict.to_binary_cache(),
+        };
+        fs::write(path, bytes).map_err(|err| format!("IoError: {}", err))
+    }
+}
+
+/// Relative paths of files with `extension` (case-insensitive) under `dir`.
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    extension: &str,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, extension, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+        {
+            files.push(
+                path.strip_prefix(root)
+                    .expect("walked from root")
+                    .to_owned(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Converts every input file under `src_dir` into `dst_dir`, keeping the relative
+/// layout, on all available cores.
+///
+/// Fails only if `src_dir` can't be walked; per-file errors are in the report,
+/// which is sorted by path.
+pub fn convert_tree<P: AsRef<Path>, Q: AsRef<Path>>(
+    src_dir: P,
+    dst_dir: Q,
+    input: InputSpec,
+    output: OutputSpec,
+) -> Result<Vec<TreeConversion>, ParseError> {
+    let (src_dir, dst_dir) = (src_dir.as_ref(), dst_dir.as_ref());
+    let mut files = vec![];
+    collect_files(src_dir, src_dir, input.extension(), &mut files)?;
+    files.sort();
+
+    let convert = |path: &Path| -> Result<usize, String> {
+        let dict = input.read(&src_dir.join(path))?;
+        let target = dst_dir.join(path).with_extension(output.extension());
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("IoError: {}", err))?;
+        }
+        output.write(&dict, &target)?;
+        Ok(dict.len())
+    };
+    let queue = Mutex::new(files.iter());
+    let report = Mutex::new(Vec::with_capacity(files.len()));
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(files.len());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    // Release the queue before converting.
+                    let next = queue.lock().unwrap().next();
+                    let Some(path) = next else { break };
+                    let outcome = convert(path);
+                    report.lock().unwrap().push(TreeConversion {
+                        path: path.clone(),
+                        outcome,
+                    });
+                }
+            });
+        }
+    });
+    let mut report = report.into_inner().unwrap();
+    report.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_cp1251_tree() {
+        let root = std::env::temp_dir().join("fo_msg_format_convert_tree");
+        let _ = fs::remove_dir_all(&root);
+        let (src, dst, back) = (root.join("src"), root.join("dst"), root.join("back"));
+        fs::create_dir_all(src.join("russ")).unwrap();
+        fs::write(
+            src.join("russ").join("FOTEXT.MSG"),
+            b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2}\n",
+        )
+        .unwrap();
+        fs::write(src.join("broken.msg"), b"{1}{}{unclosed\n").unwrap();
+        fs::write(src.join("readme.txt"), b"skipped").unwrap();
+
+        let decoder = LineDecoder::Encoding(encoding_rs::WINDOWS_1251);
+        let report =
+            convert_tree(&src, &dst, InputSpec::Msg(decoder), OutputSpec::BinaryCache).unwrap();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].path, Path::new("broken.msg"));
+        assert!(report[0].outcome.is_err());
+        assert_eq!(report[1].outcome, Ok(1));
+
+        convert_tree(&dst, &back, InputSpec::BinaryCache, OutputSpec::Msg).unwrap();
+        assert_eq!(
+            fs::read(back.join("russ").join("FOTEXT.msg")).unwrap(),
+            "{1}{}{Привет}\n".as_bytes()
+        );
+
+        let encoded = root.join("encoded");
+        fs::write(back.join("han.msg"), "{1}{}{漢}\n").unwrap();
+        let output = OutputSpec::MsgEncoded(encoding_rs::WINDOWS_1251, EncodePolicy::Error);
+        let utf8 = InputSpec::Msg(LineDecoder::Utf8Strict);
+        let report = convert_tree(&back, &encoded, utf8, output).unwrap();
+        assert_eq!(report[0].path, Path::new("han.msg"));
+        assert!(
+            report[0]
+                .outcome
+                .as_ref()
+                .unwrap_err()
+                .contains("unrepresentable")
+        );
+        assert!(!encoded.join("han.msg").exists());
+        assert_eq!(report[1].outcome, Ok(1));
+        assert_eq!(
+            fs::read(encoded.join("russ").join("FOTEXT.msg")).unwrap(),
+            b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2}\n"
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    LineDecoder, MsgDictionary, MsgDocument, compression, json, parse_msg_with, roundtrip,
+};
+
+/// Environment variable listing corpus directories, separated like `PATH`.
+pub const CORPUS_ENV: &str = "FO_MSG_CORPUS";
+
+/// Suffix of fixtures recorded from the reference C++ engine: `FOTEXT.MSG.ref` holds
+/// the entries it loaded from `FOTEXT.MSG`, written in the
+/// [`MsgDictionary::to_binary_cache`] layout.
+pub const REFERENCE_SUFFIX: &str = ".ref";
+
+/// Result of checking one MSG file, see [`run_corpus`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusFile {
+    pub path: PathBuf,
+    pub outcome: Result<Conformance, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conformance {
+    pub entries: usize,
+    /// 1-based line where the file read as a [`MsgDocument`] and written back
+    /// first differs, if it does.
+    pub roundtrip_divergence: Option<usize>,
+    pub reference: ReferenceCheck,
+}
+
+/// Comparison of the parsed entries with those the reference engine loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceCheck {
+    /// No fixture next to the file.
+    NoFixture,
+    Match,
+    /// Outputs differ, at the first differing key if the fixture could be read.
+    Mismatch(Option<(u32, u32)>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorpusReport {
+    pub files: Vec<CorpusFile>,
+}
+
+impl CorpusReport {
+    /// Whether every file parsed.
+    pub fn all_parsed(&self) -> bool {
+        self.files.iter().all(|file| file.outcome.is_ok())
+    }
+
+    /// Whether every file parsed, round-tripped byte for byte and matched its
+    /// reference fixture if it has one.
+    pub fn is_conformant(&self) -> bool {
+        self.files.iter().all(|file| {
+            file.outcome.as_ref().is_ok_and(|conformance| {
+                conformance.roundtrip_divergence.is_none()
+                    && !matches!(conformance.reference, ReferenceCheck::Mismatch(_))
+            })
+        })
+    }
+
+    /// `{"files":[{"path":..,"entries":..,"roundtrip_divergence":..|null,"reference":..}|{"path":..,"error":..}]}`,
+    /// where `reference` is `null` without a fixture, `"match"`, `"mismatch"` or
+    /// `"mismatch at index:sub_index"`.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"files\":[");
+        for (i, file) in self.files.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"path\":");
+            json::push_str(&mut out, &file.path.to_string_lossy());
+            match &file.outcome {
+                Ok(conformance) => {
+                    let _ = write!(
+                        out,
+                        ",\"entries\":{},\"roundtrip_divergence\":",
+                        conformance.entries
+                    );
+                    match conformance.roundtrip_divergence {
+                        Some(line) => {
+                            let _ = write!(out, "{}", line);
+                        }
+                        None => out.push_str("null"),
+                    }
+                    out.push_str(",\"reference\":");
+                    match conformance.reference {
+                        ReferenceCheck::NoFixture => out.push_str("null"),
+                        ReferenceCheck::Match => out.push_str("\"match\""),
+                        ReferenceCheck::Mismatch(None) => out.push_str("\"mismatch\""),
+                        ReferenceCheck::Mismatch(Some((index, sub_index))) => {
+                            let _ = write!(out, "\"mismatch at {}:{}\"", index, sub_index);
+                        }
+                    }
+                }
+                Err(err) => {
+                    out.push_str(",\"error\":");
+                    json::push_str(&mut out, err);
+                }
+            }
+            out.push('}');
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+/// Directories listed in [`CORPUS_ENV`], empty if it is unset.
+pub fn corpus_dirs_from_env() -> Vec<PathBuf> {
+    std::env::var_os(CORPUS_ENV)
+        .map(|dirs| std::env::split_paths(&dirs).collect())
+        .unwrap_or_default()
+}
+
+/// Parses and round-trips every `*.msg` file (case-insensitive) directly inside
+/// `dirs`, in path order, comparing with [`REFERENCE_SUFFIX`] fixtures where present.
+pub fn run_corpus<P: AsRef<Path>>(dirs: &[P]) -> Result<CorpusReport, String> {
+    let mut paths = vec![];
+    for dir in dirs {
+        let entries = std::fs::read_dir(dir).map_err(|err| format!("IoError: {}", err))?;
+        for entry in entries {
+            let path = entry.map_err(|err| format!("IoError: {}", err))?.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("msg"))
+            {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    let files = paths
+        .into_iter()
+        .map(|path| {
+            let outcome = check_file(&path);
+            CorpusFile { path, outcome }
+        })
+        .collect();
+    Ok(CorpusReport { files })
+}
+
+fn check_file(path: &Path) -> Result<Conformance, String> {
+    let bytes = compression::read_file(path).map_err(|err| err.to_string())?;
+    let dict = parse_msg_with(&bytes, LineDecoder::RawBytes).map_err(|err| err.to_string())?;
+    let document = MsgDocument::parse(&bytes).map_err(|err| err.to_string())?;
+    let roundtrip = roundtrip::compare(&bytes, &document.to_bytes());
+    Ok(Conformance {
+        entries: dict.len(),
+        roundtrip_divergence: roundtrip.divergence.map(|divergence| divergence.line),
+        reference: check_reference(path, &dict)?,
+    })
+}
+
+fn check_reference(path: &Path, dict: &MsgDictionary) -> Result<ReferenceCheck, String> {
+    let mut fixture = path.as_os_str().to_owned();
+    fixture.push(REFERENCE_SUFFIX);
+    let expected = match std::fs::read(&fixture) {
+        Ok(expected) => expected,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(ReferenceCheck::NoFixture);
+        }
+        Err(err) => return Err(format!("IoError: {}", err)),
+    };
+    let actual = dict.to_binary_cache();
+    if actual == expected {
+        return Ok(ReferenceCheck::Match);
+    }
+    // Read both back so values decode alike.
+    let actual = MsgDictionary::from_binary_cache(&actual)?;
+    let key = MsgDictionary::from_binary_cache(&expected)
+        .ok()
+        .and_then(|reference| {
+            reference
+                .diff(&actual)
+                .entries
+                .first()
+                .map(|entry| entry.key())
+        });
+    Ok(ReferenceCheck::Mismatch(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_on_directory() {
+        let dir = std::env::temp_dir().join(format!("fo_msg_corpus_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("A.MSG"),
+            b"# items\r\n{1}{}{a}  // first\r\n{2}{}{b}",
+        )
+        .unwrap();
+        std::fs::write(dir.join("b.msg"), b"{1}{}{unclosed\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+        std::fs::write(dir.join("c.msg"), b"{1}{}{a}\n{2}{}{b}\n").unwrap();
+        let reference = parse_msg_with(b"{1}{}{a}\n{2}{}{B}\n", LineDecoder::RawBytes).unwrap();
+        std::fs::write(dir.join("A.MSG.ref"), reference.to_binary_cache()).unwrap();
+        std::fs::write(dir.join("c.msg.ref"), b"junk").unwrap();
+
+        let report = run_corpus(&[&dir]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(
+            report.files[0].outcome,
+            Ok(Conformance {
+                entries: 2,
+                roundtrip_divergence: None,
+                reference: ReferenceCheck::Mismatch(Some((2, 0))),
+            })
+        );
+        assert!(report.files[1].outcome.is_err());
+        assert_eq!(
+            report.files[2].outcome.as_ref().unwrap().reference,
+            ReferenceCheck::Mismatch(None)
+        );
+        assert!(!report.all_parsed());
+        assert!(report.to_json().contains(
+            "\"entries\":2,\"roundtrip_divergence\":null,\"reference\":\"mismatch at 2:0\"}"
+        ));
+    }
+}
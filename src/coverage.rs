@@ -0,0 +1,165 @@
+use std::{collections::btree_map::BTreeMap, fmt::Write};
+
+use super::{LanguagePack, MsgDictionary, ReviewState, StateCoverage, json};
+
+/// Version of the [`Coverage::to_json`] layout, bumped on incompatible changes.
+pub const COVERAGE_SCHEMA_VERSION: u32 = 1;
+
+/// How many keys of a base file a translation has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileCoverage {
+    pub total: usize,
+    pub translated: usize,
+    /// Keys of the base file by review state in the translation.
+    pub states: StateCoverage,
+}
+
+impl FileCoverage {
+    pub fn of(base: &MsgDictionary, translation: &MsgDictionary) -> Self {
+        let mut coverage = Self {
+            states: StateCoverage::of(base, translation),
+            ..Self::default()
+        };
+        for (key, _value) in base.index_to_line.iter() {
+            coverage.total += 1;
+            if translation.index_to_line.get(key).is_some() {
+                coverage.translated += 1;
+            }
+        }
+        coverage
+    }
+
+    pub fn missing(&self) -> usize {
+        self.total - self.translated
+    }
+
+    /// Translated share in percent, 100 for empty files.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.translated as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+/// Per-file coverage of a translation against a base language, see
+/// [`LanguagePack::coverage`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Coverage {
+    pub files: BTreeMap<String, FileCoverage>,
+}
+
+impl Coverage {
+    pub fn overall(&self) -> FileCoverage {
+        self.files
+            .values()
+            .fold(FileCoverage::default(), |sum, file| FileCoverage {
+                total: sum.total + file.total,
+                translated: sum.translated + file.translated,
+                states: sum.states.add(&file.states),
+            })
+    }
+
+    /// Summary for dashboards:
+    /// `{"schema":1,"overall":{..},"files":[{"name":..,"total":..,"translated":..,"missing":..,"percent":..,"states":{"untranslated":..,..}}]}`,
+    /// with percentages rounded to two decimals.
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"schema\":{},\"overall\":{{", COVERAGE_SCHEMA_VERSION);
+        push_counts(&mut out, &self.overall());
+        out.push_str("},\"files\":[");
+        for (i, (name, file)) in self.files.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"name\":");
+            json::push_str(&mut out, name);
+            out.push(',');
+            push_counts(&mut out, file);
+            out.push('}');
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+/// Appends the `"total":..,"translated":..,"missing":..,"percent":..,"states":{..}` fields.
+fn push_counts(out: &mut String, coverage: &FileCoverage) {
+    let _ = write!(
+        out,
+        "\"total\":{},\"translated\":{},\"missing\":{},\"percent\":{:.2}",
+        coverage.total,
+        coverage.translated,
+        coverage.missing(),
+        coverage.percent()
+    );
+    out.push_str(",\"states\":{");
+    for (i, state) in ReviewState::ALL.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "\"{}\":{}", state, coverage.states.count(*state));
+    }
+    out.push('}');
+}
+
+impl LanguagePack {
+    /// Coverage of `self` as a translation of `base`, for every file of `base`.
+    pub fn coverage(&self, base: &LanguagePack) -> Coverage {
+        let empty = MsgDictionary::new();
+        let files = base
+            .iter()
+            .map(|(name, dict)| {
+                let translation = self.get(name).unwrap_or(&empty);
+                (name.to_owned(), FileCoverage::of(dict, translation))
+            })
+            .collect();
+        Coverage { files }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn coverage_json() {
+        let mut base = LanguagePack::new();
+        base.insert("FOGM.MSG", parse_msg(b"{10}{}{Map}\n").unwrap());
+        base.insert(
+            "FOTEXT.MSG",
+            parse_msg(b"{1}{}{a}\n{2}{}{b}\n{3}{}{c}\n").unwrap(),
+        );
+        let mut translation = LanguagePack::new();
+        translation.insert(
+            "FOTEXT.MSG",
+            parse_msg(b"{1}{}{A}\n{3}{}{C}\n{4}{}{D}\n").unwrap(),
+        );
+
+        let coverage = translation.coverage(&base);
+        assert_eq!(
+            coverage.overall(),
+            FileCoverage {
+                total: 4,
+                translated: 2,
+                states: StateCoverage {
+                    untranslated: 2,
+                    translated: 2,
+                    ..StateCoverage::default()
+                },
+            }
+        );
+        assert_eq!(
+            coverage.to_json(),
+            "{\"schema\":1,\
+             \"overall\":{\"total\":4,\"translated\":2,\"missing\":2,\"percent\":50.00,\
+             \"states\":{\"untranslated\":2,\"translated\":2,\"reviewed\":0,\"approved\":0}},\
+             \"files\":[\
+             {\"name\":\"FOGM.MSG\",\"total\":1,\"translated\":0,\"missing\":1,\"percent\":0.00,\
+             \"states\":{\"untranslated\":1,\"translated\":0,\"reviewed\":0,\"approved\":0}},\
+             {\"name\":\"FOTEXT.MSG\",\"total\":3,\"translated\":2,\"missing\":1,\"percent\":66.67,\
+             \"states\":{\"untranslated\":1,\"translated\":2,\"reviewed\":0,\"approved\":0}}]}"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use super::MsgLine;
+
+/// Built-in conversions from raw value bytes to [`MsgLine`], see [`crate::parse_msg_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LineDecoder {
+    /// Valid UTF-8 becomes a string, anything else is kept as bytes.
+    #[default]
+    Utf8Strict,
+    /// Invalid UTF-8 sequences are replaced with U+FFFD.
+    Utf8Lossy,
+    /// Decode with the given encoding, keeping bytes that are malformed in it.
+    #[cfg(any(test, feature = "cp1251"))]
+    Encoding(&'static encoding_rs::Encoding),
+    /// Keep every value as bytes.
+    RawBytes,
+}
+
+impl LineDecoder {
+    pub fn decode(&self, bytes: &[u8]) -> MsgLine {
+        match self {
+            LineDecoder::Utf8Strict => match std::str::from_utf8(bytes) {
+                Ok(str) => MsgLine::String(str.into()),
+                Err(_) => MsgLine::Bytes(bytes.into()),
+            },
+            LineDecoder::Utf8Lossy => MsgLine::String(String::from_utf8_lossy(bytes).into()),
+            #[cfg(any(test, feature = "cp1251"))]
+            LineDecoder::Encoding(encoding) => {
+                match encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+                    Some(cow) => MsgLine::String(cow.into()),
+                    None => MsgLine::Bytes(bytes.into()),
+                }
+            }
+            LineDecoder::RawBytes => MsgLine::Bytes(bytes.into()),
+        }
+    }
+
+    /// Like [`LineDecoder::decode`], but rejects values that are malformed in the
+    /// decoder's encoding instead of keeping them as bytes or replacing characters.
+    pub fn decode_strict(&self, bytes: &[u8]) -> Result<MsgLine, DecodeError> {
+        match self {
+            LineDecoder::Utf8Strict | LineDecoder::Utf8Lossy => match std::str::from_utf8(bytes) {
+                Ok(str) => Ok(MsgLine::String(str.into())),
+                Err(err) => Err(DecodeError {
+                    valid_up_to: err.valid_up_to(),
+                }),
+            },
+            #[cfg(any(test, feature = "cp1251"))]
+            LineDecoder::Encoding(encoding) => {
+                match encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+                    Some(cow) => Ok(MsgLine::String(cow.into())),
+                    None => Err(DecodeError {
+                        valid_up_to: malformed_offset(encoding, bytes),
+                    }),
+                }
+            }
+            LineDecoder::RawBytes => Ok(MsgLine::Bytes(bytes.into())),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "cp1251"))]
+fn malformed_offset(encoding: &'static encoding_rs::Encoding, bytes: &[u8]) -> usize {
+    use encoding_rs::DecoderResult;
+
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut sink = String::with_capacity(1024);
+    let mut read = 0;
+    loop {
+        sink.clear();
+        let (result, consumed) =
+            decoder.decode_to_string_without_replacement(&bytes[read..], &mut sink, true);
+        read += consumed;
+        match result {
+            DecoderResult::Malformed(bad, after) => return read - bad as usize - after as usize,
+            DecoderResult::OutputFull => continue,
+            DecoderResult::InputEmpty => return bytes.len(),
+        }
+    }
+}
+
+/// Value is malformed in the requested encoding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecodeError {
+    /// Offset of the first malformed byte within the value.
+    pub valid_up_to: usize,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "malformed byte at value offset {}", self.valid_up_to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INVALID_UTF8: &[u8] = b"\xcf\xf0\xe8\xe2\xe5\xf2";
+
+    #[test]
+    fn utf8_decoders() {
+        assert_eq!(
+            LineDecoder::Utf8Strict.decode(b"foo"),
+            MsgLine::String("foo".into())
+        );
+        assert_eq!(
+            LineDecoder::Utf8Strict.decode(INVALID_UTF8),
+            MsgLine::Bytes(INVALID_UTF8.into())
+        );
+        assert_eq!(
+            LineDecoder::Utf8Lossy.decode(b"a\xffb"),
+            MsgLine::String("a\u{fffd}b".into())
+        );
+        assert_eq!(
+            LineDecoder::RawBytes.decode(b"foo"),
+            MsgLine::Bytes(b"foo"[..].into())
+        );
+    }
+
+    #[test]
+    fn strict_decoding() {
+        assert_eq!(
+            LineDecoder::Utf8Lossy.decode_strict(b"ab\xffc"),
+            Err(DecodeError { valid_up_to: 2 })
+        );
+        assert_eq!(
+            LineDecoder::RawBytes.decode_strict(INVALID_UTF8),
+            Ok(MsgLine::Bytes(INVALID_UTF8.into()))
+        );
+    }
+
+    #[test]
+    fn cp1251_decoder() {
+        let decoder = LineDecoder::Encoding(encoding_rs::WINDOWS_1251);
+        assert_eq!(
+            decoder.decode(INVALID_UTF8),
+            MsgLine::String("Привет".into())
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::{collections::btree_map::BTreeMap, sync::Arc};
+
+use super::{MsgDictionary, MsgLine, MsgProvider, MsgRead};
+
+/// Changes on top of a shared base dictionary, for per-server or per-event text
+/// that differs in a handful of indices.
+///
+/// Overrides replace all values of an index, sub-indices of overridden indices
+/// count from zero.
+#[derive(Debug, Clone)]
+pub struct DeltaDict {
+    base: Arc<MsgDictionary>,
+    /// Values replacing those of the base, empty for removed indices.
+    overrides: BTreeMap<u32, Vec<MsgLine>>,
+}
+
+impl DeltaDict {
+    pub fn new(base: Arc<MsgDictionary>) -> Self {
+        Self {
+            base,
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    pub fn base(&self) -> &Arc<MsgDictionary> {
+        &self.base
+    }
+
+    /// Replaces all values of `index` with `value`.
+    pub fn set(&mut self, index: u32, value: MsgLine) {
+        self.set_all(index, vec![value]);
+    }
+
+    pub fn set_all(&mut self, index: u32, values: Vec<MsgLine>) {
+        self.overrides.insert(index, values);
+    }
+
+    /// Hides `index` of the base.
+    pub fn remove(&mut self, index: u32) {
+        self.overrides.insert(index, vec![]);
+    }
+
+    /// Drops the override of `index`, exposing the base values again.
+    pub fn reset(&mut self, index: u32) {
+        self.overrides.remove(&index);
+    }
+
+    /// Indices that differ from the base.
+    pub fn changed_indices(&self) -> impl Iterator<Item = u32> + '_ {
+        self.overrides.keys().copied()
+    }
+
+    pub fn get_first_string(&self, index: u32) -> Option<&str> {
+        self.get_string(index, 0)
+    }
+
+    pub fn get_first_bytes(&self, index: u32) -> Option<&[u8]> {
+        self.get_bytes(index, 0)
+    }
+
+    fn get(&self, index: u32, sub_index: u32) -> Option<&MsgLine> {
+        match self.overrides.get(&index) {
+            Some(values) => values.get(sub_index as usize),
+            None => self.base.lookup((index, sub_index), None),
+        }
+    }
+
+    /// Base with the changes applied, as a standalone dictionary.
+    pub fn to_dictionary(&self) -> MsgDictionary {
+        let mut dict = MsgDictionary::new();
+        for (&(index, sub_index), value) in self.base.index_to_line.iter() {
+            if !self.overrides.contains_key(&index) {
+                dict.index_to_line.insert((index, sub_index), value.clone());
+            }
+        }
+        for (&index, values) in &self.overrides {
+            for value in values {
+                dict.insert(index, value.clone());
+            }
+        }
+        dict
+    }
+}
+
+impl MsgRead for DeltaDict {
+    fn get_string(&self, index: u32, sub_index: u32) -> Option<&str> {
+        self.get(index, sub_index)?.string()
+    }
+
+    fn get_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        Some(self.get(index, sub_index)?.bytes())
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        match self.overrides.get(&index) {
+            Some(values) => values.len() as u32,
+            None => self.base.count(index),
+        }
+    }
+}
+
+impl MsgProvider for DeltaDict {
+    fn get_str_nth(&self, index: u32, skip: u32) -> Option<&str> {
+        match self.overrides.get(&index) {
+            Some(values) => values.get(skip as usize)?.string(),
+            None => self.base.get_str_nth(index, skip),
+        }
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        MsgRead::count(self, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn overrides_base() {
+        let base = Arc::new(parse_msg(b"{1}{}{Hello}\n{2}{}{a}\n{2}{}{b}\n{3}{}{Bye}\n").unwrap());
+        let mut event = DeltaDict::new(base.clone());
+        event.set(1, MsgLine::String("Happy holidays".into()));
+        event.remove(3);
+
+        assert_eq!(event.get_first_string(1), Some("Happy holidays"));
+        assert_eq!(event.get_str_nth(2, 1), Some("b"));
+        assert_eq!(MsgRead::count(&event, 3), 0);
+        assert_eq!(base.get_first_string(1), Some("Hello"));
+        assert_eq!(event.changed_indices().collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(
+            event.to_dictionary(),
+            parse_msg(b"{1}{}{Happy holidays}\n{2}{}{a}\n{2}{}{b}\n").unwrap()
+        );
+
+        event.reset(3);
+        assert_eq!(event.get_first_string(3), Some("Bye"));
+    }
+
+    #[test]
+    fn follows_base_aliases() {
+        let base = Arc::new(parse_msg(b"#= alias 1 -> 2\n{2}{}{a}\n{2}{}{b}\n").unwrap());
+        let event = DeltaDict::new(base);
+        assert_eq!(event.get_first_string(1), Some("a"));
+        assert_eq!(MsgRead::get_string(&event, 1, 1), Some("b"));
+        assert_eq!(MsgRead::count(&event, 1), 2);
+    }
+}
@@ -0,0 +1,75 @@
+use super::MsgDictionary;
+
+/// Trailing comment that marks an entry's index as deprecated when parsing,
+/// optionally followed by `:` and a note, e.g. `{100}{}{Old}# deprecated: use 2100`.
+pub(crate) const ANNOTATION: &str = "deprecated";
+
+impl MsgDictionary {
+    /// Marks `index` as deprecated. Lookups of it are then reported to the
+    /// lookup hook along with `note`, e.g. where the text moved to.
+    pub fn deprecate(&mut self, index: u32, note: &str) {
+        self.deprecated.insert(index, note.into());
+    }
+
+    pub fn undeprecate(&mut self, index: u32) {
+        self.deprecated.remove(&index);
+    }
+
+    /// Note of a deprecated `index`, empty if none was given.
+    pub fn deprecation(&self, index: u32) -> Option<&str> {
+        self.deprecated.get(&index).map(|note| &**note)
+    }
+
+    pub fn deprecated_indices(&self) -> impl Iterator<Item = u32> + '_ {
+        self.deprecated.keys().copied()
+    }
+}
+
+/// Note of a trailing comment holding the deprecation annotation.
+pub(crate) fn annotation(comment: &[u8]) -> Option<String> {
+    let comment = String::from_utf8_lossy(comment);
+    let rest = comment.trim().strip_prefix(ANNOTATION)?;
+    if rest.is_empty() {
+        return Some(String::new());
+    }
+    Some(rest.strip_prefix(':')?.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{LookupEventKind, parse_msg};
+
+    #[test]
+    fn parse_annotation() {
+        assert_eq!(annotation(b" deprecated"), Some(String::new()));
+        assert_eq!(
+            annotation(b"deprecated: use 2100 "),
+            Some("use 2100".into())
+        );
+        assert_eq!(annotation(b"deprecatedness"), None);
+        assert_eq!(annotation(b"todo"), None);
+    }
+
+    #[test]
+    fn deprecated_lookups_warn() {
+        let mut dict = parse_msg(b"{1}{}{Old}# deprecated: use 2\n{2}{}{New}\n").unwrap();
+        assert_eq!(dict.deprecation(1), Some("use 2"));
+        assert_eq!(dict.deprecation(2), None);
+
+        let warnings = Arc::new(Mutex::new(vec![]));
+        let sink = warnings.clone();
+        dict.set_lookup_hook(move |event| {
+            if let LookupEventKind::Deprecated { note } = event.kind {
+                sink.lock().unwrap().push((event.index, note.to_owned()));
+            }
+        });
+        assert_eq!(dict.get_first_string(1), Some("Old"));
+        assert_eq!(dict.get_first_string(2), Some("New"));
+        dict.undeprecate(1);
+        dict.get_first_string(1);
+        assert_eq!(*warnings.lock().unwrap(), vec![(1, "use 2".to_owned())]);
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::btree_map::BTreeMap;
+
+use super::{LineDecoder, MsgDictionary, parse_msg_with};
+
+/// Index of text `idx` of dialog `dlg_id` in the game's dialog MSG file.
+pub const fn dlg_str_id(dlg_id: u32, idx: u32) -> u32 {
+    dlg_id * 1000 + idx
+}
+
+/// Texts of a `.fodlg` dialog source, one dictionary per language section,
+/// keyed by [`dlg_str_id`].
+///
+/// Languages are taken from `lang=` in the `[data]` section.
+pub fn extract_dialog_texts(
+    source: &[u8],
+    dlg_id: u32,
+    decoder: LineDecoder,
+) -> Result<BTreeMap<String, MsgDictionary>, String> {
+    let sections = sections(source);
+    let data = sections
+        .iter()
+        .find(|&&(name, _body)| name == b"data")
+        .ok_or("No [data] section")?;
+    let languages = data
+        .1
+        .split(|&byte| byte == b'\n')
+        .find_map(|line| line.trim_ascii().strip_prefix(b"lang="))
+        .ok_or("No lang= in [data] section")?;
+
+    let mut texts = BTreeMap::new();
+    for lang in languages
+        .split(u8::is_ascii_whitespace)
+        .filter(|lang| !lang.is_empty())
+    {
+        let lang = String::from_utf8_lossy(lang).into_owned();
+        let body = sections
+            .iter()
+            .find(|&&(name, _body)| name == lang.as_bytes())
+            .map(|&(_name, body)| body)
+            .ok_or_else(|| format!("No [{}] section", lang))?;
+        let local = parse_msg_with(body, decoder).map_err(|err| format!("[{}]: {}", lang, err))?;
+        let mut dict = MsgDictionary::new();
+        for (&(idx, _sub_index), value) in local.index_to_line.iter() {
+            if idx >= 1000 {
+                return Err(format!(
+                    "[{}]: text index {} doesn't fit a dialog",
+                    lang, idx
+                ));
+            }
+            dict.insert(dlg_str_id(dlg_id, idx), value.clone());
+        }
+        texts.insert(lang, dict);
+    }
+    Ok(texts)
+}
+
+/// `[name]` sections with their bodies, up to the next header.
+fn sections(source: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut sections: Vec<(&[u8], &[u8])> = vec![];
+    let mut pos = 0;
+    let mut current: Option<(&[u8], usize)> = None;
+    while pos < source.len() {
+        let end = source[pos..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(source.len(), |len| pos + len + 1);
+        let line = source[pos..end].trim_ascii();
+        if let Some(name) = line
+            .strip_prefix(b"[")
+            .and_then(|line| line.strip_suffix(b"]"))
+        {
+            if let Some((name, start)) = current {
+                sections.push((name, &source[start..pos]));
+            }
+            current = Some((name, end));
+        }
+        pos = end;
+    }
+    if let Some((name, start)) = current {
+        sections.push((name, &source[start..]));
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract() {
+        let source = b"[comment]\nGuard\n[data]\nlang=russ engl\n[dialog]\n&\n1 1000 None 0 #\n[russ]\n{100}{}{\xcf\xf0\xe8\xe2\xe5\xf2}\n[engl]\r\n{100}{}{Hello}\r\n{200}{}{Bye}\r\n";
+        let texts = extract_dialog_texts(source, 12, LineDecoder::Utf8Strict).unwrap();
+        assert_eq!(texts.keys().collect::<Vec<_>>(), vec!["engl", "russ"]);
+        let engl = &texts["engl"];
+        assert_eq!(engl.get_first_string(12_100), Some("Hello"));
+        assert_eq!(engl.get_first_string(12_200), Some("Bye"));
+        assert_eq!(
+            texts["russ"].get_first_bytes(12_100),
+            Some(&b"\xcf\xf0\xe8\xe2\xe5\xf2"[..])
+        );
+        assert!(extract_dialog_texts(b"[data]\nlang=engl\n", 1, LineDecoder::Utf8Strict).is_err());
+    }
+}
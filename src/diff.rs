@@ -0,0 +1,371 @@
+use std::{borrow::Cow, fmt::Write};
+
+use super::{MsgDictionary, MsgLine, case, json, merge::lcs_pairs};
+
+/// Single difference between two dictionaries, keyed by `(index, sub_index)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffEntry<'a> {
+    Added {
+        key: (u32, u32),
+        new: &'a MsgLine,
+    },
+    Removed {
+        key: (u32, u32),
+        old: &'a MsgLine,
+    },
+    Changed {
+        key: (u32, u32),
+        old: &'a MsgLine,
+        new: &'a MsgLine,
+    },
+}
+
+impl DiffEntry<'_> {
+    pub fn key(&self) -> (u32, u32) {
+        match *self {
+            DiffEntry::Added { key, .. }
+            | DiffEntry::Removed { key, .. }
+            | DiffEntry::Changed { key, .. } => key,
+        }
+    }
+}
+
+/// Differences between two dictionaries in key order, see [`MsgDictionary::diff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MsgDiff<'a> {
+    pub entries: Vec<DiffEntry<'a>>,
+}
+
+impl MsgDictionary {
+    /// Lists what changed going from `self` to `new`.
+    pub fn diff<'a>(&'a self, new: &'a MsgDictionary) -> MsgDiff<'a> {
+        let mut entries = vec![];
+        let mut old_iter = self.index_to_line.iter().peekable();
+        let mut new_iter = new.index_to_line.iter().peekable();
+        loop {
+            let entry = match (old_iter.peek(), new_iter.peek()) {
+                (None, None) => break,
+                (Some(&(&key, old)), None) => {
+                    old_iter.next();
+                    DiffEntry::Removed { key, old }
+                }
+                (None, Some(&(&key, new))) => {
+                    new_iter.next();
+                    DiffEntry::Added { key, new }
+                }
+                (Some(&(&old_key, old)), Some(&(&new_key, new))) => {
+                    if old_key < new_key {
+                        old_iter.next();
+                        DiffEntry::Removed { key: old_key, old }
+                    } else if new_key < old_key {
+                        new_iter.next();
+                        DiffEntry::Added { key: new_key, new }
+                    } else {
+                        old_iter.next();
+                        new_iter.next();
+                        if old == new {
+                            continue;
+                        }
+                        DiffEntry::Changed {
+                            key: old_key,
+                            old,
+                            new,
+                        }
+                    }
+                }
+            };
+            entries.push(entry);
+        }
+        MsgDiff { entries }
+    }
+}
+
+/// Value for display, byte values decoded as CP1251.
+pub(crate) fn text(value: &MsgLine) -> Cow<'_, str> {
+    case::cp1251_string(value).unwrap_or_else(|| String::from_utf8_lossy(value.bytes()))
+}
+
+fn push_prefixed(out: &mut String, prefix: &str, value: &MsgLine) {
+    for line in text(value).split('\n') {
+        out.push_str(prefix);
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+impl MsgDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Unified-diff-like text: an `@@ index:sub_index @@` header per entry,
+    /// then old value lines prefixed with `-` and new ones with `+`.
+    pub fn render_unified(&self) -> String {
+        let mut out = String::new();
+        for entry in &self.entries {
+            let (index, sub_index) = entry.key();
+            let _ = writeln!(out, "@@ {}:{} @@", index, sub_index);
+            match entry {
+                DiffEntry::Added { new, .. } => push_prefixed(&mut out, "+", new),
+                DiffEntry::Removed { old, .. } => push_prefixed(&mut out, "-", old),
+                DiffEntry::Changed { old, new, .. } => {
+                    push_prefixed(&mut out, "-", old);
+                    push_prefixed(&mut out, "+", new);
+                }
+            }
+        }
+        out
+    }
+
+    /// JSON array of `{"op", "index", "sub_index", "old"?, "new"?}` objects.
+    pub fn render_json(&self) -> String {
+        let mut out = String::from("[");
+        for (pos, entry) in self.entries.iter().enumerate() {
+            if pos > 0 {
+                out.push(',');
+            }
+            let (index, sub_index) = entry.key();
+            let (op, old, new) = match entry {
+                DiffEntry::Added { new, .. } => ("added", None, Some(new)),
+                DiffEntry::Removed { old, .. } => ("removed", Some(old), None),
+                DiffEntry::Changed { old, new, .. } => ("changed", Some(old), Some(new)),
+            };
+            let _ = write!(
+                out,
+                "{{\"op\":\"{}\",\"index\":{},\"sub_index\":{}",
+                op, index, sub_index
+            );
+            if let Some(old) = old {
+                out.push_str(",\"old\":");
+                json::push_str(&mut out, &text(old));
+            }
+            if let Some(new) = new {
+                out.push_str(",\"new\":");
+                json::push_str(&mut out, &text(new));
+            }
+            out.push('}');
+        }
+        out.push(']');
+        out
+    }
+
+    /// HTML table with old and new values side by side, characters that differ
+    /// wrapped in `<del>` and `<ins>`.
+    pub fn render_html(&self) -> String {
+        let mut out = String::from(
+            "<table class=\"msg-diff\">\n<tr><th>Key</th><th>Old</th><th>New</th></tr>\n",
+        );
+        for entry in &self.entries {
+            let (index, sub_index) = entry.key();
+            let (old, new) = match entry {
+                DiffEntry::Added { new, .. } => (String::new(), html_mark(&text(new), "ins")),
+                DiffEntry::Removed { old, .. } => (html_mark(&text(old), "del"), String::new()),
+                DiffEntry::Changed { old, new, .. } => html_char_diff(&text(old), &text(new)),
+            };
+            let _ = writeln!(
+                out,
+                "<tr><td>{}:{}</td><td>{}</td><td>{}</td></tr>",
+                index, sub_index, old, new
+            );
+        }
+        out.push_str("</table>\n");
+        out
+    }
+
+    /// MSG file with the new values, old values kept as `#` comments above them.
+    /// Comments name the `index:sub_index` of each change.
+    ///
+    /// Values are written as stored, so byte values keep their encoding.
+    pub fn render_msg(&self) -> Vec<u8> {
+        let mut out = vec![];
+        let push_entry = |out: &mut Vec<u8>, index: u32, value: &MsgLine| {
+            out.extend_from_slice(format!("{{{}}}{{}}{{", index).as_bytes());
+            out.extend_from_slice(value.bytes());
+            out.extend_from_slice(b"}\n");
+        };
+        let push_commented = |out: &mut Vec<u8>, value: &MsgLine| {
+            for line in value.bytes().split(|&byte| byte == b'\n') {
+                out.extend_from_slice(b"# ");
+                out.extend_from_slice(line);
+                out.push(b'\n');
+            }
+        };
+        for entry in &self.entries {
+            let (index, sub_index) = entry.key();
+            match entry {
+                DiffEntry::Added { new, .. } => {
+                    out.extend_from_slice(format!("# added {}:{}\n", index, sub_index).as_bytes());
+                    push_entry(&mut out, index, new);
+                }
+                DiffEntry::Removed { old, .. } => {
+                    out.extend_from_slice(
+                        format!("# removed {}:{}:\n", index, sub_index).as_bytes(),
+                    );
+                    push_commented(&mut out, old);
+                }
+                DiffEntry::Changed { old, new, .. } => {
+                    out.extend_from_slice(format!("# old {}:{}:\n", index, sub_index).as_bytes());
+                    push_commented(&mut out, old);
+                    push_entry(&mut out, index, new);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn push_html(out: &mut String, ch: char) {
+    match ch {
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '&' => out.push_str("&amp;"),
+        '"' => out.push_str("&quot;"),
+        '\n' => out.push_str("<br>"),
+        ch => out.push(ch),
+    }
+}
+
+fn html_mark(value: &str, tag: &str) -> String {
+    let mut out = format!("<{}>", tag);
+    value.chars().for_each(|ch| push_html(&mut out, ch));
+    let _ = write!(out, "</{}>", tag);
+    out
+}
+
+/// Escaped `chars`, with those not in `common` wrapped in `tag`.
+fn html_highlight(chars: &[char], common: &[bool], tag: &str) -> String {
+    let mut out = String::new();
+    let mut marked = false;
+    for (&ch, &common) in chars.iter().zip(common) {
+        if marked == common {
+            let _ = if common {
+                write!(out, "</{}>", tag)
+            } else {
+                write!(out, "<{}>", tag)
+            };
+            marked = !common;
+        }
+        push_html(&mut out, ch);
+    }
+    if marked {
+        let _ = write!(out, "</{}>", tag);
+    }
+    out
+}
+
+fn html_char_diff(old: &str, new: &str) -> (String, String) {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    let mut old_common = vec![false; old.len()];
+    let mut new_common = vec![false; new.len()];
+    for (o, n) in lcs_pairs(&old, &new) {
+        old_common[o] = true;
+        new_common[n] = true;
+    }
+    (
+        html_highlight(&old, &old_common, "del"),
+        html_highlight(&new, &new_common, "ins"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    fn sample() -> (MsgDictionary, MsgDictionary) {
+        (
+            parse_msg(b"{10}{}{Same}\n{15}{}{Old}\n{20}{}{Gone}").unwrap(),
+            parse_msg(b"{10}{}{Same}\n{15}{}{New}\n{30}{}{Added}").unwrap(),
+        )
+    }
+
+    #[test]
+    fn diff_entries() {
+        let (old, new) = sample();
+        let keys: Vec<_> = old.diff(&new).entries.iter().map(DiffEntry::key).collect();
+        assert_eq!(keys, vec![(15, 0), (20, 0), (30, 0)]);
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn renderers() {
+        let (old, new) = sample();
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.render_unified(),
+            "@@ 15:0 @@\n-Old\n+New\n@@ 20:0 @@\n-Gone\n@@ 30:0 @@\n+Added\n"
+        );
+        assert_eq!(
+            diff.render_json(),
+            concat!(
+                r#"[{"op":"changed","index":15,"sub_index":0,"old":"Old","new":"New"},"#,
+                r#"{"op":"removed","index":20,"sub_index":0,"old":"Gone"},"#,
+                r#"{"op":"added","index":30,"sub_index":0,"new":"Added"}]"#
+            )
+        );
+        assert_eq!(
+            diff.render_msg(),
+            b"# old 15:0:\n# Old\n{15}{}{New}\n# removed 20:0:\n# Gone\n# added 30:0\n{30}{}{Added}\n"
+        );
+    }
+
+    // Привет -> Пока, stored as CP1251 bytes.
+    fn cp1251_sample() -> (MsgDictionary, MsgDictionary) {
+        (
+            parse_msg(b"{1}{}{a}\n{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2}").unwrap(),
+            parse_msg(b"{1}{}{a}\n{1}{}{\xcf\xee\xea\xe0}").unwrap(),
+        )
+    }
+
+    #[test]
+    fn unified_bytes() {
+        let (old, new) = cp1251_sample();
+        assert_eq!(
+            old.diff(&new).render_unified(),
+            "@@ 1:1 @@\n-Привет\n+Пока\n"
+        );
+    }
+
+    #[test]
+    fn json_bytes() {
+        let (old, new) = cp1251_sample();
+        assert_eq!(
+            old.diff(&new).render_json(),
+            r#"[{"op":"changed","index":1,"sub_index":1,"old":"Привет","new":"Пока"}]"#
+        );
+    }
+
+    #[test]
+    fn html_bytes() {
+        let (old, new) = cp1251_sample();
+        assert!(
+            old.diff(&new).render_html().contains(
+                "<tr><td>1:1</td><td>П<del>ривет</del></td><td>П<ins>ока</ins></td></tr>"
+            )
+        );
+    }
+
+    #[test]
+    fn msg_bytes() {
+        let (old, new) = cp1251_sample();
+        assert_eq!(
+            old.diff(&new).render_msg(),
+            b"# old 1:1:\n# \xcf\xf0\xe8\xe2\xe5\xf2\n{1}{}{\xcf\xee\xea\xe0}\n"
+        );
+    }
+
+    #[test]
+    fn html_side_by_side() {
+        let old = parse_msg(b"{1}{}{Hello <world>}\n{2}{}{Gone}").unwrap();
+        let new = parse_msg(b"{1}{}{Hallo <world>!}\n").unwrap();
+        assert_eq!(
+            old.diff(&new).render_html(),
+            "<table class=\"msg-diff\">\n<tr><th>Key</th><th>Old</th><th>New</th></tr>\n\
+             <tr><td>1:0</td><td>H<del>e</del>llo &lt;world&gt;</td>\
+             <td>H<ins>a</ins>llo &lt;world&gt;<ins>!</ins></td></tr>\n\
+             <tr><td>2:0</td><td><del>Gone</del></td><td></td></tr>\n\
+             </table>\n"
+        );
+    }
+}
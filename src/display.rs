@@ -0,0 +1,70 @@
+/// Columns `value` takes in a fixed-width UI: wide (CJK) characters take two,
+/// combining marks and control characters none.
+pub fn display_width(value: &str) -> usize {
+    value.chars().map(char_width).sum()
+}
+
+/// Longest prefix of `value` that fits into `max_cols` columns.
+///
+/// `@...@` placeholders are kept whole or dropped whole, so the engine never sees
+/// half of one.
+pub fn truncate_display(value: &str, max_cols: usize) -> &str {
+    let mut cols = 0;
+    let mut end = 0;
+    let mut placeholder_start = None;
+    for (pos, ch) in value.char_indices() {
+        cols += char_width(ch);
+        if cols > max_cols {
+            return &value[..placeholder_start.unwrap_or(end)];
+        }
+        end = pos + ch.len_utf8();
+        if ch == '@' {
+            placeholder_start = match placeholder_start {
+                Some(_) => None,
+                None => Some(pos),
+            };
+        }
+    }
+    value
+}
+
+fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x00..=0x1f | 0x7f..=0x9f => 0,
+        0x0300..=0x036f | 0x0483..=0x0489 | 0x200b..=0x200f | 0xfe00..=0xfe0f => 0,
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        assert_eq!(display_width("Привет"), 6);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn truncate() {
+        assert_eq!(truncate_display("Привет, мир", 6), "Привет");
+        assert_eq!(truncate_display("日本語", 5), "日本");
+        assert_eq!(truncate_display("short", 10), "short");
+        assert_eq!(truncate_display("Hi @pname@!", 6), "Hi ");
+        assert_eq!(truncate_display("Hi @pname@!", 10), "Hi @pname@");
+        assert_eq!(truncate_display("a@b@c@d", 6), "a@b@c");
+    }
+}
@@ -0,0 +1,450 @@
+use std::{
+    collections::btree_map::BTreeMap,
+    io::{self, Write},
+    ops::Range,
+};
+
+use super::{
+    Entry, Line, LineDecoder, Msg, MsgDictionary, ParseError, ParseMetrics, SecondaryKeyPolicy,
+    collect_entries, scanner::Scanner,
+};
+
+/// MSG file keeping every line as written, so it serializes back byte for byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsgDocument {
+    pub(crate) lines: Vec<DocLine>,
+}
+
+/// Line of a [`MsgDocument`]. An entry whose value spans lines is one `DocLine`.
+///
+/// Lines of an `ArenaDocument` (`arena` feature) borrow their bytes from the arena.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocLine<R = Box<[u8]>> {
+    raw: R,
+    ending: &'static [u8],
+    kind: DocLineKind,
+}
+
+/// What a [`DocLine`] holds, with ranges into [`DocLine::raw`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocLineKind {
+    Entry {
+        index: u32,
+        sub_index: u32,
+        /// Text between the second braces, empty unless the entry has a secondary key.
+        secondary: Range<usize>,
+        value: Range<usize>,
+        /// Trailing comment text, without the marker.
+        comment: Option<Range<usize>>,
+    },
+    /// Comment text, without the marker.
+    Comment(Range<usize>),
+    Blank,
+}
+
+impl<R: AsRef<[u8]>> DocLine<R> {
+    /// Bytes of the line without its line ending.
+    pub fn raw(&self) -> &[u8] {
+        self.raw.as_ref()
+    }
+
+    /// `\r\n`, `\n`, or empty for the last line.
+    pub fn ending(&self) -> &[u8] {
+        self.ending
+    }
+
+    pub fn kind(&self) -> &DocLineKind {
+        &self.kind
+    }
+
+    /// Key of an entry line.
+    pub fn key(&self) -> Option<(u32, u32)> {
+        match self.kind {
+            DocLineKind::Entry {
+                index, sub_index, ..
+            } => Some((index, sub_index)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn map_raw<S>(self, map: impl FnOnce(R) -> S) -> DocLine<S> {
+        DocLine {
+            raw: map(self.raw),
+            ending: self.ending,
+            kind: self.kind,
+        }
+    }
+
+    pub fn value(&self) -> Option<&[u8]> {
+        match &self.kind {
+            DocLineKind::Entry { value, .. } => Some(&self.raw()[value.clone()]),
+            _ => None,
+        }
+    }
+
+    /// Text of a comment line, or the trailing comment of an entry.
+    pub fn comment(&self) -> Option<&[u8]> {
+        match &self.kind {
+            DocLineKind::Entry { comment, .. } => Some(&self.raw()[comment.clone()?]),
+            DocLineKind::Comment(text) => Some(&self.raw()[text.clone()]),
+            DocLineKind::Blank => None,
+        }
+    }
+}
+
+impl MsgDocument {
+    pub fn parse(input: &[u8]) -> Result<Self, ParseError> {
+        let mut lines = vec![];
+        scan_lines(input, |line| lines.push(line.map_raw(Box::from)))?;
+        Ok(Self { lines })
+    }
+
+    pub fn lines(&self) -> &[DocLine] {
+        &self.lines
+    }
+
+    /// Entry lines with their keys and values, in file order.
+    pub fn entries(&self) -> impl Iterator<Item = ((u32, u32), &[u8])> {
+        entries(&self.lines)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_to(&mut out)
+            .expect("writing to a Vec doesn't fail");
+        out
+    }
+
+    pub fn write_to(&self, out: impl Write) -> io::Result<()> {
+        write_lines(&self.lines, out)
+    }
+
+    /// Replaces the value of an entry, leaving the rest of its line as written.
+    pub fn set_value(&mut self, index: u32, sub_index: u32, value: &[u8]) -> Result<(), String> {
+        check_value(value)?;
+        let line = self.position(index, sub_index)?;
+        let line = &mut self.lines[line];
+        if let DocLineKind::Entry {
+            value: range,
+            comment,
+            ..
+        } = &mut line.kind
+        {
+            let mut raw = line.raw.to_vec();
+            raw.splice(range.clone(), value.iter().copied());
+            let shift = |at: usize| at + value.len() - range.len();
+            if let Some(comment) = comment {
+                *comment = shift(comment.start)..shift(comment.end);
+            }
+            range.end = range.start + value.len();
+            line.raw = raw.into();
+        }
+        Ok(())
+    }
+
+    /// Adds an entry on a new line below the entry `after`, returning its key.
+    pub fn insert_after(
+        &mut self,
+        after: (u32, u32),
+        index: u32,
+        value: &[u8],
+    ) -> Result<(u32, u32), String> {
+        let line = self.position(after.0, after.1)?;
+        self.insert_entry(line + 1, index, value)
+    }
+
+    /// Adds an entry as line `at`, returning its key.
+    pub(crate) fn insert_entry(
+        &mut self,
+        at: usize,
+        index: u32,
+        value: &[u8],
+    ) -> Result<(u32, u32), String> {
+        check_value(value)?;
+        let mut raw = format!("{{{}}}{{}}{{", index).into_bytes();
+        let start = raw.len();
+        raw.extend_from_slice(value);
+        raw.push(b'}');
+        self.insert_line(
+            at,
+            raw,
+            DocLineKind::Entry {
+                index,
+                sub_index: 0,
+                secondary: start - 2..start - 2,
+                value: start..start + value.len(),
+                comment: None,
+            },
+        );
+        self.renumber();
+        Ok(self.lines[at].key().expect("inserted an entry"))
+    }
+
+    /// Removes the line of an entry, returning its value.
+    pub fn remove_entry(&mut self, index: u32, sub_index: u32) -> Result<Vec<u8>, String> {
+        let line = self.position(index, sub_index)?;
+        let removed = self.lines.remove(line);
+        if removed.ending.is_empty() && line > 0 {
+            self.lines[line - 1].ending = b"";
+        }
+        if self.lines.is_empty() {
+            self.lines.push(DocLine {
+                raw: Box::new([]),
+                ending: b"",
+                kind: DocLineKind::Blank,
+            });
+        }
+        self.renumber();
+        Ok(removed.value().unwrap_or_default().to_vec())
+    }
+
+    /// Adds a `# text` comment line above the entry `before`.
+    pub fn add_comment(&mut self, before: (u32, u32), text: &str) -> Result<(), String> {
+        if text.contains('\n') {
+            return Err("Comment must fit on one line".into());
+        }
+        let line = self.position(before.0, before.1)?;
+        let raw = format!("# {}", text).into_bytes();
+        let len = raw.len();
+        self.insert_line(line, raw, DocLineKind::Comment(2..len));
+        Ok(())
+    }
+
+    fn position(&self, index: u32, sub_index: u32) -> Result<usize, String> {
+        self.lines
+            .iter()
+            .position(|line| line.key() == Some((index, sub_index)))
+            .ok_or_else(|| format!("No entry {}:{}", index, sub_index))
+    }
+
+    /// Inserts a line at `at`, ending it like the rest of the document.
+    fn insert_line(&mut self, at: usize, raw: Vec<u8>, kind: DocLineKind) {
+        let ending = self
+            .lines
+            .iter()
+            .map(|line| line.ending)
+            .find(|ending| !ending.is_empty())
+            .unwrap_or(b"\n");
+        let mut line = DocLine {
+            raw: raw.into(),
+            ending,
+            kind,
+        };
+        // Appending after the unterminated last line moves the missing ending to the new one.
+        if at == self.lines.len() && self.lines[at - 1].ending.is_empty() {
+            self.lines[at - 1].ending = ending;
+            line.ending = b"";
+        }
+        self.lines.insert(at, line);
+    }
+
+    /// Recounts sub-indices in file order after lines were added or removed.
+    fn renumber(&mut self) {
+        let mut sub_indices: BTreeMap<u32, u32> = BTreeMap::new();
+        for line in &mut self.lines {
+            if let DocLineKind::Entry {
+                index, sub_index, ..
+            } = &mut line.kind
+            {
+                let next = sub_indices.entry(*index).or_insert(0);
+                *sub_index = *next;
+                *next += 1;
+            }
+        }
+    }
+
+    /// Entries decoded into a dictionary, dropping the formatting. Same as parsing
+    /// [`MsgDocument::to_bytes`] with [`crate::parse_msg_with`], annotations included.
+    pub fn to_dictionary(&self, decoder: LineDecoder) -> Result<MsgDictionary, ParseError> {
+        to_dictionary(&self.lines, decoder)
+    }
+}
+
+/// Splits `input` into lines, passing each one to `push` as it's scanned.
+pub(crate) fn scan_lines<'a>(
+    input: &'a [u8],
+    mut push: impl FnMut(DocLine<&'a [u8]>),
+) -> Result<(), ParseError> {
+    let mut scanner = Scanner::new(input);
+    let mut sub_indices: BTreeMap<u32, u32> = BTreeMap::new();
+    loop {
+        let start = scanner.pos;
+        let line = scanner.line().map_err(|err| ParseError::scan(input, err))?;
+        let end = scanner.pos;
+        let offset = |part: &[u8]| part.as_ptr() as usize - input.as_ptr() as usize - start;
+        let span = |part: &[u8]| offset(part)..offset(part) + part.len();
+        let kind = match line {
+            Line::Entry(entry) => {
+                let sub_index = sub_indices.entry(entry.index).or_insert(0);
+                *sub_index += 1;
+                DocLineKind::Entry {
+                    index: entry.index,
+                    sub_index: *sub_index - 1,
+                    secondary: span(entry.secondary),
+                    value: span(entry.value),
+                    comment: entry.comment.map(span),
+                }
+            }
+            Line::Comment(text) => DocLineKind::Comment(span(text)),
+            Line::Break => DocLineKind::Blank,
+        };
+        let newline = scanner.newline();
+        let ending: &'static [u8] = match scanner.pos - end {
+            2 => b"\r\n",
+            1 => b"\n",
+            _ => b"",
+        };
+        push(DocLine {
+            raw: &input[start..end],
+            ending,
+            kind,
+        });
+        if !newline {
+            break;
+        }
+    }
+    if scanner.pos != input.len() {
+        return Err(ParseError::non_exhaustive(input, scanner.pos));
+    }
+    Ok(())
+}
+
+pub(crate) fn entries<R: AsRef<[u8]>>(
+    lines: &[DocLine<R>],
+) -> impl Iterator<Item = ((u32, u32), &[u8])> {
+    lines
+        .iter()
+        .filter_map(|line| Some((line.key()?, line.value()?)))
+}
+
+pub(crate) fn to_dictionary<R: AsRef<[u8]>>(
+    lines: &[DocLine<R>],
+    decoder: LineDecoder,
+) -> Result<MsgDictionary, ParseError> {
+    let mut input = Vec::new();
+    write_lines(lines, &mut input).expect("writing to a Vec doesn't fail");
+    let mut msg = Msg {
+        lines: Vec::with_capacity(lines.len()),
+    };
+    let mut start = 0;
+    for line in lines {
+        let raw = &input[start..start + line.raw().len()];
+        start += raw.len() + line.ending.len();
+        msg.lines.push(match &line.kind {
+            DocLineKind::Entry {
+                index,
+                secondary,
+                value,
+                comment,
+                ..
+            } => Line::Entry(Entry {
+                index: *index,
+                secondary: &raw[secondary.clone()],
+                value: &raw[value.clone()],
+                comment: comment.clone().map(|comment| &raw[comment]),
+            }),
+            DocLineKind::Comment(text) => Line::Comment(&raw[text.clone()]),
+            DocLineKind::Blank => Line::Break,
+        });
+    }
+    let (dict, _failures) = collect_entries(
+        &input,
+        msg,
+        MsgDictionary::new(),
+        SecondaryKeyPolicy::Error,
+        |bytes| Ok::<_, std::convert::Infallible>(decoder.decode(bytes)),
+        decoder,
+        &mut ParseMetrics::default(),
+    )?;
+    Ok(dict)
+}
+
+pub(crate) fn write_lines<R: AsRef<[u8]>>(
+    lines: &[DocLine<R>],
+    mut out: impl Write,
+) -> io::Result<()> {
+    for line in lines {
+        out.write_all(line.raw())?;
+        out.write_all(line.ending)?;
+    }
+    Ok(())
+}
+
+fn check_value(value: &[u8]) -> Result<(), String> {
+    if value.contains(&b'}') {
+        Err("Value can't contain '}'".into())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips_byte_for_byte() {
+        let samples: &[&[u8]] = &[
+            b"",
+            b"\n\n",
+            b"# Transit Name\r\n\n  // Map 0\n{10}{}{Global map}\n{15}{}{20car}  # car\n{15}{}{03 - A Way.ogg}",
+            b"  {2}{}{\n multi \n\n}\r\n{1}{}{a}\n",
+        ];
+        for &sample in samples {
+            assert_eq!(MsgDocument::parse(sample).unwrap().to_bytes(), sample);
+        }
+        assert!(MsgDocument::parse(b"{1}{}{a}\njunk").is_err());
+    }
+
+    #[test]
+    fn lines_and_entries() {
+        let doc = MsgDocument::parse(b"# Maps\n{15}{}{20car}  # car\n\n{15}{}{23world}").unwrap();
+        let kinds: Vec<_> = doc.lines().iter().map(DocLine::kind).collect();
+        assert!(matches!(kinds[0], DocLineKind::Comment(_)));
+        assert_eq!(kinds[2], &DocLineKind::Blank);
+        assert_eq!(doc.lines()[1].comment(), Some(&b"car"[..]));
+        assert_eq!(
+            doc.entries().collect::<Vec<_>>(),
+            vec![((15, 0), &b"20car"[..]), ((15, 1), &b"23world"[..])]
+        );
+        let dict = doc.to_dictionary(LineDecoder::Utf8Strict).unwrap();
+        assert_eq!(dict.get_all_strings(15).count(), 2);
+    }
+
+    #[test]
+    fn dictionary_like_parsing() {
+        let input: &[u8] = b"#= alias 1 -> 2\n{2}{}{a} # hint; tags: ui; state: reviewed\n{3}{}{b} # deprecated: gone\n";
+        let dict = MsgDocument::parse(input)
+            .unwrap()
+            .to_dictionary(LineDecoder::Utf8Strict)
+            .unwrap();
+        assert_eq!(dict, crate::parse_msg(input).unwrap());
+        assert_eq!(dict.get_first_string(1), Some("a"));
+        assert_eq!(dict.comment(2, 0), Some("hint"));
+        assert_eq!(dict.tags(2, 0).collect::<Vec<_>>(), vec!["ui"]);
+        assert!(dict.review_state(2, 0).is_some());
+        assert!(dict.deprecation(3).is_some());
+        let doc = MsgDocument::parse(b"{1}{key}{a}\n").unwrap();
+        assert!(doc.to_dictionary(LineDecoder::Utf8Strict).is_err());
+    }
+
+    #[test]
+    fn edits_touch_only_their_lines() {
+        let mut doc = MsgDocument::parse(
+            b"# Maps\r\n  {10}{}{Global}  // keep\r\n{15}{}{20car}\r\n{15}{}{23world}",
+        )
+        .unwrap();
+        doc.set_value(10, 0, b"Global map").unwrap();
+        assert_eq!(doc.lines()[1].comment(), Some(&b"keep"[..]));
+        assert_eq!(doc.insert_after((15, 0), 15, b"21den").unwrap(), (15, 1));
+        assert_eq!(doc.insert_after((15, 2), 20, b"Den").unwrap(), (20, 0));
+        assert_eq!(doc.remove_entry(15, 0).unwrap(), b"20car");
+        doc.add_comment((15, 0), "music").unwrap();
+        assert_eq!(
+            doc.to_bytes(),
+            b"# Maps\r\n  {10}{}{Global map}  // keep\r\n# music\r\n{15}{}{21den}\r\n{15}{}{23world}\r\n{20}{}{Den}"
+        );
+        assert!(doc.set_value(10, 0, b"a}b").is_err());
+        assert!(doc.remove_entry(15, 2).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt;
+
+use encoding_rs::{EncoderResult, Encoding};
+
+use super::{LineDecoder, MsgDictionary, MsgLine, WriteOptions};
+
+/// Entry that won't survive a round trip through some encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodingIssue {
+    pub index: u32,
+    pub sub_index: u32,
+    pub kind: EncodingIssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodingIssueKind {
+    /// Byte value is malformed in the encoding.
+    Malformed { valid_up_to: usize },
+    /// String value contains a character the encoding can't represent.
+    Unrepresentable { character: char },
+}
+
+impl fmt::Display for EncodingIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Entry {}:{} ", self.index, self.sub_index)?;
+        match self.kind {
+            EncodingIssueKind::Malformed { valid_up_to } => {
+                write!(f, "is malformed after byte {}", valid_up_to)
+            }
+            EncodingIssueKind::Unrepresentable { character } => {
+                write!(f, "has unrepresentable character {:?}", character)
+            }
+        }
+    }
+}
+
+/// What [`MsgDictionary::to_msg_bytes_encoded`] does with characters the target
+/// encoding can't represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodePolicy {
+    /// Fail on the first entry holding one.
+    Error,
+    /// Write `?` instead.
+    Replace,
+    /// Write the character's UTF-8 bytes.
+    PassThroughBytes,
+}
+
+impl MsgDictionary {
+    /// Same layout as [`to_msg_bytes`](Self::to_msg_bytes), with string values,
+    /// comments and annotations encoded to `encoding`, e.g. WINDOWS-1251 for game clients.
+    ///
+    /// Byte values are written as they are.
+    pub fn to_msg_bytes_encoded(
+        &self,
+        encoding: &'static Encoding,
+        policy: EncodePolicy,
+    ) -> Result<Vec<u8>, EncodingIssue> {
+        let mut out = vec![];
+        let mut issue = None;
+        self.write_encoded_with(&mut out, &WriteOptions::default(), |key, text, buf| {
+            let unmappable = encode_into(encoding, policy, text, buf);
+            if let (Some(character), None) = (unmappable, &issue) {
+                issue = Some(EncodingIssue {
+                    index: key.0,
+                    sub_index: key.1,
+                    kind: EncodingIssueKind::Unrepresentable { character },
+                });
+            }
+        })
+        .expect("writing to a Vec doesn't fail");
+        match issue {
+            Some(issue) if policy == EncodePolicy::Error => Err(issue),
+            _ => Ok(out),
+        }
+    }
+
+    /// Lists every entry that can't be decoded from or encoded to `encoding`.
+    pub fn validate_encoding(&self, encoding: &'static Encoding) -> Vec<EncodingIssue> {
+        let decoder = LineDecoder::Encoding(encoding);
+        self.index_to_line
+            .iter()
+            .filter_map(|(&(index, sub_index), line)| {
+                let kind = match line {
+                    MsgLine::Bytes(bytes) => match decoder.decode_strict(bytes) {
+                        Ok(_) => return None,
+                        Err(err) => EncodingIssueKind::Malformed {
+                            valid_up_to: err.valid_up_to,
+                        },
+                    },
+                    MsgLine::String(string) => EncodingIssueKind::Unrepresentable {
+                        character: first_unmappable(encoding, string)?,
+                    },
+                };
+                Some(EncodingIssue {
+                    index,
+                    sub_index,
+                    kind,
+                })
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn first_unmappable(encoding: &'static Encoding, string: &str) -> Option<char> {
+    let mut encoder = encoding.new_encoder();
+    let mut sink = Vec::with_capacity(1024);
+    let mut read = 0;
+    loop {
+        sink.clear();
+        let (result, consumed) =
+            encoder.encode_from_utf8_to_vec_without_replacement(&string[read..], &mut sink, true);
+        read += consumed;
+        match result {
+            EncoderResult::InputEmpty => return None,
+            EncoderResult::OutputFull => continue,
+            EncoderResult::Unmappable(character) => return Some(character),
+        }
+    }
+}
+
+/// Appends `text` encoded to `encoding`, handling unmappable characters by `policy`.
+/// Returns the first unmappable character.
+fn encode_into(
+    encoding: &'static Encoding,
+    policy: EncodePolicy,
+    text: &str,
+    out: &mut Vec<u8>,
+) -> Option<char> {
+    let mut encoder = encoding.new_encoder();
+    let mut read = 0;
+    let mut unmappable = None;
+    loop {
+        out.reserve(4 * (text.len() - read) + 4);
+        let (result, consumed) =
+            encoder.encode_from_utf8_to_vec_without_replacement(&text[read..], out, true);
+        read += consumed;
+        match result {
+            EncoderResult::InputEmpty => return unmappable,
+            EncoderResult::OutputFull => continue,
+            EncoderResult::Unmappable(character) => {
+                unmappable = unmappable.or(Some(character));
+                match policy {
+                    EncodePolicy::PassThroughBytes => {
+                        out.extend_from_slice(character.encode_utf8(&mut [0; 4]).as_bytes())
+                    }
+                    EncodePolicy::Error | EncodePolicy::Replace => out.push(b'?'),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{UTF_8, WINDOWS_1251};
+
+    use super::*;
+
+    #[test]
+    fn validate_cp1251() {
+        let mut dict = MsgDictionary::new();
+        dict.insert(1, MsgLine::String("Привет".into()));
+        dict.insert(2, MsgLine::String("日本".into()));
+        dict.insert(3, MsgLine::Bytes(b"\xcf\xf0"[..].into()));
+        assert_eq!(
+            dict.validate_encoding(WINDOWS_1251),
+            vec![EncodingIssue {
+                index: 2,
+                sub_index: 0,
+                kind: EncodingIssueKind::Unrepresentable { character: '日' },
+            }]
+        );
+        assert_eq!(
+            dict.validate_encoding(UTF_8),
+            vec![EncodingIssue {
+                index: 3,
+                sub_index: 0,
+                kind: EncodingIssueKind::Malformed { valid_up_to: 0 },
+            }]
+        );
+    }
+
+    #[test]
+    fn write_cp1251() {
+        let mut dict = MsgDictionary::new();
+        dict.insert(1, MsgLine::String("Привет".into()));
+        dict.insert(2, MsgLine::String("日b".into()));
+        dict.insert(3, MsgLine::Bytes(b"\xcf"[..].into()));
+        dict.set_comment(1, 0, "Ж").unwrap();
+
+        let encode = |policy| dict.to_msg_bytes_encoded(WINDOWS_1251, policy);
+        assert_eq!(
+            encode(EncodePolicy::Replace).unwrap(),
+            b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2} # \xc6\n{2}{}{?b}\n{3}{}{\xcf}\n"
+        );
+        assert_eq!(
+            encode(EncodePolicy::PassThroughBytes).unwrap(),
+            b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2} # \xc6\n{2}{}{\xe6\x97\xa5b}\n{3}{}{\xcf}\n"
+        );
+        let err = encode(EncodePolicy::Error).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Entry 2:0 has unrepresentable character '日'"
+        );
+    }
+
+    #[test]
+    fn error_covers_annotations() {
+        let mut dict = MsgDictionary::new();
+        dict.insert(1, MsgLine::String("Привет".into()));
+        dict.insert(2, MsgLine::String("b".into()));
+        dict.add_alias(5, 1).unwrap();
+        dict.add_tag(2, 0, "日");
+        let err = dict
+            .to_msg_bytes_encoded(WINDOWS_1251, EncodePolicy::Error)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Entry 2:0 has unrepresentable character '日'"
+        );
+
+        let mut dict = MsgDictionary::new();
+        dict.insert(1, MsgLine::String("a".into()));
+        dict.deprecate(1, "use 日");
+        assert!(
+            dict.to_msg_bytes_encoded(WINDOWS_1251, EncodePolicy::Error)
+                .is_err()
+        );
+        assert_eq!(
+            dict.to_msg_bytes_encoded(WINDOWS_1251, EncodePolicy::Replace)
+                .unwrap(),
+            b"{1}{}{a} # deprecated: use ?\n"
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use std::{error::Error, fmt, io, path::PathBuf};
+
+use super::{DecodeError, scanner::ScanError, suggest::suggestion, validate};
+
+/// Why reading or parsing a MSG file failed.
+#[derive(Debug)]
+pub enum ParseError {
+    /// Reading the file failed, or its compressed content couldn't be unpacked.
+    Io(io::Error),
+    /// Malformed entry.
+    Syntax {
+        /// Byte offset in the input.
+        offset: usize,
+        /// 1-based line and column of `offset`, the column counted in characters.
+        line: usize,
+        column: usize,
+        message: String,
+        /// How the problem might be fixed.
+        help: Option<String>,
+    },
+    /// Value malformed in the requested encoding.
+    Encoding {
+        index: u32,
+        /// Byte offset of the value in the input.
+        offset: usize,
+        line: usize,
+        column: usize,
+        error: DecodeError,
+    },
+    /// Input left over after the last line the lexer understood.
+    NonExhaustive {
+        offset: usize,
+        line: usize,
+        column: usize,
+        /// Up to 20 characters of the rest.
+        tail: String,
+        help: Option<String>,
+    },
+    /// Well-formed input the dictionary can't hold, e.g. an alias cycle.
+    Invalid(String),
+    /// Error in one of several files read together.
+    File {
+        path: PathBuf,
+        error: Box<ParseError>,
+    },
+}
+
+/// 1-based line and column of the byte `offset` in `input`, the column counted in
+/// characters.
+pub fn line_column(input: &[u8], offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    let line_start = before
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |pos| pos + 1);
+    let line = 1 + before.iter().filter(|&&byte| byte == b'\n').count();
+    let column = 1 + String::from_utf8_lossy(&before[line_start..])
+        .chars()
+        .count();
+    (line, column)
+}
+
+impl ParseError {
+    /// Syntax error at `offset` in `input`.
+    pub(crate) fn syntax_at(input: &[u8], offset: usize, message: String) -> Self {
+        let (line, column) = line_column(input, offset);
+        ParseError::Syntax {
+            offset,
+            line,
+            column,
+            message,
+            help: suggestion(input, offset),
+        }
+    }
+
+    /// Syntax error from a lexer that doesn't report where it failed.
+    pub(crate) fn syntax(input: &[u8], message: String) -> Self {
+        let offset = validate::validate_msg(input)
+            .err()
+            .and_then(|diagnostics| diagnostics.first().map(|diagnostic| diagnostic.offset))
+            .unwrap_or(0);
+        Self::syntax_at(input, offset, message)
+    }
+
+    /// Error for input left over at `offset`.
+    pub(crate) fn non_exhaustive(input: &[u8], offset: usize) -> Self {
+        let (line, column) = line_column(input, offset);
+        ParseError::NonExhaustive {
+            offset,
+            line,
+            column,
+            tail: input[offset..]
+                .iter()
+                .take(20)
+                .map(|&byte| byte as char)
+                .collect(),
+            help: suggestion(input, offset),
+        }
+    }
+
+    /// Error for input whose scan failed.
+    pub(crate) fn scan(input: &[u8], err: ScanError) -> Self {
+        Self::syntax_at(input, err.offset, err.to_string())
+    }
+
+    /// Wraps the error with the file it occurred in.
+    pub(crate) fn in_file(self, path: impl Into<PathBuf>) -> Self {
+        ParseError::File {
+            path: path.into(),
+            error: Box::new(self),
+        }
+    }
+
+    /// Byte offset in the input the error points at, if any.
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            ParseError::Syntax { offset, .. }
+            | ParseError::Encoding { offset, .. }
+            | ParseError::NonExhaustive { offset, .. } => Some(offset),
+            ParseError::File { ref error, .. } => error.offset(),
+            ParseError::Io(_) | ParseError::Invalid(_) => None,
+        }
+    }
+
+    /// 1-based line and column the error points at, if any.
+    pub fn line_column(&self) -> Option<(usize, usize)> {
+        match *self {
+            ParseError::Syntax { line, column, .. }
+            | ParseError::Encoding { line, column, .. }
+            | ParseError::NonExhaustive { line, column, .. } => Some((line, column)),
+            ParseError::File { ref error, .. } => error.line_column(),
+            ParseError::Io(_) | ParseError::Invalid(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let help = match self {
+            ParseError::Io(err) => return write!(f, "IoError: {}", err),
+            ParseError::Syntax {
+                line,
+                column,
+                message,
+                help,
+                ..
+            } => {
+                write!(f, "{} (line {}, column {})", message, line, column)?;
+                help
+            }
+            ParseError::Encoding {
+                index,
+                offset,
+                line,
+                column,
+                error,
+            } => {
+                return write!(
+                    f,
+                    "Failed to decode [{}] at {} (line {}, column {}): {}",
+                    index, offset, line, column, error
+                );
+            }
+            ParseError::NonExhaustive {
+                line,
+                column,
+                tail,
+                help,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Failed to exhaust input at line {}, column {}: {}",
+                    line, column, tail
+                )?;
+                help
+            }
+            ParseError::Invalid(message) => return f.write_str(message),
+            ParseError::File { path, error } => {
+                return write!(f, "{}: {}", path.display(), error);
+            }
+        };
+        if let Some(help) = help {
+            write!(f, "\nhelp: {}", help)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(err) => Some(err),
+            ParseError::File { error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        ParseError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::line_column;
+    use crate::{LineDecoder, ParseError, parse_file, parse_file_strict, parse_msg};
+
+    #[test]
+    fn positions() {
+        assert_eq!(line_column(b"", 0), (1, 1));
+        assert_eq!(line_column("{1}{}{а}\n{2}{}{б}".as_bytes(), 8), (1, 8));
+        assert_eq!(line_column(b"{1}{}{a}\r\n  {x", 12), (2, 3));
+        let err = parse_msg(b"# head\n{1}{}{a}\n\n{2}{}{b}\n  {3}{}{unclosed").unwrap_err();
+        assert_eq!(err.line_column(), Some((5, 17)));
+        assert!(err.to_string().contains("line 5, column 17"));
+    }
+
+    #[test]
+    fn structured_errors() {
+        match parse_msg(b"{1}{}{a}\njunk") {
+            Err(ParseError::NonExhaustive {
+                offset,
+                line,
+                column,
+                tail,
+                help,
+            }) => {
+                assert_eq!((offset, line, column), (9, 2, 1));
+                assert_eq!(tail, "junk");
+                assert!(help.is_some());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        let err = parse_file("/nonexistent/FOTEXT.MSG").unwrap_err();
+        assert!(matches!(&err, ParseError::Io(io) if io.kind() == std::io::ErrorKind::NotFound));
+        assert!(err.to_string().starts_with("IoError: "));
+        assert!(std::error::Error::source(&err).is_some());
+
+        let path = std::env::temp_dir().join(format!("fo_msg_error_{}.msg", std::process::id()));
+        std::fs::write(&path, b"{1}{}{a}\n{2}{}{\xff}\n").unwrap();
+        let err = parse_file_strict(&path, LineDecoder::Utf8Strict).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            err,
+            ParseError::Encoding {
+                index: 2,
+                offset: 15,
+                line: 2,
+                column: 7,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+use super::MsgDictionary;
+
+const ESCAPE: char = '\\';
+
+/// Splits `value` on `delimiter`; a backslash makes the next character literal.
+pub fn split_fields(value: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch == ESCAPE {
+            field.push(chars.next().unwrap_or(ESCAPE));
+        } else if ch == delimiter {
+            fields.push(std::mem::take(&mut field));
+        } else {
+            field.push(ch);
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Joins `fields` with `delimiter`, escaping delimiters and backslashes inside them.
+pub fn join_fields<S: AsRef<str>>(fields: &[S], delimiter: char) -> String {
+    let mut out = String::new();
+    for (pos, field) in fields.iter().enumerate() {
+        if pos > 0 {
+            out.push(delimiter);
+        }
+        for ch in field.as_ref().chars() {
+            if ch == delimiter || ch == ESCAPE {
+                out.push(ESCAPE);
+            }
+            out.push(ch);
+        }
+    }
+    out
+}
+
+impl MsgDictionary {
+    /// First string value of `index` split with [`split_fields`].
+    pub fn get_fields(&self, index: u32, delimiter: char) -> Option<Vec<String>> {
+        Some(split_fields(self.get_first_string(index)?, delimiter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn split_and_join() {
+        let fields = ["a|b", "c\\d", "", "e"];
+        let joined = join_fields(&fields, '|');
+        assert_eq!(joined, "a\\|b|c\\\\d||e");
+        assert_eq!(split_fields(&joined, '|'), fields);
+    }
+
+    #[test]
+    fn dictionary_fields() {
+        let dict = parse_msg(b"{1}{}{param1|param2|param3}").unwrap();
+        assert_eq!(
+            dict.get_fields(1, '|'),
+            Some(vec!["param1".into(), "param2".into(), "param3".into()])
+        );
+        assert_eq!(dict.get_fields(2, '|'), None);
+    }
+}
@@ -0,0 +1,109 @@
+use super::{MsgDictionary, MsgLine, MsgRead};
+
+/// Immutable snapshot of a dictionary for long-running readers.
+///
+/// Values sit in one sorted slice, with the first value of every index found by
+/// binary search over precomputed group starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrozenMsg {
+    values: Box<[MsgLine]>,
+    /// `(index, position of its first value)`, ascending.
+    groups: Box<[(u32, u32)]>,
+}
+
+impl FrozenMsg {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// All values of `index`, in sub-index order.
+    pub fn get_all(&self, index: u32) -> &[MsgLine] {
+        let group = match self
+            .groups
+            .binary_search_by_key(&index, |&(index, _)| index)
+        {
+            Ok(group) => group,
+            Err(_) => return &[],
+        };
+        let start = self.groups[group].1 as usize;
+        let end = self
+            .groups
+            .get(group + 1)
+            .map_or(self.values.len(), |&(_index, start)| start as usize);
+        &self.values[start..end]
+    }
+
+    pub fn get(&self, index: u32, sub_index: u32) -> Option<&MsgLine> {
+        self.get_all(index).get(sub_index as usize)
+    }
+
+    pub fn get_first_string(&self, index: u32) -> Option<&str> {
+        self.get(index, 0)?.string()
+    }
+
+    pub fn get_first_bytes(&self, index: u32) -> Option<&[u8]> {
+        Some(self.get(index, 0)?.bytes())
+    }
+
+    pub fn indices(&self) -> impl Iterator<Item = u32> + '_ {
+        self.groups.iter().map(|&(index, _start)| index)
+    }
+}
+
+impl From<MsgDictionary> for FrozenMsg {
+    /// Sub-indices are renumbered from 0 without gaps.
+    fn from(dict: MsgDictionary) -> Self {
+        let mut values = Vec::with_capacity(dict.len());
+        let mut groups = vec![];
+        for ((index, _sub_index), value) in dict.index_to_line.into_entries() {
+            if groups.last().is_none_or(|&(last, _start)| last != index) {
+                groups.push((index, values.len() as u32));
+            }
+            values.push(value);
+        }
+        Self {
+            values: values.into(),
+            groups: groups.into(),
+        }
+    }
+}
+
+impl MsgRead for FrozenMsg {
+    fn get_string(&self, index: u32, sub_index: u32) -> Option<&str> {
+        self.get(index, sub_index)?.string()
+    }
+
+    fn get_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        Some(self.get(index, sub_index)?.bytes())
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        self.get_all(index).len() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn frozen_lookups() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FrozenMsg>();
+
+        let dict = parse_msg(b"{10}{}{a}\n{15}{}{b}\n{15}{}{c}\n{20}{}{d}\n").unwrap();
+        let frozen = FrozenMsg::from(dict);
+        assert_eq!(frozen.len(), 4);
+        assert_eq!(frozen.get_first_string(10), Some("a"));
+        assert_eq!(frozen.get_string(15, 1), Some("c"));
+        assert_eq!(MsgRead::count(&frozen, 15), 2);
+        assert_eq!(frozen.get_first_string(20), Some("d"));
+        assert!(frozen.get_all(11).is_empty());
+        assert_eq!(frozen.indices().collect::<Vec<_>>(), vec![10, 15, 20]);
+    }
+}
@@ -0,0 +1,156 @@
+use std::ops::Range;
+
+use super::{MsgDictionary, Schema};
+
+/// Unused indices, see [`Schema::gaps`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gap {
+    pub indices: Range<u32>,
+    pub kind: GapKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GapKind {
+    /// Inside a range declared as reserved.
+    Reserved { range: String },
+    /// Accidental hole, inside a declared range if `range` is set.
+    Hole { range: Option<String> },
+}
+
+impl MsgDictionary {
+    /// Unused index runs between the first and the last index.
+    pub fn gaps(&self) -> Vec<Range<u32>> {
+        let mut gaps = vec![];
+        let mut indices = self.indices();
+        let mut previous = match indices.next() {
+            Some(first) => first,
+            None => return gaps,
+        };
+        for index in indices {
+            if index > previous + 1 {
+                gaps.push(previous + 1..index);
+            }
+            previous = index;
+        }
+        gaps
+    }
+}
+
+impl Schema {
+    /// Unused indices of `dict` within its own span and every declared range,
+    /// split at range boundaries and classified by the range they fall in.
+    ///
+    /// Ranges are treated as contiguous, strides and slots are ignored.
+    pub fn gaps(&self, dict: &MsgDictionary) -> Vec<Gap> {
+        let used: Vec<u32> = dict.indices().collect();
+        let mut spans: Vec<Range<u32>> = self
+            .ranges
+            .iter()
+            .map(|range| range.start..range.end)
+            .collect();
+        if let (Some(&first), Some(&last)) = (used.first(), used.last()) {
+            spans.push(first..last.saturating_add(1));
+        }
+        spans.sort_by_key(|span| span.start);
+
+        // Unused runs of the union of spans.
+        let mut unused: Vec<Range<u32>> = vec![];
+        let mut covered_up_to = 0;
+        for span in spans {
+            let start = span.start.max(covered_up_to);
+            if start >= span.end {
+                continue;
+            }
+            let first_used = used.partition_point(|&index| index < start);
+            let mut run_start = start;
+            for &index in used[first_used..]
+                .iter()
+                .take_while(|&&index| index < span.end)
+            {
+                if index > run_start {
+                    unused.push(run_start..index);
+                }
+                run_start = index + 1;
+            }
+            if run_start < span.end {
+                unused.push(run_start..span.end);
+            }
+            covered_up_to = span.end;
+        }
+
+        let mut boundaries: Vec<u32> = self
+            .ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .collect();
+        boundaries.sort_unstable();
+        let mut gaps = vec![];
+        for run in unused {
+            let mut start = run.start;
+            for &boundary in boundaries
+                .iter()
+                .filter(|&&point| point > run.start && point < run.end)
+            {
+                if boundary > start {
+                    gaps.push(self.classify(start..boundary));
+                    start = boundary;
+                }
+            }
+            gaps.push(self.classify(start..run.end));
+        }
+        gaps
+    }
+
+    fn classify(&self, indices: Range<u32>) -> Gap {
+        let range = self
+            .ranges
+            .iter()
+            .find(|range| range.start <= indices.start && indices.start < range.end);
+        let kind = match range {
+            Some(range) if range.reserved => GapKind::Reserved {
+                range: range.name.clone(),
+            },
+            range => GapKind::Hole {
+                range: range.map(|range| range.name.clone()),
+            },
+        };
+        Gap { indices, kind }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IndexRange, parse_msg};
+
+    #[test]
+    fn file_and_schema_gaps() {
+        let dict = parse_msg(b"{10}{}{a}\n{11}{}{b}\n{15}{}{c}\n{40}{}{d}\n").unwrap();
+        assert_eq!(dict.gaps(), vec![12..15, 16..40]);
+
+        let schema = Schema::new(vec![
+            IndexRange::new("maps", 10..20),
+            IndexRange::new("future", 20..30).reserved(),
+        ]);
+        let hole = |indices, range: Option<&str>| Gap {
+            indices,
+            kind: GapKind::Hole {
+                range: range.map(Into::into),
+            },
+        };
+        assert_eq!(
+            schema.gaps(&dict),
+            vec![
+                hole(12..15, Some("maps")),
+                hole(16..20, Some("maps")),
+                Gap {
+                    indices: 20..30,
+                    kind: GapKind::Reserved {
+                        range: "future".into()
+                    },
+                },
+                hole(30..40, None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use super::{DictView, MsgDictionary};
+
+/// Offset of the map name from the map's base index `(pid + 1) * 10` in FOGM.MSG.
+pub const MAP_NAME_OFFSET: u32 = 0;
+/// Offset of the music tracks, one entry each.
+pub const MAP_MUSIC_OFFSET: u32 = 5;
+/// Offset of the transit name.
+pub const MAP_TRANSIT_OFFSET: u32 = 8;
+
+/// View over FOGM.MSG map texts by map pid.
+#[derive(Debug, Clone, Copy)]
+pub struct GlobalMapMsg<'a> {
+    view: DictView<'a>,
+}
+
+impl<'a> GlobalMapMsg<'a> {
+    pub fn new(dict: &'a MsgDictionary) -> Self {
+        Self {
+            view: DictView::new(dict).with_base_stride(10, 10),
+        }
+    }
+
+    pub fn name(&self, pid: u32) -> Option<&'a str> {
+        self.view.get(pid, MAP_NAME_OFFSET)
+    }
+
+    /// Music entries of the map, in file order.
+    pub fn music(&self, pid: u32) -> Vec<&'a str> {
+        self.view.get_all(pid, MAP_MUSIC_OFFSET)
+    }
+
+    pub fn transit_name(&self, pid: u32) -> Option<&'a str> {
+        self.view.get(pid, MAP_TRANSIT_OFFSET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn map_texts() {
+        let dict = parse_msg(b"# Map 0, Global, base 10\n{10}{}{Global map}\n{15}{}{20car}\n{15}{}{23world}\n{28}{}{Den}\n").unwrap();
+        let maps = GlobalMapMsg::new(&dict);
+        assert_eq!(maps.name(0), Some("Global map"));
+        assert_eq!(maps.music(0), vec!["20car", "23world"]);
+        assert_eq!(maps.transit_name(1), Some("Den"));
+        assert_eq!(maps.name(1), None);
+        assert!(maps.music(u32::MAX).is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+use super::{MsgDictionary, MsgLine};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for &byte in bytes {
+        out.push(HEX_DIGITS[usize::from(byte >> 4)] as char);
+        out.push(HEX_DIGITS[usize::from(byte & 0xf)] as char);
+    }
+    out
+}
+
+fn decode(hex: &[u8]) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high << 4 | low) as u8)
+        })
+        .collect()
+}
+
+impl MsgDictionary {
+    /// Stores binary data hex-encoded, the way the engine's `FOMsg::AddBinary` does.
+    pub fn insert_bytes(&mut self, index: u32, data: &[u8]) {
+        self.insert(index, MsgLine::String(encode(data).into()));
+    }
+
+    /// Decodes a value stored by [`MsgDictionary::insert_bytes`], `None` if it isn't valid hex.
+    pub fn get_binary(&self, index: u32) -> Option<Vec<u8>> {
+        decode(self.get_first_bytes(index)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn binary_roundtrip() {
+        let mut dict = MsgDictionary::new();
+        dict.insert_bytes(7, &[0x00, 0x7f, 0xff, 0x10]);
+        assert_eq!(dict.get_first_string(7), Some("007fff10"));
+        let parsed = parse_msg(&dict.to_msg_bytes()).unwrap();
+        assert_eq!(parsed.get_binary(7), Some(vec![0x00, 0x7f, 0xff, 0x10]));
+    }
+
+    #[test]
+    fn rejects_non_hex() {
+        let dict = parse_msg(b"{1}{}{0A0b}\n{2}{}{abc}\n{3}{}{zz}").unwrap();
+        assert_eq!(dict.get_binary(1), Some(vec![0x0a, 0x0b]));
+        assert_eq!(dict.get_binary(2), None);
+        assert_eq!(dict.get_binary(3), None);
+    }
+}
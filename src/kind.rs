@@ -0,0 +1,181 @@
+use std::fmt;
+
+use super::MsgDictionary;
+
+/// Message file kinds shipped in a language directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MsgType {
+    Text,
+    Dlg,
+    Obj,
+    Game,
+    GlobalMap,
+    Combat,
+    Quest,
+    Holo,
+    Craft,
+    Internal,
+}
+
+impl MsgType {
+    pub const ALL: [MsgType; 10] = [
+        MsgType::Text,
+        MsgType::Dlg,
+        MsgType::Obj,
+        MsgType::Game,
+        MsgType::GlobalMap,
+        MsgType::Combat,
+        MsgType::Quest,
+        MsgType::Holo,
+        MsgType::Craft,
+        MsgType::Internal,
+    ];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            MsgType::Text => "FOTEXT.MSG",
+            MsgType::Dlg => "FODLG.MSG",
+            MsgType::Obj => "FOOBJ.MSG",
+            MsgType::Game => "FOGAME.MSG",
+            MsgType::GlobalMap => "FOGM.MSG",
+            MsgType::Combat => "FOCOMBAT.MSG",
+            MsgType::Quest => "FOQUEST.MSG",
+            MsgType::Holo => "FOHOLO.MSG",
+            MsgType::Craft => "FOCRAFT.MSG",
+            MsgType::Internal => "INTERNAL.MSG",
+        }
+    }
+
+    /// Kind of a file by name, ignoring case.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.file_name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for MsgType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.file_name())
+    }
+}
+
+fn expect_type(actual: MsgType, expected: MsgType) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("Expected {}, got {}", expected, actual))
+    }
+}
+
+/// FOTEXT.MSG: plain interface texts by index.
+#[derive(Debug, PartialEq)]
+pub struct TextMsg(MsgDictionary);
+
+impl TextMsg {
+    pub fn new(dict: MsgDictionary, msg_type: MsgType) -> Result<Self, String> {
+        expect_type(msg_type, MsgType::Text).map(|()| Self(dict))
+    }
+
+    pub fn get(&self, index: u32) -> Option<&str> {
+        self.0.get_first_string(index)
+    }
+
+    pub fn into_inner(self) -> MsgDictionary {
+        self.0
+    }
+}
+
+/// FODLG.MSG: dialog texts, see [`dlg_str_id`](crate::dlg_str_id).
+#[derive(Debug, PartialEq)]
+pub struct DlgMsg(MsgDictionary);
+
+impl DlgMsg {
+    pub fn new(dict: MsgDictionary, msg_type: MsgType) -> Result<Self, String> {
+        expect_type(msg_type, MsgType::Dlg).map(|()| Self(dict))
+    }
+
+    pub fn text(&self, dlg_id: u32, idx: u32) -> Option<&str> {
+        if idx >= 1000 {
+            return None;
+        }
+        self.0
+            .get_first_string(dlg_id.checked_mul(1000)?.checked_add(idx)?)
+    }
+
+    pub fn into_inner(self) -> MsgDictionary {
+        self.0
+    }
+}
+
+/// Indices reserved per item prototype in FOOBJ.MSG.
+pub const ITEM_STR_MUL: u32 = 100;
+
+/// FOOBJ.MSG: item prototype texts at `pid * 100 + offset`.
+#[derive(Debug, PartialEq)]
+pub struct ObjMsg(MsgDictionary);
+
+impl ObjMsg {
+    pub fn new(dict: MsgDictionary, msg_type: MsgType) -> Result<Self, String> {
+        expect_type(msg_type, MsgType::Obj).map(|()| Self(dict))
+    }
+
+    pub fn name(&self, pid: u32) -> Option<&str> {
+        self.item_text(pid, 0)
+    }
+
+    pub fn description(&self, pid: u32) -> Option<&str> {
+        self.item_text(pid, 1)
+    }
+
+    fn item_text(&self, pid: u32, offset: u32) -> Option<&str> {
+        self.0
+            .get_first_string(pid.checked_mul(ITEM_STR_MUL)?.checked_add(offset)?)
+    }
+
+    pub fn into_inner(self) -> MsgDictionary {
+        self.0
+    }
+}
+
+/// FOGAME.MSG: game messages by string number.
+#[derive(Debug, PartialEq)]
+pub struct GameMsg(MsgDictionary);
+
+impl GameMsg {
+    pub fn new(dict: MsgDictionary, msg_type: MsgType) -> Result<Self, String> {
+        expect_type(msg_type, MsgType::Game).map(|()| Self(dict))
+    }
+
+    pub fn get(&self, str_num: u32) -> Option<&str> {
+        self.0.get_first_string(str_num)
+    }
+
+    pub fn into_inner(self) -> MsgDictionary {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn typed_wrappers() {
+        assert_eq!(
+            MsgType::from_file_name("fogm.msg"),
+            Some(MsgType::GlobalMap)
+        );
+        assert_eq!(MsgType::from_file_name("NOPE.MSG"), None);
+
+        let dict = parse_msg(b"{1200}{}{Knife}\n{1201}{}{Sharp.}\n{12100}{}{Hi}\n").unwrap();
+        assert!(ObjMsg::new(parse_msg(b"").unwrap(), MsgType::GlobalMap).is_err());
+        let obj = ObjMsg::new(dict, MsgType::Obj).unwrap();
+        assert_eq!(obj.name(12), Some("Knife"));
+        assert_eq!(obj.description(12), Some("Sharp."));
+        let dlg = DlgMsg::new(obj.into_inner(), MsgType::Dlg).unwrap();
+        assert_eq!(dlg.text(12, 100), Some("Hi"));
+    }
+}
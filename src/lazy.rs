@@ -0,0 +1,90 @@
+use std::sync::OnceLock;
+
+use super::{BufferMsgDictionary, LineDecoder, MsgLine, MsgRead, ParseError};
+
+/// Dictionary keeping values as raw bytes until first access, then caching the
+/// decoded value per entry.
+#[derive(Debug)]
+pub struct LazyMsgDictionary {
+    raw: BufferMsgDictionary,
+    decoded: Box<[OnceLock<MsgLine>]>,
+    decoder: LineDecoder,
+}
+
+impl LazyMsgDictionary {
+    /// Checks syntax of `input` but decodes nothing yet.
+    pub fn parse(input: &[u8], decoder: LineDecoder) -> Result<Self, ParseError> {
+        let raw = BufferMsgDictionary::parse(input)?;
+        let decoded = (0..raw.len()).map(|_| OnceLock::new()).collect();
+        Ok(Self {
+            raw,
+            decoded,
+            decoder,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Decoded value, decoding it on first access.
+    pub fn get(&self, index: u32, sub_index: u32) -> Option<&MsgLine> {
+        let pos = self.raw.position(index, sub_index)?;
+        Some(self.decoded[pos].get_or_init(|| self.decoder.decode(self.raw.value(pos))))
+    }
+
+    pub fn get_first_string(&self, index: u32) -> Option<&str> {
+        self.get(index, 0)?.string()
+    }
+
+    /// Raw value bytes, without decoding.
+    pub fn get_first_bytes(&self, index: u32) -> Option<&[u8]> {
+        self.raw.get_first_bytes(index)
+    }
+
+    /// Number of values decoded so far.
+    pub fn decoded_count(&self) -> usize {
+        self.decoded
+            .iter()
+            .filter(|value| value.get().is_some())
+            .count()
+    }
+}
+
+impl MsgRead for LazyMsgDictionary {
+    fn get_string(&self, index: u32, sub_index: u32) -> Option<&str> {
+        self.get(index, sub_index)?.string()
+    }
+
+    fn get_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        self.raw.get_bytes(index, sub_index)
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        MsgRead::count(&self.raw, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_on_first_access() {
+        let input = b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2}\n{2}{}{plain}\n";
+        let dict =
+            LazyMsgDictionary::parse(input, LineDecoder::Encoding(encoding_rs::WINDOWS_1251))
+                .unwrap();
+        assert_eq!(dict.decoded_count(), 0);
+        assert_eq!(dict.get_first_bytes(2), Some(&b"plain"[..]));
+        assert_eq!(dict.decoded_count(), 0);
+        assert_eq!(dict.get_first_string(1), Some("Привет"));
+        assert_eq!(dict.get_first_string(1), Some("Привет"));
+        assert_eq!(dict.decoded_count(), 1);
+        assert_eq!(dict.get_string(3, 0), None);
+    }
+}
@@ -0,0 +1,124 @@
+use super::{
+    Line, LineDecoder, Msg, MsgDictionary, ParseError, ParseMetrics, SecondaryKeyPolicy,
+    collect_entries, error::line_column, scanner::Scanner,
+};
+
+/// Line left out by [`parse_msg_lenient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedLine {
+    /// Byte offset of the problem in the input.
+    pub offset: usize,
+    /// 1-based line and column of `offset`.
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+/// What [`parse_msg_lenient`] had to skip.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    pub skipped: Vec<SkippedLine>,
+}
+
+impl ParseReport {
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
+/// Parses what it can of `input`, skipping malformed lines instead of failing.
+///
+/// Fails only on input that is well-formed but can't be held, e.g. an alias cycle.
+pub fn parse_msg_lenient(
+    input: &[u8],
+    decoder: LineDecoder,
+) -> Result<(MsgDictionary, ParseReport), ParseError> {
+    let mut scanner = Scanner::new(input);
+    let mut lines = vec![];
+    let mut report = ParseReport::default();
+    let mut skip = |offset: usize, reason: String| {
+        let (line, column) = line_column(input, offset);
+        report.skipped.push(SkippedLine {
+            offset,
+            line,
+            column,
+            reason,
+        });
+    };
+    loop {
+        let mut skipped_at = None;
+        match scanner.line() {
+            Ok(Line::Entry(entry)) if !entry.secondary.is_empty() => {
+                let offset = entry.secondary.as_ptr() as usize - input.as_ptr() as usize;
+                skip(
+                    offset,
+                    format!("Non-empty secondary key for index {}", entry.index),
+                );
+                skipped_at = Some(offset);
+            }
+            Ok(line) => lines.push(line),
+            Err(err) => {
+                skip(err.offset, format!("Expected {}", err.expected));
+                skipped_at = Some(err.offset);
+            }
+        }
+        if scanner.newline() {
+            continue;
+        }
+        if scanner.rest().is_empty() {
+            break;
+        }
+        scanner.space0();
+        if skipped_at.is_none_or(|offset| offset < scanner.pos) {
+            skip(scanner.pos, "Unexpected text".into());
+        }
+        // Drop the rest of the broken line.
+        match scanner.rest().iter().position(|&byte| byte == b'\n') {
+            Some(len) => scanner.pos += len + 1,
+            None => break,
+        }
+    }
+    let (dict, _failures) = collect_entries(
+        input,
+        Msg { lines },
+        MsgDictionary::new(),
+        SecondaryKeyPolicy::Error,
+        |bytes| Ok::<_, std::convert::Infallible>(decoder.decode(bytes)),
+        decoder,
+        &mut ParseMetrics::default(),
+    )?;
+    Ok((dict, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_broken_lines() {
+        let input = b"# ok\n{1}{}{a}\n{x}{}{b}\n{3}{s}{c}\n{4}{}{d} junk\n{5}{}{e}\n";
+        let (dict, report) = parse_msg_lenient(input, LineDecoder::Utf8Strict).unwrap();
+        assert_eq!(
+            (1..=5)
+                .map(|index| dict.get_first_string(index))
+                .collect::<Vec<_>>(),
+            vec![Some("a"), None, None, Some("d"), Some("e")]
+        );
+        assert_eq!(
+            report
+                .skipped
+                .iter()
+                .map(|skipped| (skipped.line, skipped.column, skipped.reason.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (3, 2, "Expected an index"),
+                (4, 5, "Non-empty secondary key for index 3"),
+                (5, 10, "Unexpected text"),
+            ]
+        );
+
+        let (dict, report) = parse_msg_lenient(b"{1}{}{a}\n", LineDecoder::Utf8Strict).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(dict.len(), 1);
+    }
+}
@@ -1,22 +1,10 @@
-use nom_prelude::{complete::*, nom::AsChar, *};
+use nom_prelude::{complete::*, *};
 
 use super::{Entry, Line, Msg};
 
-pub(crate) fn tokenize_msg<I: StringLikeInput>(
-    input: I,
-    exhaustive: bool,
-) -> Result<Msg<I>, String> {
-    let (rest, res) = input.err_to_string(msg(input))?;
-    if !exhaustive || rest.input_len() == 0 {
-        Ok(res)
-    } else {
-        let tail: String = rest
-            .iter_elements()
-            .take(20)
-            .map(|ch| ch.as_char())
-            .collect();
-        Err(format!("Failed to exhaust input to the end: {tail}",))
-    }
+/// Tokenizes as much of `input` as possible, returning the rest too.
+pub(crate) fn tokenize_msg_prefix<I: StringLikeInput>(input: I) -> Result<(I, Msg<I>), String> {
+    input.err_to_string(msg(input))
 }
 
 fn msg<I: StringLikeInput, E: ParseError<I>>(i: I) -> IResult<I, Msg<I>, E> {
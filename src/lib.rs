@@ -1,15 +1,213 @@
+mod aliases;
+mod approval;
+#[cfg(feature = "arena")]
+mod arena;
+#[cfg(feature = "tokio")]
+mod async_io;
+mod buffer;
+pub mod build;
+mod bundle;
+mod cache;
+mod case;
+mod codegen;
+mod collate;
+mod collisions;
+mod combine;
+mod comments;
+mod compression;
+mod convert;
+mod corpus;
+mod coverage;
+mod decoder;
+mod delta;
+mod deprecation;
+mod dialog;
+mod diff;
+mod display;
+mod document;
+#[cfg(any(test, feature = "cp1251"))]
+mod encoding;
+mod error;
+mod fields;
+mod frozen;
+mod gaps;
+mod global_map;
+mod hex;
+mod json;
+mod kind;
+mod lazy;
+mod lenient;
 mod lexer;
+mod lines;
+mod lint;
+mod lst;
+mod memory;
+mod merge;
+mod metrics;
+mod missing;
+mod monotonic;
+mod order;
+mod orphans;
+mod pack;
+mod page;
+mod patch;
+mod prefix;
+mod provider;
+mod quest;
+mod read;
+mod reload;
+mod render;
+mod replace;
+mod review;
+mod revision;
+mod roundtrip;
+mod sanitize;
+mod scanner;
+mod schema;
+#[cfg(feature = "rhai")]
+mod script;
+mod secondary;
+mod sections;
+mod stats;
+mod storage;
+mod suggest;
+mod summary;
+mod sync;
+mod tags;
+mod telemetry;
+mod tokens;
+mod transform;
+mod translit;
+mod validate;
+mod view;
+mod writer;
+mod xref;
 
-use std::collections::btree_map::BTreeMap;
+use std::{
+    borrow::Cow,
+    collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+};
 
-#[derive(Debug, PartialEq)]
+pub use approval::{ReviewState, StateCoverage};
+#[cfg(feature = "arena")]
+pub use arena::ArenaDocument;
+pub use buffer::BufferMsgDictionary;
+pub use bundle::BundleImport;
+pub use cache::{apply_cache_delta, cache_delta};
+pub use collate::Collation;
+pub use collisions::{Collision, CollisionKind, ProjectFile, Provenance, find_collisions};
+pub use combine::{CombinedMsg, combine};
+pub use compression::decompress;
+pub use convert::{InputSpec, OutputSpec, TreeConversion, convert_tree};
+pub use corpus::{
+    CORPUS_ENV, Conformance, CorpusFile, CorpusReport, REFERENCE_SUFFIX, ReferenceCheck,
+    corpus_dirs_from_env, run_corpus,
+};
+pub use coverage::{COVERAGE_SCHEMA_VERSION, Coverage, FileCoverage};
+pub use decoder::{DecodeError, LineDecoder};
+pub use delta::DeltaDict;
+pub use dialog::{dlg_str_id, extract_dialog_texts};
+pub use diff::{DiffEntry, MsgDiff};
+pub use display::{display_width, truncate_display};
+pub use document::{DocLine, DocLineKind, MsgDocument};
+#[cfg(any(test, feature = "cp1251"))]
+pub use encoding::{EncodePolicy, EncodingIssue, EncodingIssueKind};
+pub use error::{ParseError, line_column};
+pub use fields::{join_fields, split_fields};
+pub use frozen::FrozenMsg;
+pub use gaps::{Gap, GapKind};
+pub use global_map::{GlobalMapMsg, MAP_MUSIC_OFFSET, MAP_NAME_OFFSET, MAP_TRANSIT_OFFSET};
+pub use kind::{DlgMsg, GameMsg, ITEM_STR_MUL, MsgType, ObjMsg, TextMsg};
+pub use lazy::LazyMsgDictionary;
+pub use lenient::{ParseReport, SkippedLine, parse_msg_lenient};
+pub use lines::{LineEntry, parse_lines};
+pub use lint::{LintFile, LintFinding, LintIssue, LintRule, Linter, Severity};
+pub use lst::{LstFile, LstLine};
+pub use memory::MemoryUsage;
+pub use merge::{MergeConflict, MergeOptions, MergeResult, Resolution, merge3, merge3_with};
+pub use metrics::{ParseMetrics, parse_msg_with_metrics};
+pub use monotonic::{OrderIssue, fix_key_order, lint_key_order};
+pub use orphans::ReferenceSet;
+pub use pack::{LanguagePack, Manifest, ManifestEntry, ManifestMismatch};
+pub use page::Page;
+pub use patch::{Patch, PatchOp, emit_patch};
+pub use prefix::{PrefixParse, parse_msg_prefix};
+pub use provider::{FallbackChain, MsgProvider};
+pub use quest::{QUEST_MUL, QuestMsg};
+pub use read::{MsgRead, StubMsg};
+pub use reload::{ReloadEvent, ReloadablePack};
+pub use render::{RenderCache, render_lexems};
+pub use replace::{ChangedKey, ReplaceOptions};
+pub use review::{ReviewItem, ReviewKind, ReviewSession};
+pub use revision::{Revision, RevisionLog};
+pub use roundtrip::{Divergence, RoundtripReport, verify_roundtrip};
+pub use sanitize::{SanitizePolicy, SanitizedEntry};
+pub use schema::{IndexRange, Schema, SchemaView};
+#[cfg(feature = "rhai")]
+pub use script::ScriptTransform;
+pub use secondary::SecondaryKeyPolicy;
+pub use sections::{Section, section_entries};
+pub use stats::LengthStats;
+#[cfg(feature = "fxhash")]
+pub use storage::FxHashStorage;
+pub use storage::{HashStorage, MsgStorage, SortedVecStorage};
+pub use summary::{ScanSummary, scan_counts};
+pub use sync::SyncMsgDictionary;
+pub use telemetry::{LookupEvent, LookupEventKind};
+pub use tokens::{Token, TokenKind, lex};
+pub use transform::{Transform, TransformPipeline};
+pub use translit::{TranslitScheme, transliterate};
+pub use validate::{Diagnostic, validate_msg};
+pub use view::DictView;
+pub use writer::WriteOptions;
+pub use xref::{Reference, ScriptXref, XrefReport};
+
+/// Owned string value of a [`MsgLine`].
+///
+/// With the `inline-strings` feature short values are stored inline instead of
+/// in a separate allocation.
+#[cfg(not(feature = "inline-strings"))]
+pub type MsgStr = Box<str>;
+#[cfg(feature = "inline-strings")]
+pub type MsgStr = compact_str::CompactString;
+
+#[derive(Debug)]
 pub struct MsgDictionary {
-    index_to_line: BTreeMap<(u32, u32), MsgLine>,
+    index_to_line: Box<dyn MsgStorage>,
+    /// Keys in insertion order, if requested.
+    file_order: Option<Vec<(u32, u32)>>,
+    missing_template: Cow<'static, str>,
+    lookup_hook: Option<telemetry::LookupHook>,
+    /// Deprecated indices with their notes.
+    deprecated: BTreeMap<u32, Box<str>>,
+    /// Tags of entries, see [`MsgDictionary::add_tag`].
+    tags: BTreeMap<(u32, u32), BTreeSet<Box<str>>>,
+    /// Trailing same-line comments of entries.
+    comments: BTreeMap<(u32, u32), MsgLine>,
+    /// Redirects between indices, see [`MsgDictionary::add_alias`].
+    aliases: BTreeMap<u32, u32>,
+    /// Review states of entries, see [`MsgDictionary::set_review_state`].
+    review_states: BTreeMap<(u32, u32), ReviewState>,
+    /// Kept secondary keys, see [`SecondaryKeyPolicy::Keep`].
+    secondary_keys: BTreeMap<(u32, u32), Box<[u8]>>,
 }
 
-#[derive(Debug, PartialEq)]
+/// Entry taken out by [`MsgDictionary::remove_entry`] with everything kept about it.
+#[derive(Debug)]
+pub(crate) struct RemovedEntry {
+    pub value: MsgLine,
+    comment: Option<MsgLine>,
+    tags: Option<BTreeSet<Box<str>>>,
+    review_state: Option<ReviewState>,
+    secondary_key: Option<Box<[u8]>>,
+    /// Deprecation of the index, if this was its last entry.
+    deprecated: Option<Box<str>>,
+    file_position: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum MsgLine {
-    String(Box<str>),
+    String(MsgStr),
     Bytes(Box<[u8]>),
 }
 impl MsgLine {
@@ -29,50 +227,270 @@ impl MsgLine {
 }
 
 impl MsgDictionary {
-    fn new() -> Self {
+    pub fn new() -> Self {
+        Self::with_storage(BTreeMap::new())
+    }
+
+    /// Creates an empty dictionary backed by `storage`.
+    pub fn with_storage(storage: impl MsgStorage + 'static) -> Self {
+        Self {
+            index_to_line: Box::new(storage),
+            file_order: None,
+            missing_template: Cow::Borrowed(missing::DEFAULT_MISSING_TEMPLATE),
+            lookup_hook: None,
+            deprecated: BTreeMap::new(),
+            tags: BTreeMap::new(),
+            comments: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            review_states: BTreeMap::new(),
+            secondary_keys: BTreeMap::new(),
+        }
+    }
+
+    /// Moves every entry into `storage`, replacing the current backend.
+    pub fn into_storage(self, mut storage: impl MsgStorage + 'static) -> Self {
+        for (key, value) in self.index_to_line.into_entries() {
+            storage.insert(key, value);
+        }
         Self {
-            index_to_line: BTreeMap::new(),
+            index_to_line: Box::new(storage),
+            ..self
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.index_to_line.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index_to_line.is_empty()
+    }
+
     pub fn get_first_string(&self, index: u32) -> Option<&str> {
-        self.index_to_line
-            .get(&(index, 0))
-            .and_then(MsgLine::string)
+        self.lookup((index, 0), None).and_then(MsgLine::string)
     }
 
     pub fn get_first_bytes(&self, index: u32) -> Option<&[u8]> {
-        self.index_to_line.get(&(index, 0)).map(MsgLine::bytes)
+        self.lookup((index, 0), None).map(MsgLine::bytes)
     }
 
-    pub fn get_all_strings(&self, index: u32) -> impl Iterator<Item = (u32, &str)> {
-        self.index_to_line
-            .range((index, 0)..(index, u32::MAX))
+    /// First string among `indices`, tried in order, with the index it was found at,
+    /// e.g. a specific message, then a generic one, then a default.
+    pub fn get_first_string_of(&self, indices: &[u32]) -> Option<(u32, &str)> {
+        indices
+            .iter()
+            .find_map(|&index| Some((index, self.get_first_string(index)?)))
+    }
+
+    /// String values of `index` with their sub-indices, reversible with `.rev()`.
+    pub fn get_all_strings(&self, index: u32) -> impl DoubleEndedIterator<Item = (u32, &str)> {
+        self.resolved_range(index)
             .filter_map(|(&(_index, sub_index), value)| Some((sub_index, value.string()?)))
     }
 
-    pub fn insert(&mut self, index: u32, value: MsgLine) {
-        let sub_index = self
-            .index_to_line
-            .range((index, 0)..(index, u32::MAX))
-            .last()
-            .map(|((_index, sub_index), _value)| sub_index + 1)
-            .unwrap_or(0);
-        let old = self.index_to_line.insert((index, sub_index), value);
-        assert_eq!(old, None);
+    /// Value with the highest sub-index of `index`, if it is a string.
+    pub fn get_last_string(&self, index: u32) -> Option<&str> {
+        let (_key, value) = self.resolved_range(index).next_back()?;
+        value.string()
     }
 
-    pub fn iter_first_strings(&self) -> impl Iterator<Item = (u32, &str)> {
+    pub fn get_last_bytes(&self, index: u32) -> Option<&[u8]> {
+        let (_key, value) = self.resolved_range(index).next_back()?;
+        Some(value.bytes())
+    }
+
+    /// Number of values read for `index`.
+    pub fn count(&self, index: u32) -> u32 {
+        self.resolved_range(index).count() as u32
+    }
+
+    pub fn max_sub_index(&self, index: u32) -> Option<u32> {
+        self.resolved_range(index)
+            .next_back()
+            .map(|(&(_index, sub_index), _value)| sub_index)
+    }
+
+    /// Last sub-index stored under `index` itself, ignoring aliases.
+    pub(crate) fn stored_max_sub_index(&self, index: u32) -> Option<u32> {
+        self.index_to_line
+            .range(index)
+            .next_back()
+            .map(|(&(_index, sub_index), _value)| sub_index)
+    }
+
+    /// Distinct primary indices, in ascending order.
+    pub fn indices(&self) -> impl Iterator<Item = u32> + '_ {
+        let mut last = None;
         self.index_to_line
             .iter()
-            .filter_map(|(&(index, sub_index), value)| {
-                if sub_index == 0 {
-                    Some((index, value.string()?))
-                } else {
+            .filter_map(move |(&(index, _sub_index), _value)| {
+                if last == Some(index) {
                     None
+                } else {
+                    last = Some(index);
+                    Some(index)
                 }
             })
     }
+
+    /// Whether any primary index is present in both dictionaries.
+    pub fn intersects(&self, other: &MsgDictionary) -> bool {
+        self.intersection_keys(other).next().is_some()
+    }
+
+    /// Primary indices present in both dictionaries, in ascending order.
+    pub fn intersection_keys<'a>(
+        &'a self,
+        other: &'a MsgDictionary,
+    ) -> impl Iterator<Item = u32> + 'a {
+        self.readable_indices()
+            .filter(move |&index| other.resolved_range(index).next().is_some())
+    }
+
+    /// Appends `value` as the next sub-index of `index`.
+    ///
+    /// # Panics
+    /// If [`MsgDictionary::try_insert`] fails.
+    pub fn insert(&mut self, index: u32, value: MsgLine) {
+        if let Err(err) = self.try_insert(index, value) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Appends `value` as the next sub-index of `index`, returning that sub-index.
+    ///
+    /// Once `index` has a value at sub-index `u32::MAX`, further values are rejected
+    /// with [`InsertError::SubIndicesExhausted`]: sub-indices never wrap around and
+    /// existing values are never replaced.
+    pub fn try_insert(&mut self, index: u32, value: MsgLine) -> Result<u32, InsertError> {
+        let sub_index = match self.stored_max_sub_index(index) {
+            Some(max) => max
+                .checked_add(1)
+                .ok_or(InsertError::SubIndicesExhausted { index })?,
+            None => 0,
+        };
+        if self.index_to_line.get(&(index, sub_index)).is_some() {
+            return Err(InsertError::Occupied { index, sub_index });
+        }
+        self.index_to_line.insert((index, sub_index), value);
+        if let Some(order) = &mut self.file_order {
+            order.push((index, sub_index));
+        }
+        Ok(sub_index)
+    }
+
+    /// Re-packs entries into a [`SortedVecStorage`] with binary-search lookups.
+    ///
+    /// Meant for the read-only phase after loading, insertions become O(n).
+    pub fn compact(&mut self) {
+        let storage = std::mem::replace(&mut self.index_to_line, Box::new(BTreeMap::new()));
+        self.index_to_line = Box::new(SortedVecStorage::from(storage.into_entries()));
+    }
+
+    /// Removes every entry whose primary index is in `range`, returning how many were removed.
+    pub fn remove_range(&mut self, range: impl std::ops::RangeBounds<u32>) -> usize {
+        let keys: Vec<_> = self
+            .index_to_line
+            .iter()
+            .map(|(&key, _value)| key)
+            .filter(|(index, _sub_index)| range.contains(index))
+            .collect();
+        for &key in &keys {
+            self.remove_entry(key);
+        }
+        keys.len()
+    }
+
+    /// Removes the entry at `key` with its comment, tags, review state, secondary
+    /// key and position in file order, and the deprecation of its index once no
+    /// entry is left under it.
+    pub(crate) fn remove_entry(&mut self, key: (u32, u32)) -> Option<RemovedEntry> {
+        let value = self.index_to_line.remove(&key)?;
+        let file_position = self.file_order.as_mut().and_then(|order| {
+            let position = order.iter().position(|&ordered| ordered == key)?;
+            order.remove(position);
+            Some(position)
+        });
+        let deprecated = match self.index_to_line.range(key.0).next() {
+            Some(_) => None,
+            None => self.deprecated.remove(&key.0),
+        };
+        Some(RemovedEntry {
+            value,
+            comment: self.comments.remove(&key),
+            tags: self.tags.remove(&key),
+            review_state: self.review_states.remove(&key),
+            secondary_key: self.secondary_keys.remove(&key),
+            deprecated,
+            file_position,
+        })
+    }
+
+    /// Puts back an entry from [`remove_entry`](Self::remove_entry), possibly under another key.
+    pub(crate) fn restore_entry(&mut self, key: (u32, u32), entry: RemovedEntry) {
+        self.index_to_line.insert(key, entry.value);
+        if let (Some(order), Some(position)) = (&mut self.file_order, entry.file_position) {
+            order.insert(position.min(order.len()), key);
+        }
+        if let Some(note) = entry.deprecated {
+            self.deprecated.insert(key.0, note);
+        }
+        if let Some(comment) = entry.comment {
+            self.comments.insert(key, comment);
+        }
+        if let Some(tags) = entry.tags {
+            self.tags.insert(key, tags);
+        }
+        if let Some(state) = entry.review_state {
+            self.review_states.insert(key, state);
+        }
+        if let Some(secondary) = entry.secondary_key {
+            self.secondary_keys.insert(key, secondary);
+        }
+    }
+
+    pub fn iter_first_strings(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.readable_indices().filter_map(move |index| {
+            match self.resolved_range(index).next()? {
+                (&(_index, 0), value) => Some((index, value.string()?)),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// Value rejected by [`MsgDictionary::try_insert`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertError {
+    /// The storage already holds a value under the next sub-index.
+    Occupied { index: u32, sub_index: u32 },
+    /// The index already has a value at sub-index `u32::MAX`.
+    SubIndicesExhausted { index: u32 },
+}
+
+impl std::fmt::Display for InsertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InsertError::Occupied { index, sub_index } => {
+                write!(f, "entry [{}:{}] is already occupied", index, sub_index)
+            }
+            InsertError::SubIndicesExhausted { index } => {
+                write!(f, "no sub-indices left for index {}", index)
+            }
+        }
+    }
+}
+
+impl Default for MsgDictionary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for MsgDictionary {
+    fn eq(&self, other: &Self) -> bool {
+        self.index_to_line.iter().eq(other.index_to_line.iter())
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -95,38 +513,246 @@ struct Entry<I> {
     comment: Option<I>,
 }
 
-pub fn parse_msg(input: &[u8]) -> Result<MsgDictionary, String> {
-    parse_msg_ext(input, |bytes| match std::str::from_utf8(bytes) {
-        Ok(str) => MsgLine::String(str.into()),
-        Err(_) => MsgLine::Bytes(bytes.into()),
-    })
+pub fn parse_msg(input: &[u8]) -> Result<MsgDictionary, ParseError> {
+    parse_msg_with(input, LineDecoder::Utf8Strict)
 }
 
+pub fn parse_msg_with(input: &[u8], decoder: LineDecoder) -> Result<MsgDictionary, ParseError> {
+    let (dict, _failures) = parse_entries(
+        input,
+        MsgDictionary::new(),
+        SecondaryKeyPolicy::Error,
+        |bytes| Ok::<_, std::convert::Infallible>(decoder.decode(bytes)),
+        decoder,
+        &mut ParseMetrics::default(),
+    )?;
+    Ok(dict)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    pub decoder: LineDecoder,
+    /// Record entry order, see [`MsgDictionary::iter_in_file_order`].
+    pub preserve_order: bool,
+    /// Applied to every value after decoding.
+    pub transforms: TransformPipeline,
+    /// What to do with entries that have a secondary key.
+    pub secondary_keys: SecondaryKeyPolicy,
+}
+
+pub fn parse_msg_with_options(
+    input: &[u8],
+    options: &ParseOptions,
+) -> Result<MsgDictionary, ParseError> {
+    let (dict, _metrics) = parse_msg_with_metrics(input, options)?;
+    Ok(dict)
+}
+
+/// Parses with `decoder`, failing with every entry that can't be decoded.
+pub fn parse_msg_strict(
+    input: &[u8],
+    decoder: LineDecoder,
+) -> Result<MsgDictionary, TryParseError<DecodeError>> {
+    let (dict, failures) = parse_entries(
+        input,
+        MsgDictionary::new(),
+        SecondaryKeyPolicy::Error,
+        |bytes| decoder.decode_strict(bytes),
+        decoder,
+        &mut ParseMetrics::default(),
+    )
+    .map_err(TryParseError::Syntax)?;
+    if failures.is_empty() {
+        Ok(dict)
+    } else {
+        Err(TryParseError::Conversion(failures))
+    }
+}
+
+/// Parses with `line_converter` for values. Trailing comments are decoded with
+/// [`LineDecoder::Utf8Strict`].
 pub fn parse_msg_ext(
     input: &[u8],
     line_converter: impl Fn(&[u8]) -> MsgLine,
-) -> Result<MsgDictionary, String> {
-    let msg = lexer::tokenize_msg(input, true)?;
-    let mut dict = MsgDictionary::new();
-    for line in msg.lines {
+) -> Result<MsgDictionary, ParseError> {
+    let (dict, _failures) = parse_entries(
+        input,
+        MsgDictionary::new(),
+        SecondaryKeyPolicy::Error,
+        |bytes| Ok::<_, std::convert::Infallible>(line_converter(bytes)),
+        LineDecoder::Utf8Strict,
+        &mut ParseMetrics::default(),
+    )?;
+    Ok(dict)
+}
+
+/// Like [`parse_msg_ext`], but the converter may reject values.
+///
+/// Parsing continues past rejected values, so the error lists every failure.
+pub fn try_parse_msg_ext<E>(
+    input: &[u8],
+    line_converter: impl Fn(&[u8]) -> Result<MsgLine, E>,
+) -> Result<MsgDictionary, TryParseError<E>> {
+    let (dict, failures) = parse_entries(
+        input,
+        MsgDictionary::new(),
+        SecondaryKeyPolicy::Error,
+        line_converter,
+        LineDecoder::Utf8Strict,
+        &mut ParseMetrics::default(),
+    )
+    .map_err(TryParseError::Syntax)?;
+    if failures.is_empty() {
+        Ok(dict)
+    } else {
+        Err(TryParseError::Conversion(failures))
+    }
+}
+
+fn parse_entries<E>(
+    input: &[u8],
+    dict: MsgDictionary,
+    secondary_keys: SecondaryKeyPolicy,
+    line_converter: impl Fn(&[u8]) -> Result<MsgLine, E>,
+    comment_decoder: LineDecoder,
+    metrics: &mut ParseMetrics,
+) -> Result<(MsgDictionary, Vec<ConversionFailure<E>>), ParseError> {
+    let msg = scanner::tokenize_bytes(input, true)?;
+    metrics.bytes = input.len();
+    collect_entries(
+        input,
+        msg,
+        dict,
+        secondary_keys,
+        line_converter,
+        comment_decoder,
+        metrics,
+    )
+}
+
+/// Converts and inserts the entries of `msg`, tokenized from `input`.
+///
+/// Trailing comments are only decoded with `comment_decoder`, they don't go through
+/// `line_converter` and don't count in `metrics`.
+fn collect_entries<E>(
+    input: &[u8],
+    msg: Msg<&[u8]>,
+    mut dict: MsgDictionary,
+    secondary_keys: SecondaryKeyPolicy,
+    line_converter: impl Fn(&[u8]) -> Result<MsgLine, E>,
+    comment_decoder: LineDecoder,
+    metrics: &mut ParseMetrics,
+) -> Result<(MsgDictionary, Vec<ConversionFailure<E>>), ParseError> {
+    let mut failures = vec![];
+    let last = msg.lines.len().saturating_sub(1);
+    for (number, line) in msg.lines.into_iter().enumerate() {
         match line {
             Line::Entry(entry) => {
-                if !entry.secondary.is_empty() {
-                    panic!("Non-empty secondary key! {:?}", entry);
+                metrics.entries += 1;
+                if !entry.secondary.is_empty() && secondary_keys == SecondaryKeyPolicy::Error {
+                    let offset = entry.secondary.as_ptr() as usize - input.as_ptr() as usize;
+                    let (line, column) = error::line_column(input, offset);
+                    return Err(ParseError::Syntax {
+                        offset,
+                        line,
+                        column,
+                        message: format!("Non-empty secondary key for index {}", entry.index),
+                        help: Some("leave the second braces empty, e.g. {100}{}{text}".into()),
+                    });
+                }
+                let (comment, annotations) = entry
+                    .comment
+                    .map(comments::split_annotations)
+                    .unwrap_or_default();
+                for &part in &annotations {
+                    if let Some(note) = deprecation::annotation(part) {
+                        dict.deprecate(entry.index, &note);
+                    }
+                }
+                match line_converter(entry.value) {
+                    Ok(value) => {
+                        let sub_index = dict
+                            .try_insert(entry.index, value)
+                            .map_err(|err| ParseError::Invalid(err.to_string()))?;
+                        if secondary_keys == SecondaryKeyPolicy::Keep {
+                            dict.set_secondary_key(entry.index, sub_index, entry.secondary);
+                        }
+                        for &part in &annotations {
+                            if let Some(tags) = tags::annotation(part) {
+                                if !tags.is_empty() {
+                                    dict.tags.insert((entry.index, sub_index), tags);
+                                }
+                            }
+                            if let Some(state) = approval::annotation(part) {
+                                dict.set_review_state(entry.index, sub_index, state);
+                            }
+                        }
+                        if !comment.is_empty() {
+                            dict.comments
+                                .insert((entry.index, sub_index), comment_decoder.decode(&comment));
+                        }
+                    }
+                    Err(error) => failures.push(ConversionFailure {
+                        index: entry.index,
+                        offset: entry.value.as_ptr() as usize - input.as_ptr() as usize,
+                        error,
+                    }),
                 }
-                dict.insert(entry.index, line_converter(entry.value))
             }
-            Line::Break | Line::Comment(_) => {
-                //ignore line breaks and comments
+            // Text after the last line ending, not a line of its own.
+            Line::Break if number == last => {}
+            Line::Break => metrics.blank_lines += 1,
+            Line::Comment(comment) => {
+                metrics.comments += 1;
+                if let Some((from, to)) = aliases::annotation(comment) {
+                    dict.add_alias(from, to).map_err(ParseError::Invalid)?;
+                }
             }
         }
     }
-    Ok(dict)
+    Ok((dict, failures))
+}
+
+/// Value rejected by the converter passed to [`try_parse_msg_ext`].
+#[derive(Debug, PartialEq)]
+pub struct ConversionFailure<E> {
+    pub index: u32,
+    /// Byte offset of the value in the parsed input.
+    pub offset: usize,
+    pub error: E,
 }
 
+#[derive(Debug)]
+pub enum TryParseError<E> {
+    Syntax(ParseError),
+    Conversion(Vec<ConversionFailure<E>>),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for TryParseError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TryParseError::Syntax(err) => err.fmt(f),
+            TryParseError::Conversion(failures) => {
+                write!(f, "Failed to convert {} value(s):", failures.len())?;
+                for failure in failures {
+                    write!(
+                        f,
+                        " [{}] at {}: {};",
+                        failure.index, failure.offset, failure.error
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Note that values which fail to decode are stored lossily decoded, while values
+/// that decode cleanly are kept as bytes. Use [`parse_msg_with`] or [`parse_msg_strict`]
+/// with [`LineDecoder::Encoding`] for the opposite.
 #[cfg(any(test, feature = "cp1251"))]
-pub fn parse_cp1251_file<P: AsRef<std::path::Path>>(path: P) -> Result<MsgDictionary, String> {
-    let bytes = std::fs::read(path).map_err(|err| format!("IoError: {}", err))?;
+pub fn parse_cp1251_file<P: AsRef<std::path::Path>>(path: P) -> Result<MsgDictionary, ParseError> {
+    let bytes = compression::read_file(path)?;
 
     //println!("{:?}", cow.as_ref());
     parse_msg_ext(&bytes, |bytes| {
@@ -140,11 +766,40 @@ pub fn parse_cp1251_file<P: AsRef<std::path::Path>>(path: P) -> Result<MsgDictio
     })
 }
 
-pub fn parse_file<P: AsRef<std::path::Path>>(path: P) -> Result<MsgDictionary, String> {
-    let bytes = std::fs::read(path).map_err(|err| format!("IoError: {}", err))?;
+pub fn parse_file<P: AsRef<std::path::Path>>(path: P) -> Result<MsgDictionary, ParseError> {
+    let bytes = compression::read_file(path)?;
     parse_msg(&bytes)
 }
 
+/// Reads and parses a file with `decoder`, failing on the first value it can't decode.
+pub fn parse_file_strict<P: AsRef<std::path::Path>>(
+    path: P,
+    decoder: LineDecoder,
+) -> Result<MsgDictionary, ParseError> {
+    let bytes = compression::read_file(path)?;
+    let (dict, failures) = parse_entries(
+        &bytes,
+        MsgDictionary::new(),
+        SecondaryKeyPolicy::Error,
+        |bytes| decoder.decode_strict(bytes),
+        decoder,
+        &mut ParseMetrics::default(),
+    )?;
+    match failures.into_iter().next() {
+        Some(failure) => {
+            let (line, column) = error::line_column(&bytes, failure.offset);
+            Err(ParseError::Encoding {
+                index: failure.index,
+                offset: failure.offset,
+                line,
+                column,
+                error: failure.error,
+            })
+        }
+        None => Ok(dict),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,6 +825,167 @@ mod tests {
         assert_eq!(dict, correct);
     }
 
+    #[test]
+    fn try_parse_collects_failures() {
+        const SAMPLE: &[u8] = b"{1}{}{ok}\n{2}{}{bad}\n{3}{}{ok}\n{3}{}{bad}";
+        let res = try_parse_msg_ext(SAMPLE, |bytes| match bytes {
+            b"bad" => Err("bad value"),
+            _ => Ok(MsgLine::Bytes(bytes.into())),
+        });
+        match res {
+            Err(TryParseError::Conversion(failures)) => assert_eq!(
+                failures,
+                vec![
+                    ConversionFailure {
+                        index: 2,
+                        offset: 16,
+                        error: "bad value"
+                    },
+                    ConversionFailure {
+                        index: 3,
+                        offset: 37,
+                        error: "bad value"
+                    },
+                ]
+            ),
+            other => panic!("unexpected {:?}", other),
+        }
+        let res = try_parse_msg_ext(b"{1}{}{a}\njunk", |bytes| {
+            Ok::<_, ()>(MsgLine::Bytes(bytes.into()))
+        });
+        assert!(matches!(
+            res,
+            Err(TryParseError::Syntax(ParseError::NonExhaustive {
+                line: 2,
+                ..
+            }))
+        ));
+        let dict = try_parse_msg_ext(b"{1}{}{ok} # bad", |bytes| match bytes {
+            b"bad" => Err("rejected"),
+            _ => Ok(MsgLine::Bytes(bytes.into())),
+        })
+        .unwrap();
+        assert_eq!(dict.comment(1, 0), Some("bad"));
+    }
+
+    #[test]
+    fn strict_cp1251() {
+        const SAMPLE: &[u8] = b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2}\n{2}{}{ok\xff}";
+        let utf8 = parse_msg_strict(SAMPLE, LineDecoder::Utf8Strict);
+        match utf8 {
+            Err(TryParseError::Conversion(failures)) => {
+                let offsets: Vec<_> = failures
+                    .iter()
+                    .map(|failure| (failure.index, failure.offset + failure.error.valid_up_to))
+                    .collect();
+                assert_eq!(offsets, vec![(1, 6), (2, 22)]);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        let cp1251 =
+            parse_msg_strict(SAMPLE, LineDecoder::Encoding(encoding_rs::WINDOWS_1251)).unwrap();
+        assert_eq!(cp1251.get_first_string(1), Some("Привет"));
+        assert_eq!(cp1251.get_first_string(2), Some("okя"));
+    }
+
+    #[test]
+    fn compact_keeps_contents() {
+        let mut dict = parse_msg(b"{10}{}{a}\n{15}{}{b}\n{15}{}{c}\n{20}{}{d}").unwrap();
+        let before = dict.memory_usage();
+        dict.compact();
+        assert!(dict.memory_usage().map_overhead < before.map_overhead);
+        assert_eq!(dict.get_first_string(10), Some("a"));
+        assert_eq!(
+            dict.get_all_strings(15).collect::<Vec<_>>(),
+            vec![(0, "b"), (1, "c")]
+        );
+        assert_eq!(dict.get_first_string(16), None);
+        dict.insert(15, MsgLine::String("e".into()));
+        assert_eq!(dict.get_all_strings(15).count(), 3);
+    }
+
+    #[test]
+    fn count_sub_indices() {
+        let dict = parse_msg(b"{10}{}{a}\n{15}{}{b}\n{15}{}{c}").unwrap();
+        assert_eq!(dict.count(15), 2);
+        assert_eq!(dict.count(16), 0);
+        assert_eq!(dict.max_sub_index(15), Some(1));
+        assert_eq!(dict.max_sub_index(10), Some(0));
+        assert_eq!(dict.max_sub_index(11), None);
+    }
+
+    #[test]
+    fn try_insert_sub_indices() {
+        let mut dict = MsgDictionary::new();
+        assert_eq!(dict.try_insert(7, MsgLine::String("a".into())), Ok(0));
+        assert_eq!(dict.try_insert(7, MsgLine::String("b".into())), Ok(1));
+        assert_eq!(dict.try_insert(8, MsgLine::String("c".into())), Ok(0));
+        assert_eq!(dict.get_last_string(7), Some("b"));
+    }
+
+    #[test]
+    fn sub_index_exhaustion() {
+        let mut dict = mock_dict(&[((7, u32::MAX - 1), "a")]);
+        assert_eq!(
+            dict.try_insert(7, MsgLine::String("b".into())),
+            Ok(u32::MAX)
+        );
+        assert_eq!(
+            dict.try_insert(7, MsgLine::String("c".into())),
+            Err(InsertError::SubIndicesExhausted { index: 7 })
+        );
+        assert_eq!(dict.count(7), 2);
+        assert_eq!(dict.get_last_string(7), Some("b"));
+    }
+
+    #[test]
+    fn intersection() {
+        let left = parse_msg(b"{10}{}{a}\n{15}{}{b}\n{15}{}{c}\n{20}{}{d}").unwrap();
+        let right = parse_msg(b"{15}{}{x}\n{20}{}{y}\n{30}{}{z}").unwrap();
+        let other = parse_msg(b"{1}{}{x}").unwrap();
+        assert!(left.intersects(&right));
+        assert!(!left.intersects(&other));
+        assert_eq!(
+            left.intersection_keys(&right).collect::<Vec<_>>(),
+            vec![15, 20]
+        );
+    }
+
+    #[test]
+    fn remove_index_range() {
+        let mut dict = parse_msg(b"{10}{}{a}\n{15}{}{b}\n{15}{}{c}\n{20}{}{d}").unwrap();
+        assert_eq!(dict.remove_range(11..20), 2);
+        assert_eq!(dict, mock_dict(&[((10, 0), "a"), ((20, 0), "d")]));
+        assert_eq!(dict.remove_range(30..40), 0);
+    }
+
+    #[test]
+    fn removed_entries_leave_no_metadata() {
+        let mut dict = parse_msg(b"{10}{}{a}\n{15}{}{b}").unwrap();
+        dict.set_comment(15, 0, "note").unwrap();
+        dict.add_tag(15, 0, "ui");
+        dict.set_review_state(15, 0, ReviewState::Approved);
+        dict.deprecate(15, "old");
+        assert_eq!(dict.remove_range(15..=15), 1);
+        dict.insert(15, MsgLine::String("c".into()));
+        assert_eq!(dict.comment(15, 0), None);
+        assert_eq!(dict.tags(15, 0).count(), 0);
+        assert_eq!(dict.review_state(15, 0), None);
+        assert_eq!(dict.deprecation(15), None);
+    }
+
+    #[test]
+    fn last_and_reverse() {
+        let dict = parse_msg(b"{15}{}{a}\n{15}{}{b}\n{15}{}{c}\n{16}{}{d}").unwrap();
+        assert_eq!(dict.get_last_string(15), Some("c"));
+        assert_eq!(dict.get_last_bytes(16), Some(&b"d"[..]));
+        assert_eq!(dict.get_last_string(17), None);
+        assert_eq!(
+            dict.get_all_strings(15).rev().collect::<Vec<_>>(),
+            vec![(2, "c"), (1, "b"), (0, "a")]
+        );
+    }
+
     fn mock_dict(data: &[((u32, u32), &str)]) -> MsgDictionary {
         let mut dict = MsgDictionary::new();
         for &((index, sub_index), value) in data {
@@ -179,36 +995,26 @@ mod tests {
         dict
     }
 
-    fn file_id<P: AsRef<std::path::Path>>(file: P) -> Option<String> {
-        let file = file.as_ref();
-        Some(format!(
-            "{}/{}",
-            file.parent()?.file_name()?.to_str()?,
-            file.file_name()?.to_str()?,
-        ))
+    #[test]
+    fn first_string_of_candidates() {
+        let dict = parse_msg(b"{10}{}{generic}\n{1}{}{default}\n").unwrap();
+        assert_eq!(
+            dict.get_first_string_of(&[12, 10, 1]),
+            Some((10, "generic"))
+        );
+        assert_eq!(dict.get_first_string_of(&[12, 1]), Some((1, "default")));
+        assert_eq!(dict.get_first_string_of(&[12, 13]), None);
+        assert_eq!(dict.get_first_string_of(&[]), None);
     }
 
+    /// Set `FO_MSG_CORPUS` to directories of real MSG files to check them.
     #[test]
-    fn parse_all_forp_msg_files() {
-        let mut vec = vec![];
-        for dir in &["../../../FO4RP/text/engl"] {
-            //, "../../../FO4RP/text/russ"] {
-            for file in std::fs::read_dir(dir).unwrap() {
-                let path = file.unwrap().path();
-                if let Some(ext) = path.extension() {
-                    if &ext.to_str().unwrap().to_uppercase() == "MSG" {
-                        //if path.file_name().unwrap() == "FOGM.MSG" {
-                        let path_str = path.to_str().unwrap();
-                        let res = parse_cp1251_file(&path).expect(path_str);
-                        vec.push((file_id(path).unwrap(), res));
-                    }
-                }
-            }
+    fn parse_corpus_from_env() {
+        let dirs = corpus::corpus_dirs_from_env();
+        if dirs.is_empty() {
+            return;
         }
-        /*for (file, dict) in vec {
-            for ((index, sub_index), value) in &dict.index_to_string {
-                println!("[{}][{}][{}] \"{}\"", file, index, sub_index, value);
-            }
-        }*/
+        let report = corpus::run_corpus(&dirs).unwrap();
+        assert!(report.all_parsed(), "{}", report.to_json());
     }
 }
@@ -0,0 +1,123 @@
+use std::ops::Range;
+
+use super::{
+    Line, LineDecoder, MsgLine, ParseError,
+    scanner::{Scanner, find_byte},
+};
+
+/// Entry returned by [`parse_lines`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineEntry {
+    /// 1-based line the entry starts on.
+    pub line: usize,
+    pub index: u32,
+    pub value: MsgLine,
+    /// Comment after the entry on its last line, without the marker.
+    pub comment: Option<String>,
+}
+
+/// Entries starting on lines `lines` (1-based) of `input`, for editors that fill
+/// previews of huge files as the user scrolls.
+///
+/// Input is scanned only up to `lines.end`. Malformed lines before the range are
+/// skipped, so only errors on lines of the range fail.
+pub fn parse_lines(
+    input: &[u8],
+    lines: Range<usize>,
+    decoder: LineDecoder,
+) -> Result<Vec<LineEntry>, ParseError> {
+    let mut scanner = Scanner::new(input);
+    let mut entries = vec![];
+    let mut line = 1;
+    while line < lines.end {
+        let start = scanner.pos;
+        let in_range = line >= lines.start;
+        let complete = match scanner.line() {
+            Ok(Line::Entry(entry)) if in_range => {
+                entries.push(LineEntry {
+                    line,
+                    index: entry.index,
+                    value: decoder.decode(entry.value),
+                    comment: entry
+                        .comment
+                        .map(|comment| String::from_utf8_lossy(comment).into_owned()),
+                });
+                true
+            }
+            Ok(_) => true,
+            Err(err) if in_range => return Err(ParseError::scan(input, err)),
+            Err(_) => false,
+        };
+        if complete && scanner.newline() {
+            // Values may span lines.
+            line += count_lines(&input[start..scanner.pos]);
+            continue;
+        }
+        if complete && scanner.rest().is_empty() {
+            break;
+        }
+        if in_range {
+            return Err(ParseError::non_exhaustive(input, scanner.pos));
+        }
+        // Resync at the start of the next line.
+        let broken = if complete { scanner.pos } else { start };
+        match find_byte(b'\n', &input[broken..]) {
+            Some(len) => scanner.pos = broken + len + 1,
+            None => break,
+        }
+        line += count_lines(&input[start..scanner.pos]);
+    }
+    Ok(entries)
+}
+
+fn count_lines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&byte| byte == b'\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_window() {
+        let input = b"# header\n{1}{}{a}\n{2}{}{multi\nline}\n\n{3}{}{c} # hint\n{4}{}{d}\n";
+        let entries = parse_lines(input, 3..7, LineDecoder::Utf8Strict).unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.line, entry.index))
+                .collect::<Vec<_>>(),
+            vec![(3, 2), (6, 3)]
+        );
+        assert_eq!(entries[0].value, MsgLine::String("multi\nline".into()));
+        assert_eq!(entries[1].comment.as_deref(), Some("hint"));
+        assert!(
+            parse_lines(input, 100..200, LineDecoder::Utf8Strict)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(parse_lines(b"{1}{}{", 1..2, LineDecoder::Utf8Strict).is_err());
+    }
+
+    #[test]
+    fn errors_outside_the_window() {
+        let input = b"{1}{}{a}\n{x}{}{b}\n{3}{}{c\nd}\n{4}{}{e\nf} junk\n{5}{}{";
+        let window = |lines| {
+            parse_lines(input, lines, LineDecoder::Utf8Strict)
+                .map(|entries| entries.iter().map(|entry| entry.index).collect::<Vec<_>>())
+        };
+        assert_eq!(window(3..5).unwrap(), vec![3]);
+        assert_eq!(window(4..5).unwrap(), vec![]);
+        assert!(matches!(
+            window(2..3),
+            Err(ParseError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            window(5..6),
+            Err(ParseError::NonExhaustive { line: 6, .. })
+        ));
+        assert_eq!(window(6..7).unwrap(), vec![]);
+        assert!(window(1..usize::MAX).is_err());
+        assert!(window(7..usize::MAX).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::{collections::btree_map::BTreeMap, fmt};
+
+use super::{LineDecoder, LineEntry, lint_key_order, parse_lines, validate_msg};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The rule doesn't run.
+    Allow,
+    Warning,
+    Error,
+}
+
+/// File handed to each [`LintRule`].
+#[derive(Debug)]
+pub struct LintFile<'a> {
+    pub name: &'a str,
+    pub input: &'a [u8],
+    /// Decoded entries, empty if the file doesn't parse.
+    pub entries: &'a [LineEntry],
+}
+
+/// Problem reported by a rule, before its severity is applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// 1-based line.
+    pub line: usize,
+    pub message: String,
+}
+
+/// Check run by a [`Linter`] on every file, e.g. a project's own domain rules.
+pub trait LintRule: Send + Sync {
+    /// Name used in severity configuration and `# lint: allow(name)` annotations.
+    fn name(&self) -> &str;
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, file: &LintFile<'_>) -> Vec<LintIssue>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFinding {
+    pub rule: String,
+    pub severity: Severity,
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Allow => "allow",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}:{}: {}[{}]: {}",
+            self.file, self.line, severity, self.rule, self.message
+        )
+    }
+}
+
+/// Registry of lint rules with per-rule severities.
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+    severities: BTreeMap<String, Severity>,
+    decoder: LineDecoder,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Linter {
+    /// Linter with the built-in `syntax` and `key-order` rules.
+    pub fn new() -> Self {
+        Self::empty().with_rule(SyntaxRule).with_rule(KeyOrderRule)
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            rules: vec![],
+            severities: BTreeMap::new(),
+            decoder: LineDecoder::Utf8Lossy,
+        }
+    }
+
+    pub fn with_rule(mut self, rule: impl LintRule + 'static) -> Self {
+        self.register(rule);
+        self
+    }
+
+    pub fn register(&mut self, rule: impl LintRule + 'static) {
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Overrides the default severity of the rule `name`.
+    pub fn set_severity(&mut self, name: &str, severity: Severity) {
+        self.severities.insert(name.into(), severity);
+    }
+
+    /// Decoder for the entries rules see, UTF-8 by default.
+    pub fn with_decoder(mut self, decoder: LineDecoder) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
+    pub fn rule_names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|rule| rule.name())
+    }
+
+    /// Runs every enabled rule on the file `name`, except those suppressed in it by
+    /// `# lint: allow(rule, ...)` comments.
+    pub fn lint(&self, name: &str, input: &[u8]) -> Vec<LintFinding> {
+        let entries = parse_lines(input, 1..usize::MAX, self.decoder).unwrap_or_default();
+        let file = LintFile {
+            name,
+            input,
+            entries: &entries,
+        };
+        let suppressed = suppressions(input);
+        let mut findings = vec![];
+        for rule in &self.rules {
+            let severity = self
+                .severities
+                .get(rule.name())
+                .copied()
+                .unwrap_or_else(|| rule.default_severity());
+            if severity == Severity::Allow || suppressed.iter().any(|name| name == rule.name()) {
+                continue;
+            }
+            findings.extend(rule.check(&file).into_iter().map(|issue| LintFinding {
+                rule: rule.name().into(),
+                severity,
+                file: name.into(),
+                line: issue.line,
+                message: issue.message,
+            }));
+        }
+        findings.sort_by_key(|finding| finding.line);
+        findings
+    }
+}
+
+/// Rule names listed in `# lint: allow(...)` comment lines.
+fn suppressions(input: &[u8]) -> Vec<String> {
+    let input = String::from_utf8_lossy(input);
+    let mut names = vec![];
+    for line in input.lines() {
+        let line = line.trim();
+        let comment = match line.strip_prefix('#').or_else(|| line.strip_prefix("//")) {
+            Some(comment) => comment.trim(),
+            None => continue,
+        };
+        let list = comment
+            .strip_prefix("lint:")
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix("allow("))
+            .and_then(|rest| rest.strip_suffix(')'));
+        if let Some(list) = list {
+            names.extend(list.split(',').map(|name| name.trim().to_owned()));
+        }
+    }
+    names
+}
+
+/// 1-based line of the byte `offset`.
+fn line_of(input: &[u8], offset: usize) -> usize {
+    1 + input[..offset.min(input.len())]
+        .iter()
+        .filter(|&&byte| byte == b'\n')
+        .count()
+}
+
+/// [`validate_msg`] as a rule.
+struct SyntaxRule;
+
+impl LintRule for SyntaxRule {
+    fn name(&self) -> &str {
+        "syntax"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &LintFile<'_>) -> Vec<LintIssue> {
+        validate_msg(file.input)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|diagnostic| LintIssue {
+                line: line_of(file.input, diagnostic.offset),
+                message: match diagnostic.suggestion {
+                    Some(suggestion) => format!("{}, help: {}", diagnostic.message, suggestion),
+                    None => diagnostic.message,
+                },
+            })
+            .collect()
+    }
+}
+
+/// [`lint_key_order`] as a rule.
+struct KeyOrderRule;
+
+impl LintRule for KeyOrderRule {
+    fn name(&self) -> &str {
+        "key-order"
+    }
+
+    fn check(&self, file: &LintFile<'_>) -> Vec<LintIssue> {
+        lint_key_order(file.input)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|issue| LintIssue {
+                line: issue.line,
+                message: format!("Index {} after {}", issue.index, issue.previous),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoLatin;
+
+    impl LintRule for NoLatin {
+        fn name(&self) -> &str {
+            "no-latin"
+        }
+
+        fn check(&self, file: &LintFile<'_>) -> Vec<LintIssue> {
+            file.entries
+                .iter()
+                .filter(|entry| {
+                    entry
+                        .value
+                        .string()
+                        .unwrap_or_default()
+                        .chars()
+                        .any(|ch| ch.is_ascii_alphabetic())
+                })
+                .map(|entry| LintIssue {
+                    line: entry.line,
+                    message: format!("Latin letters in {}", entry.index),
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn custom_rules_and_severities() {
+        let mut linter = Linter::new().with_rule(NoLatin);
+        let input = "{2}{}{Привет}\n{1}{}{Hello}\n".as_bytes();
+        let findings = linter.lint("russ/FOTEXT.MSG", input);
+        assert_eq!(
+            findings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "russ/FOTEXT.MSG:2: warning[key-order]: Index 1 after 2",
+                "russ/FOTEXT.MSG:2: warning[no-latin]: Latin letters in 1",
+            ]
+        );
+
+        linter.set_severity("key-order", Severity::Allow);
+        linter.set_severity("no-latin", Severity::Error);
+        let findings = linter.lint("russ/FOTEXT.MSG", input);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Error);
+
+        let broken = b"{1}{}{a}\n{x}{}{b}\n";
+        let findings = linter.lint("broken.msg", broken);
+        assert_eq!(findings[0].rule, "syntax");
+        assert_eq!(findings[0].line, 2);
+    }
+
+    #[test]
+    fn suppression_comments() {
+        let linter = Linter::new().with_rule(NoLatin);
+        let input = b"# lint: allow(no-latin, key-order)\n{2}{}{a}\n{1}{}{b}\n";
+        assert!(linter.lint("a.msg", input).is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::Write;
+
+/// Line of a `.lst` list file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LstLine {
+    /// `[index] name`, e.g. `12 ARMOR.MSG` or `intro.ogg`.
+    Entry {
+        index: Option<u32>,
+        name: String,
+    },
+    /// Text after `#`, without the marker.
+    Comment(String),
+    Blank,
+}
+
+/// `.lst` list file with comments and blank lines kept for writing back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LstFile {
+    pub lines: Vec<LstLine>,
+}
+
+impl LstFile {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut lines = vec![];
+        for (number, line) in input.lines().enumerate() {
+            let line = line.trim();
+            let parsed = if line.is_empty() {
+                LstLine::Blank
+            } else if let Some(comment) = line.strip_prefix('#') {
+                LstLine::Comment(comment.trim_start().to_owned())
+            } else {
+                let mut fields = line.split_whitespace();
+                let first = fields.next().unwrap_or_default();
+                let (index, name) = match first.parse::<u32>() {
+                    Ok(index) => (Some(index), fields.next()),
+                    Err(_) => (None, Some(first)),
+                };
+                match (name, fields.next()) {
+                    (Some(name), None) => LstLine::Entry {
+                        index,
+                        name: name.to_owned(),
+                    },
+                    _ => return Err(format!("Malformed line {}: {}", number + 1, line)),
+                }
+            };
+            lines.push(parsed);
+        }
+        Ok(Self { lines })
+    }
+
+    /// `(index, name)` of every entry.
+    pub fn entries(&self) -> impl Iterator<Item = (Option<u32>, &str)> {
+        self.lines.iter().filter_map(|line| match line {
+            LstLine::Entry { index, name } => Some((*index, name.as_str())),
+            _ => None,
+        })
+    }
+
+    /// Name of the entry with `index`.
+    pub fn get(&self, index: u32) -> Option<&str> {
+        self.entries()
+            .find(|&(entry, _name)| entry == Some(index))
+            .map(|(_index, name)| name)
+    }
+
+    pub fn to_lst_string(&self) -> String {
+        let mut out = String::new();
+        for line in &self.lines {
+            let _ = match line {
+                LstLine::Entry {
+                    index: Some(index),
+                    name,
+                } => writeln!(out, "{} {}", index, name),
+                LstLine::Entry { index: None, name } => writeln!(out, "{}", name),
+                LstLine::Comment(comment) => writeln!(out, "# {}", comment),
+                LstLine::Blank => writeln!(out),
+            };
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_write() {
+        let input = "# Msg files\n0 TEXT.MSG\n1  DLG.MSG\n\nintro.ogg\n";
+        let lst = LstFile::parse(input).unwrap();
+        assert_eq!(
+            lst.entries().collect::<Vec<_>>(),
+            vec![
+                (Some(0), "TEXT.MSG"),
+                (Some(1), "DLG.MSG"),
+                (None, "intro.ogg")
+            ]
+        );
+        assert_eq!(lst.get(1), Some("DLG.MSG"));
+        assert_eq!(
+            lst.to_lst_string(),
+            "# Msg files\n0 TEXT.MSG\n1 DLG.MSG\n\nintro.ogg\n"
+        );
+        assert!(LstFile::parse("1 a b\n").is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use super::{MsgDictionary, MsgLine, MsgStr};
+
+/// Approximate heap footprint of a [`MsgDictionary`], in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryUsage {
+    pub entries: usize,
+    /// Keys, value headers and storage structure, see [`crate::MsgStorage::heap_overhead`].
+    pub map_overhead: usize,
+    /// Heap bytes owned by `MsgLine::String` values.
+    pub string_bytes: usize,
+    /// Heap bytes owned by `MsgLine::Bytes` values.
+    pub bytes_bytes: usize,
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> usize {
+        self.map_overhead + self.string_bytes + self.bytes_bytes
+    }
+}
+
+impl MsgDictionary {
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            entries: self.len(),
+            map_overhead: self.index_to_line.heap_overhead(),
+            ..MemoryUsage::default()
+        };
+        for (_key, line) in self.index_to_line.iter() {
+            match line {
+                MsgLine::String(string) => usage.string_bytes += heap_len(string),
+                MsgLine::Bytes(bytes) => usage.bytes_bytes += bytes.len(),
+            }
+        }
+        usage
+    }
+}
+
+#[cfg(not(feature = "inline-strings"))]
+fn heap_len(string: &MsgStr) -> usize {
+    string.len()
+}
+
+#[cfg(feature = "inline-strings")]
+fn heap_len(string: &MsgStr) -> usize {
+    if string.is_heap_allocated() {
+        string.capacity()
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_value_bytes() {
+        let mut dict = MsgDictionary::new();
+        dict.insert(1, MsgLine::String("four".into()));
+        dict.insert(1, MsgLine::Bytes(b"\xff\xfe"[..].into()));
+        let usage = dict.memory_usage();
+        assert_eq!(usage.entries, 2);
+        #[cfg(not(feature = "inline-strings"))]
+        assert_eq!(usage.string_bytes, 4);
+        assert_eq!(usage.bytes_bytes, 2);
+        assert!(usage.total() > 6);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::btree_set::BTreeSet;
+
+use super::{MsgDictionary, MsgLine};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MergeOptions {
+    /// When both sides changed a string value, try a line-based three-way merge
+    /// of it before reporting a conflict.
+    pub merge_lines: bool,
+}
+
+/// Key changed differently on both sides. `None` means the key is absent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub key: (u32, u32),
+    pub base: Option<MsgLine>,
+    pub ours: Option<MsgLine>,
+    pub theirs: Option<MsgLine>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MergeResult {
+    /// Merged entries; conflicting keys keep our value.
+    pub merged: MsgDictionary,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// How [`merge3_with`] should settle a conflict.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    Base,
+    Ours,
+    Theirs,
+    Value(MsgLine),
+    Remove,
+    /// Keep our value and report the conflict.
+    Conflict,
+}
+
+/// Three-way merge of two dictionaries derived from `base`.
+pub fn merge3(
+    base: &MsgDictionary,
+    ours: &MsgDictionary,
+    theirs: &MsgDictionary,
+    options: MergeOptions,
+) -> MergeResult {
+    merge3_with(base, ours, theirs, options, |_conflict| {
+        Resolution::Conflict
+    })
+}
+
+/// Like [`merge3`], but every conflict is first offered to `resolver`.
+pub fn merge3_with(
+    base: &MsgDictionary,
+    ours: &MsgDictionary,
+    theirs: &MsgDictionary,
+    options: MergeOptions,
+    mut resolver: impl FnMut(&MergeConflict) -> Resolution,
+) -> MergeResult {
+    let keys: BTreeSet<(u32, u32)> = [base, ours, theirs]
+        .iter()
+        .flat_map(|dict| dict.index_to_line.iter().map(|(&key, _value)| key))
+        .collect();
+    let mut merged = MsgDictionary::new();
+    let mut conflicts = vec![];
+    for key in keys {
+        let base = base.index_to_line.get(&key);
+        let ours = ours.index_to_line.get(&key);
+        let theirs = theirs.index_to_line.get(&key);
+        let resolved = if ours == theirs || theirs == base {
+            ours.cloned()
+        } else if ours == base {
+            theirs.cloned()
+        } else if let Some(value) = options
+            .merge_lines
+            .then(|| merge_values(base?, ours?, theirs?))
+            .flatten()
+        {
+            Some(value)
+        } else {
+            let conflict = MergeConflict {
+                key,
+                base: base.cloned(),
+                ours: ours.cloned(),
+                theirs: theirs.cloned(),
+            };
+            match resolver(&conflict) {
+                Resolution::Base => conflict.base,
+                Resolution::Ours => conflict.ours,
+                Resolution::Theirs => conflict.theirs,
+                Resolution::Value(value) => Some(value),
+                Resolution::Remove => None,
+                Resolution::Conflict => {
+                    let ours = conflict.ours.clone();
+                    conflicts.push(conflict);
+                    ours
+                }
+            }
+        };
+        if let Some(value) = resolved {
+            merged.index_to_line.insert(key, value);
+        }
+    }
+    MergeResult { merged, conflicts }
+}
+
+fn merge_values(base: &MsgLine, ours: &MsgLine, theirs: &MsgLine) -> Option<MsgLine> {
+    let lines = |line: &MsgLine| -> Option<Vec<String>> {
+        Some(line.string()?.split('\n').map(str::to_owned).collect())
+    };
+    let merged = merge_lines(&lines(base)?, &lines(ours)?, &lines(theirs)?)?;
+    Some(MsgLine::String(merged.join("\n").into()))
+}
+
+/// Indices of a longest common subsequence of `left` and `right`, as increasing pairs.
+pub(crate) fn lcs_pairs<T: PartialEq>(left: &[T], right: &[T]) -> Vec<(usize, usize)> {
+    let mut table = vec![vec![0u32; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            table[i][j] = if left[i] == right[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = vec![];
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// diff3-style merge: `None` if both sides changed the same region differently.
+fn merge_lines(base: &[String], ours: &[String], theirs: &[String]) -> Option<Vec<String>> {
+    let mut to_ours = vec![None; base.len()];
+    for (b, o) in lcs_pairs(base, ours) {
+        to_ours[b] = Some(o);
+    }
+    let mut to_theirs = vec![None; base.len()];
+    for (b, t) in lcs_pairs(base, theirs) {
+        to_theirs[b] = Some(t);
+    }
+
+    let mut out = vec![];
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        let stable = (b..base.len()).find_map(|pos| Some((pos, to_ours[pos]?, to_theirs[pos]?)));
+        let (next_b, next_o, next_t) = stable.unwrap_or((base.len(), ours.len(), theirs.len()));
+        let base_chunk = &base[b..next_b];
+        let ours_chunk = &ours[o..next_o];
+        let theirs_chunk = &theirs[t..next_t];
+        if ours_chunk == base_chunk || ours_chunk == theirs_chunk {
+            out.extend_from_slice(theirs_chunk);
+        } else if theirs_chunk == base_chunk {
+            out.extend_from_slice(ours_chunk);
+        } else {
+            return None;
+        }
+        if stable.is_none() {
+            return Some(out);
+        }
+        out.push(base[next_b].clone());
+        b = next_b + 1;
+        o = next_o + 1;
+        t = next_t + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn merges_independent_changes() {
+        let base = parse_msg(b"{1}{}{a}\n{2}{}{b}\n{3}{}{c}").unwrap();
+        let ours = parse_msg(b"{1}{}{A}\n{2}{}{b}\n{3}{}{c}\n{4}{}{d}").unwrap();
+        let theirs = parse_msg(b"{1}{}{a}\n{2}{}{B}").unwrap();
+        let result = merge3(&base, &ours, &theirs, MergeOptions::default());
+        assert_eq!(result.conflicts, vec![]);
+        assert_eq!(
+            result.merged,
+            parse_msg(b"{1}{}{A}\n{2}{}{B}\n{4}{}{d}").unwrap()
+        );
+    }
+
+    #[test]
+    fn resolver_settles_conflicts() {
+        let base = parse_msg(b"{1}{}{a}\n{2}{}{b}").unwrap();
+        let ours = parse_msg(b"{1}{}{ours}\n{2}{}{ours}").unwrap();
+        let theirs = parse_msg(b"{1}{}{theirs}\n{2}{}{theirs}").unwrap();
+        let mut seen = vec![];
+        let result = merge3_with(&base, &ours, &theirs, MergeOptions::default(), |conflict| {
+            seen.push(conflict.key);
+            match conflict.key.0 {
+                1 => Resolution::Theirs,
+                _ => Resolution::Conflict,
+            }
+        });
+        assert_eq!(seen, vec![(1, 0), (2, 0)]);
+        assert_eq!(result.merged.get_first_string(1), Some("theirs"));
+        assert_eq!(result.merged.get_first_string(2), Some("ours"));
+        assert_eq!(result.conflicts.len(), 1);
+    }
+
+    #[test]
+    fn line_merge_inside_values() {
+        let base = parse_msg(b"{1}{}{one\ntwo\nthree}").unwrap();
+        let ours = parse_msg(b"{1}{}{ONE\ntwo\nthree}").unwrap();
+        let theirs = parse_msg(b"{1}{}{one\ntwo\nTHREE}").unwrap();
+
+        let plain = merge3(&base, &ours, &theirs, MergeOptions::default());
+        assert_eq!(plain.conflicts.len(), 1);
+        assert_eq!(plain.merged.get_first_string(1), Some("ONE\ntwo\nthree"));
+
+        let options = MergeOptions { merge_lines: true };
+        let merged = merge3(&base, &ours, &theirs, options);
+        assert_eq!(merged.conflicts, vec![]);
+        assert_eq!(merged.merged.get_first_string(1), Some("ONE\ntwo\nTHREE"));
+
+        let clash = parse_msg(b"{1}{}{one\ntwo\n3}").unwrap();
+        let result = merge3(&base, &theirs, &clash, options);
+        assert_eq!(result.conflicts[0].key, (1, 0));
+    }
+}
@@ -0,0 +1,111 @@
+use std::{cell::Cell, time::Duration};
+
+use super::{LineDecoder, MsgDictionary, MsgLine, ParseError, ParseOptions, parse_entries};
+
+/// What a parse went through, see [`parse_msg_with_metrics`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseMetrics {
+    /// Bytes consumed, the whole input on success.
+    pub bytes: usize,
+    pub entries: usize,
+    pub comments: usize,
+    pub blank_lines: usize,
+    /// Values the decoder couldn't decode and kept as bytes.
+    pub decode_fallbacks: usize,
+    /// Values changed by [`ParseOptions::transforms`].
+    pub transformed: usize,
+    pub elapsed: Duration,
+}
+
+/// Like [`crate::parse_msg_with_options`], also returning metrics for logging.
+pub fn parse_msg_with_metrics(
+    input: &[u8],
+    options: &ParseOptions,
+) -> Result<(MsgDictionary, ParseMetrics), ParseError> {
+    let started = std::time::Instant::now();
+    let mut dict = MsgDictionary::new();
+    if options.preserve_order {
+        dict.preserve_file_order();
+    }
+    let fallbacks = Cell::new(0);
+    let transformed = Cell::new(0);
+    let mut metrics = ParseMetrics::default();
+    let (dict, _failures) = parse_entries(
+        input,
+        dict,
+        options.secondary_keys,
+        |bytes| {
+            let value = options.decoder.decode(bytes);
+            if let (MsgLine::Bytes(_), false) = (&value, options.decoder == LineDecoder::RawBytes) {
+                fallbacks.set(fallbacks.get() + 1);
+            }
+            if options.transforms.is_empty() {
+                return Ok::<_, std::convert::Infallible>(value);
+            }
+            let original = value.clone();
+            let value = options.transforms.apply(value);
+            if value != original {
+                transformed.set(transformed.get() + 1);
+            }
+            Ok(value)
+        },
+        options.decoder,
+        &mut metrics,
+    )?;
+    metrics.decode_fallbacks = fallbacks.get();
+    metrics.transformed = transformed.get();
+    metrics.elapsed = started.elapsed();
+    Ok((dict, metrics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts() {
+        let input = b"# header\n\n{1}{}{a}\n// note\n{2}{}{\xff}\n{2}{}{b}\n";
+        let (dict, metrics) = parse_msg_with_metrics(input, &ParseOptions::default()).unwrap();
+        assert_eq!(dict.len(), 3);
+        assert_eq!(
+            ParseMetrics {
+                elapsed: Duration::default(),
+                ..metrics
+            },
+            ParseMetrics {
+                bytes: input.len(),
+                entries: 3,
+                comments: 2,
+                blank_lines: 1,
+                decode_fallbacks: 1,
+                transformed: 0,
+                elapsed: Duration::default(),
+            }
+        );
+        let (_dict, metrics) =
+            parse_msg_with_metrics(b"{1}{}{a}\n\n", &ParseOptions::default()).unwrap();
+        assert_eq!(metrics.blank_lines, 1);
+        let (_dict, metrics) = parse_msg_with_metrics(b"", &ParseOptions::default()).unwrap();
+        assert_eq!(metrics.blank_lines, 0);
+    }
+
+    #[test]
+    fn comments_skip_the_converter() {
+        let options = ParseOptions {
+            transforms: crate::TransformPipeline::new()
+                .then(crate::Transform::custom(|value| value.to_uppercase())),
+            ..ParseOptions::default()
+        };
+        let (dict, metrics) = parse_msg_with_metrics(
+            b"{1}{}{a} # note
+{2}{}{b} # \xff\n",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(dict.get_first_string(1), Some("A"));
+        assert_eq!(dict.comment(1, 0), Some("note"));
+        assert_eq!(dict.comment_bytes(2, 0), Some(&b"\xff"[..]));
+        assert_eq!(metrics.transformed, 2);
+        assert_eq!(metrics.decode_fallbacks, 0);
+    }
+}
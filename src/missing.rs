@@ -0,0 +1,35 @@
+use std::borrow::Cow;
+
+use super::MsgDictionary;
+
+pub(crate) const DEFAULT_MISSING_TEMPLATE: &str = "<error str {}>";
+
+impl MsgDictionary {
+    /// Sets the placeholder returned by [`MsgDictionary::get_or_missing`];
+    /// `{}` is replaced with the index. Defaults to `<error str {}>`.
+    pub fn set_missing_template(&mut self, template: impl Into<Cow<'static, str>>) {
+        self.missing_template = template.into();
+    }
+
+    /// First string value of `index`, or a traceable placeholder if there is none.
+    pub fn get_or_missing(&self, index: u32) -> Cow<'_, str> {
+        match self.get_first_string(index) {
+            Some(value) => Cow::Borrowed(value),
+            None => Cow::Owned(self.missing_template.replace("{}", &index.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_msg;
+
+    #[test]
+    fn missing_placeholder() {
+        let mut dict = parse_msg(b"{1}{}{Hello}").unwrap();
+        assert_eq!(dict.get_or_missing(1), "Hello");
+        assert_eq!(dict.get_or_missing(1234), "<error str 1234>");
+        dict.set_missing_template("???{}");
+        assert_eq!(dict.get_or_missing(7), "???7");
+    }
+}
@@ -0,0 +1,144 @@
+use super::{Line, scanner::Scanner};
+
+/// Entry whose index is lower than an earlier one in the same file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderIssue {
+    /// 1-based line the entry starts on.
+    pub line: usize,
+    pub index: u32,
+    /// Highest index seen before the entry.
+    pub previous: u32,
+}
+
+/// Entry with the comment lines right above it, as a byte range.
+struct Block {
+    index: u32,
+    start: usize,
+    entry_start: usize,
+    end: usize,
+}
+
+fn blocks(input: &[u8]) -> Result<Vec<Block>, String> {
+    let mut scanner = Scanner::new(input);
+    let mut blocks = vec![];
+    let mut comments_start = None;
+    loop {
+        let start = scanner.pos;
+        let line = scanner.line().map_err(|err| err.to_string())?;
+        let newline = scanner.newline();
+        match line {
+            Line::Comment(_) => {
+                comments_start.get_or_insert(start);
+            }
+            Line::Break => comments_start = None,
+            Line::Entry(entry) => {
+                let entry_start = start
+                    + input[start..]
+                        .iter()
+                        .position(|&byte| byte == b'{')
+                        .unwrap_or(0);
+                blocks.push(Block {
+                    index: entry.index,
+                    start: comments_start.take().unwrap_or(start),
+                    entry_start,
+                    end: scanner.pos,
+                });
+            }
+        }
+        if !newline {
+            break;
+        }
+    }
+    if scanner.pos != input.len() {
+        return Err(format!("Failed to exhaust input at byte {}", scanner.pos));
+    }
+    Ok(blocks)
+}
+
+/// Entries that break ascending index order, usually left by a botched merge.
+pub fn lint_key_order(input: &[u8]) -> Result<Vec<OrderIssue>, String> {
+    let mut issues = vec![];
+    let mut previous: Option<u32> = None;
+    let mut line = 1;
+    let mut counted_up_to = 0;
+    for block in blocks(input)? {
+        line += input[counted_up_to..block.entry_start]
+            .iter()
+            .filter(|&&byte| byte == b'\n')
+            .count();
+        counted_up_to = block.entry_start;
+        match previous {
+            Some(previous) if block.index < previous => issues.push(OrderIssue {
+                line,
+                index: block.index,
+                previous,
+            }),
+            _ => previous = Some(block.index),
+        }
+    }
+    Ok(issues)
+}
+
+/// Sorts entries by index, moving each with the comment lines directly above it.
+///
+/// Entries with the same index keep their order, and blank lines and detached
+/// comments stay where they were.
+pub fn fix_key_order(input: &[u8]) -> Result<Vec<u8>, String> {
+    let blocks = blocks(input)?;
+    let mut sorted: Vec<&Block> = blocks.iter().collect();
+    sorted.sort_by_key(|block| block.index);
+    let text = |block: &Block| {
+        let text = &input[block.start..block.end];
+        text.strip_suffix(b"\r\n")
+            .or_else(|| text.strip_suffix(b"\n"))
+            .unwrap_or(text)
+    };
+    let newline: &[u8] = if input.windows(2).any(|pair| pair == b"\r\n") {
+        b"\r\n"
+    } else {
+        b"\n"
+    };
+    let mut out = Vec::with_capacity(input.len() + newline.len());
+    let mut copied_up_to = 0;
+    for (slot, block) in blocks.iter().zip(sorted) {
+        out.extend_from_slice(&input[copied_up_to..slot.start]);
+        out.extend_from_slice(text(block));
+        // Keep the slot's own line ending, or add one if the moved entry had the file's last line.
+        let slot_ending = &input[slot.start + text(slot).len()..slot.end];
+        out.extend_from_slice(if slot_ending.is_empty() { b"" } else { newline });
+        copied_up_to = slot.end;
+    }
+    out.extend_from_slice(&input[copied_up_to..]);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lint_and_fix() {
+        let input = b"# header\n\n{10}{}{a}\n# about 5\n{5}{}{b}\n{20}{}{c}\n\n{15}{}{d}";
+        assert_eq!(
+            lint_key_order(input).unwrap(),
+            vec![
+                OrderIssue {
+                    line: 5,
+                    index: 5,
+                    previous: 10
+                },
+                OrderIssue {
+                    line: 8,
+                    index: 15,
+                    previous: 20
+                },
+            ]
+        );
+        let fixed = fix_key_order(input).unwrap();
+        assert_eq!(
+            String::from_utf8(fixed.clone()).unwrap(),
+            "# header\n\n# about 5\n{5}{}{b}\n{10}{}{a}\n{15}{}{d}\n\n{20}{}{c}"
+        );
+        assert!(lint_key_order(&fixed).unwrap().is_empty());
+    }
+}
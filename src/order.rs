@@ -0,0 +1,52 @@
+use std::collections::hash_set::HashSet;
+
+use super::{MsgDictionary, MsgLine};
+
+impl MsgDictionary {
+    /// Starts recording the order in which entries get inserted.
+    pub fn preserve_file_order(&mut self) {
+        self.file_order.get_or_insert_with(Vec::new);
+    }
+
+    /// Entries in the order they were inserted (for parsed dictionaries, the order
+    /// of the file) if [`MsgDictionary::preserve_file_order`] was enabled.
+    ///
+    /// Entries with no recorded position, such as those added through patches or
+    /// merges, follow in key order.
+    pub fn iter_in_file_order(&self) -> impl Iterator<Item = ((u32, u32), &MsgLine)> {
+        let mut seen = HashSet::new();
+        let mut entries = vec![];
+        for key in self.file_order.iter().flatten() {
+            if let Some(value) = self.index_to_line.get(key) {
+                if seen.insert(*key) {
+                    entries.push((*key, value));
+                }
+            }
+        }
+        for (key, value) in self.index_to_line.iter() {
+            if !seen.contains(key) {
+                entries.push((*key, value));
+            }
+        }
+        entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseOptions, parse_msg_with_options};
+
+    #[test]
+    fn file_order() {
+        let options = ParseOptions {
+            preserve_order: true,
+            ..ParseOptions::default()
+        };
+        let mut dict =
+            parse_msg_with_options(b"{20}{}{a}\n{10}{}{b}\n{20}{}{c}\n{5}{}{d}", &options).unwrap();
+        dict.remove_range(10..11);
+        dict.insert_bytes(1, b"");
+        let keys: Vec<_> = dict.iter_in_file_order().map(|(key, _value)| key).collect();
+        assert_eq!(keys, vec![(20, 0), (20, 1), (5, 0), (1, 0)]);
+    }
+}
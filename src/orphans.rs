@@ -0,0 +1,85 @@
+use std::{collections::BTreeSet, ops::Range};
+
+use super::{MsgDictionary, ScriptXref};
+
+/// Indices something refers to: scripts, dialogs, proto lists.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReferenceSet {
+    indices: BTreeSet<u32>,
+    ranges: Vec<Range<u32>>,
+}
+
+impl ReferenceSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, index: u32) {
+        self.indices.insert(index);
+    }
+
+    /// Marks a whole block as used, e.g. `pid * 100..pid * 100 + 100` of a listed proto.
+    pub fn insert_range(&mut self, range: Range<u32>) {
+        self.ranges.push(range);
+    }
+
+    /// Adds every resolved reference found by `xref`.
+    pub fn add_xref(&mut self, xref: &ScriptXref) {
+        self.extend(
+            xref.references()
+                .iter()
+                .filter_map(|reference| reference.index),
+        );
+    }
+
+    pub fn contains(&self, index: u32) -> bool {
+        self.indices.contains(&index) || self.ranges.iter().any(|range| range.contains(&index))
+    }
+}
+
+impl Extend<u32> for ReferenceSet {
+    fn extend<T: IntoIterator<Item = u32>>(&mut self, iter: T) {
+        self.indices.extend(iter);
+    }
+}
+
+impl std::iter::FromIterator<u32> for ReferenceSet {
+    fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl MsgDictionary {
+    /// Indices nothing in `references` refers to, candidates for pruning.
+    pub fn unreferenced(&self, references: &ReferenceSet) -> Vec<u32> {
+        self.indices()
+            .filter(|&index| !references.contains(index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn find_orphans() {
+        let dict =
+            parse_msg(b"{100}{}{A}\n{101}{}{B}\n{200}{}{C}\n{1200}{}{Item}\n{1201}{}{Desc}\n")
+                .unwrap();
+        let mut xref = ScriptXref::default();
+        xref.add_defines("#define STR_A (100)\n");
+        xref.scan(
+            "a.fos",
+            "GetMsgStr(TEXTMSG_TEXT, STR_A); GetMsgStr(TEXTMSG_TEXT, 200);",
+        );
+
+        let mut references = ReferenceSet::new();
+        references.add_xref(&xref);
+        references.insert_range(1200..1300);
+        assert_eq!(dict.unreferenced(&references), vec![101]);
+    }
+}
@@ -0,0 +1,279 @@
+use std::{collections::btree_map::BTreeMap, fmt, io, path::Path, str::FromStr};
+
+use super::{MsgDictionary, ParseError, decompress, parse_msg};
+
+/// MSG files of a single language, keyed by file name.
+///
+/// Packs are equal when their files are, however they were loaded.
+#[derive(Debug, Default)]
+pub struct LanguagePack {
+    files: BTreeMap<String, MsgDictionary>,
+    /// Hashes of files as [`load_dir`](Self::load_dir) read them, dropped once a file
+    /// may have changed.
+    source_hashes: BTreeMap<String, u64>,
+}
+
+impl LanguagePack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every `*.msg` file (case-insensitive) directly inside `dir`.
+    ///
+    /// Errors in a file are wrapped in [`ParseError::File`].
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Self, ParseError> {
+        let mut pack = Self::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_msg = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("msg"));
+            if !is_msg {
+                continue;
+            }
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) => name.to_owned(),
+                None => continue,
+            };
+            let load = || {
+                let raw = std::fs::read(&path)?;
+                let hash = fnv1a(&raw);
+                let bytes = decompress(raw).map_err(|err| {
+                    ParseError::Io(io::Error::new(io::ErrorKind::InvalidData, err))
+                })?;
+                Ok((parse_msg(&bytes)?, hash))
+            };
+            let (dict, hash) = load().map_err(|err: ParseError| err.in_file(&path))?;
+            pack.insert(name.clone(), dict);
+            pack.source_hashes.insert(name, hash);
+        }
+        Ok(pack)
+    }
+
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        dict: MsgDictionary,
+    ) -> Option<MsgDictionary> {
+        let name = name.into();
+        self.source_hashes.remove(&name);
+        self.files.insert(name, dict)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MsgDictionary> {
+        self.files.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut MsgDictionary> {
+        self.source_hashes.remove(name);
+        self.files.get_mut(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &MsgDictionary)> {
+        self.files.iter().map(|(name, dict)| (name.as_str(), dict))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut MsgDictionary)> {
+        self.source_hashes.clear();
+        self.files
+            .iter_mut()
+            .map(|(name, dict)| (name.as_str(), dict))
+    }
+
+    /// Hashes every file as read from disk, or its serialized form if it was
+    /// inserted or borrowed mutably since.
+    pub fn manifest(&self) -> Manifest {
+        let files = self
+            .files
+            .iter()
+            .map(|(name, dict)| (name.clone(), self.entry(name, dict)))
+            .collect();
+        Manifest { files }
+    }
+
+    /// Lists every file that doesn't match `manifest`.
+    pub fn verify(&self, manifest: &Manifest) -> Vec<ManifestMismatch> {
+        let mut mismatches = vec![];
+        for (name, expected) in &manifest.files {
+            match self.files.get(name) {
+                None => mismatches.push(ManifestMismatch::Missing(name.clone())),
+                Some(dict) => {
+                    let actual = self.entry(name, dict);
+                    if actual != *expected {
+                        mismatches.push(ManifestMismatch::Changed {
+                            name: name.clone(),
+                            expected: *expected,
+                            actual,
+                        });
+                    }
+                }
+            }
+        }
+        for name in self.files.keys() {
+            if !manifest.files.contains_key(name) {
+                mismatches.push(ManifestMismatch::Unexpected(name.clone()));
+            }
+        }
+        mismatches
+    }
+
+    fn entry(&self, name: &str, dict: &MsgDictionary) -> ManifestEntry {
+        ManifestEntry {
+            hash: match self.source_hashes.get(name) {
+                Some(&hash) => hash,
+                None => fnv1a(&dict.to_msg_bytes()),
+            },
+            entries: dict.len(),
+        }
+    }
+}
+
+impl PartialEq for LanguagePack {
+    fn eq(&self, other: &Self) -> bool {
+        self.files == other.files
+    }
+}
+
+/// Per-file content hashes of a [`LanguagePack`].
+///
+/// Text form is one `hash\tentries\tname` line per file, see the `Display` and `FromStr` impls.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Manifest {
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManifestEntry {
+    /// FNV-1a hash of the file's bytes.
+    pub hash: u64,
+    pub entries: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ManifestMismatch {
+    Missing(String),
+    Unexpected(String),
+    Changed {
+        name: String,
+        expected: ManifestEntry,
+        actual: ManifestEntry,
+    },
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, entry) in &self.files {
+            writeln!(f, "{:016x}\t{}\t{}", entry.hash, entry.entries, name)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut files = BTreeMap::new();
+        for (number, line) in s.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(3, '\t');
+            let mut next = || {
+                parts
+                    .next()
+                    .ok_or_else(|| format!("Malformed manifest line {}", number + 1))
+            };
+            let hash = u64::from_str_radix(next()?, 16)
+                .map_err(|err| format!("Bad hash on manifest line {}: {}", number + 1, err))?;
+            let entries = next()?.parse().map_err(|err| {
+                format!("Bad entry count on manifest line {}: {}", number + 1, err)
+            })?;
+            files.insert(next()?.to_owned(), ManifestEntry { hash, entries });
+        }
+        Ok(Manifest { files })
+    }
+}
+
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    fn sample_pack() -> LanguagePack {
+        let mut pack = LanguagePack::new();
+        pack.insert("FOGM.MSG", parse_msg(b"{10}{}{Global map}").unwrap());
+        pack.insert("FOTEXT.MSG", parse_msg(b"{1}{}{a}\n{1}{}{b}").unwrap());
+        pack
+    }
+
+    #[test]
+    fn manifest_text_roundtrip() {
+        let manifest = sample_pack().manifest();
+        assert_eq!(manifest.files["FOTEXT.MSG"].entries, 2);
+        let text = manifest.to_string();
+        assert_eq!(text.parse::<Manifest>().unwrap(), manifest);
+    }
+
+    #[test]
+    fn verify_detects_changes() {
+        let manifest = sample_pack().manifest();
+        let mut pack = sample_pack();
+        assert_eq!(pack.verify(&manifest), vec![]);
+        pack.insert("FOTEXT.MSG", parse_msg(b"{1}{}{a}").unwrap());
+        pack.insert("EXTRA.MSG", parse_msg(b"").unwrap());
+        let mismatches = pack.verify(&manifest);
+        assert!(matches!(
+            &mismatches[0],
+            ManifestMismatch::Changed { name, .. } if name == "FOTEXT.MSG"
+        ));
+        assert_eq!(
+            mismatches[1],
+            ManifestMismatch::Unexpected("EXTRA.MSG".into())
+        );
+    }
+
+    #[test]
+    fn hashes_files_as_read() {
+        let dir = std::env::temp_dir().join(format!("fo_msg_pack_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("FOTEXT.MSG");
+        std::fs::write(&path, b"{1}{}{a}\n").unwrap();
+        let manifest = LanguagePack::load_dir(&dir).unwrap().manifest();
+        assert_eq!(manifest.files["FOTEXT.MSG"].hash, fnv1a(b"{1}{}{a}\n"));
+
+        // Same entries, different bytes.
+        std::fs::write(&path, b"# edited\n{1}{}{a}\n").unwrap();
+        let mut pack = LanguagePack::load_dir(&dir).unwrap();
+        std::fs::write(dir.join("BAD.MSG"), b"{1}{}{a}\njunk").unwrap();
+        let err = LanguagePack::load_dir(&dir).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        match err {
+            ParseError::File { path, error } => {
+                assert!(path.ends_with("BAD.MSG"));
+                assert!(matches!(*error, ParseError::NonExhaustive { line: 2, .. }));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            &pack.verify(&manifest)[..],
+            [ManifestMismatch::Changed { name, .. }] if name == "FOTEXT.MSG"
+        ));
+        assert_eq!(pack, {
+            let mut inserted = LanguagePack::new();
+            inserted.insert("FOTEXT.MSG", parse_msg(b"{1}{}{a}\n").unwrap());
+            inserted
+        });
+        pack.get_mut("FOTEXT.MSG");
+        assert_eq!(
+            pack.manifest().files["FOTEXT.MSG"].hash,
+            fnv1a(b"{1}{}{a}\n")
+        );
+    }
+}
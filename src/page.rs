@@ -0,0 +1,71 @@
+use super::{MsgDictionary, MsgLine};
+
+/// Entries in `(index, sub_index)` order with the cursor to continue from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<'a> {
+    pub entries: Vec<((u32, u32), &'a MsgLine)>,
+    /// Pass to [`MsgDictionary::page_after`] for the next page, `None` on the last one.
+    pub next: Option<(u32, u32)>,
+}
+
+impl MsgDictionary {
+    /// At most `limit` entries starting from entry number `offset`, in key order.
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<((u32, u32), &MsgLine)> {
+        self.index_to_line
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(&key, value)| (key, value))
+            .collect()
+    }
+
+    /// At most `limit` entries with keys after `cursor` (from the start if `None`).
+    ///
+    /// Unlike offsets, cursors stay stable while entries are added or removed
+    /// between requests.
+    pub fn page_after(&self, cursor: Option<(u32, u32)>, limit: usize) -> Page<'_> {
+        let entries = match cursor {
+            Some(cursor) => self.index_to_line.after(cursor),
+            None => self.index_to_line.iter(),
+        };
+        let mut entries: Vec<_> = entries
+            .take(limit.saturating_add(1))
+            .map(|(&key, value)| (key, value))
+            .collect();
+        let next = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|&(key, _value)| key)
+        } else {
+            None
+        };
+        Page { entries, next }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MsgLine, parse_msg};
+
+    fn keys(entries: &[((u32, u32), &MsgLine)]) -> Vec<(u32, u32)> {
+        entries.iter().map(|&(key, _value)| key).collect()
+    }
+
+    #[test]
+    fn pages() {
+        let mut dict = parse_msg(b"{1}{}{a}\n{1}{}{b}\n{2}{}{c}\n{5}{}{d}\n{9}{}{e}\n").unwrap();
+        assert_eq!(keys(&dict.page(1, 2)), vec![(1, 1), (2, 0)]);
+        assert!(dict.page(10, 2).is_empty());
+
+        let first = dict.page_after(None, 2);
+        assert_eq!(keys(&first.entries), vec![(1, 0), (1, 1)]);
+        assert_eq!(first.next, Some((1, 1)));
+        let cursor = first.next;
+
+        dict.remove_range(1..2);
+        let second = dict.page_after(cursor, 2);
+        assert_eq!(keys(&second.entries), vec![(2, 0), (5, 0)]);
+        let last = dict.page_after(second.next, 2);
+        assert_eq!(keys(&last.entries), vec![(9, 0)]);
+        assert_eq!(last.next, None);
+    }
+}
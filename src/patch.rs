@@ -0,0 +1,610 @@
+use std::{fmt, str::FromStr};
+
+use super::{
+    MsgDictionary, MsgLine, RemovedEntry,
+    diff::{DiffEntry, MsgDiff},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "op", rename_all = "snake_case")
+)]
+pub enum PatchOp {
+    Add {
+        index: u32,
+        sub_index: Option<u32>,
+        #[cfg_attr(feature = "serde", serde(with = "value_serde"))]
+        value: MsgLine,
+    },
+    Remove {
+        index: u32,
+        sub_index: Option<u32>,
+    },
+    Replace {
+        index: u32,
+        sub_index: u32,
+        #[cfg_attr(feature = "serde", serde(with = "value_serde"))]
+        value: MsgLine,
+    },
+    /// Moves every value of `from` to the free index `to`.
+    Renumber {
+        from: u32,
+        to: u32,
+    },
+}
+
+/// Line-based patch for dictionaries, parsed with `FromStr` and emitted with `Display`.
+///
+/// ```text
+/// # comments and blank lines are ignored
+/// +{10}{}{New}        append a value to index 10
+/// +{10}[2]{}{New}     insert at sub-index 2, which must be free
+/// -{15}[1]            remove sub-index 1 of index 15
+/// -{15}               remove every sub-index of index 15
+/// ~{20}{}{Changed}    replace sub-index 0 of index 20
+/// ~{20}[1]{}{Changed} replace sub-index 1
+/// >{30}{40}           move index 30 to 40
+/// ```
+///
+/// Values may span lines, like in MSG files. Byte values, e.g. undecoded CP1251
+/// text, are written as `b{...}` with `\xNN` escapes for bytes outside printable
+/// ASCII and `\\` for a backslash:
+///
+/// ```text
+/// ~{20}{}b{\xcf\xf0\xe8\xe2\xe5\xf2}
+/// ```
+///
+/// With the `serde` feature patches also (de)serialize as a list of tagged
+/// operations, e.g. in TOML (`Patch::from_toml`), byte values as `value = { bytes = [...] }`:
+///
+/// ```toml
+/// [[ops]]
+/// op = "replace"
+/// index = 20
+/// sub_index = 0
+/// value = "Changed"
+///
+/// [[ops]]
+/// op = "renumber"
+/// from = 30
+/// to = 40
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Patch {
+    pub ops: Vec<PatchOp>,
+}
+
+/// Serializes `diff` as a patch that turns the old dictionary into the new one.
+pub fn emit_patch(diff: &MsgDiff<'_>) -> String {
+    Patch::from(diff).to_string()
+}
+
+impl From<&MsgDiff<'_>> for Patch {
+    fn from(diff: &MsgDiff<'_>) -> Self {
+        let ops = diff
+            .entries
+            .iter()
+            .map(|entry| match *entry {
+                DiffEntry::Added {
+                    key: (index, sub_index),
+                    new,
+                } => PatchOp::Add {
+                    index,
+                    sub_index: Some(sub_index),
+                    value: new.clone(),
+                },
+                DiffEntry::Removed {
+                    key: (index, sub_index),
+                    ..
+                } => PatchOp::Remove {
+                    index,
+                    sub_index: Some(sub_index),
+                },
+                DiffEntry::Changed {
+                    key: (index, sub_index),
+                    new,
+                    ..
+                } => PatchOp::Replace {
+                    index,
+                    sub_index,
+                    value: new.clone(),
+                },
+            })
+            .collect();
+        Patch { ops }
+    }
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sub = |sub_index: Option<u32>| match sub_index {
+            Some(sub_index) => format!("[{}]", sub_index),
+            None => String::new(),
+        };
+        for op in &self.ops {
+            match op {
+                PatchOp::Add {
+                    index,
+                    sub_index,
+                    value,
+                } => writeln!(f, "+{{{}}}{}{{}}{}", index, sub(*sub_index), Value(value))?,
+                PatchOp::Remove { index, sub_index } => {
+                    writeln!(f, "-{{{}}}{}", index, sub(*sub_index))?
+                }
+                PatchOp::Replace {
+                    index,
+                    sub_index,
+                    value,
+                } => writeln!(f, "~{{{}}}[{}]{{}}{}", index, sub_index, Value(value))?,
+                PatchOp::Renumber { from, to } => writeln!(f, ">{{{}}}{{{}}}", from, to)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Value of an operation in braces, escaped if it is bytes.
+struct Value<'a>(&'a MsgLine);
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            MsgLine::String(string) => write!(f, "{{{}}}", string),
+            MsgLine::Bytes(bytes) => {
+                f.write_str("b{")?;
+                for &byte in bytes.iter() {
+                    match byte {
+                        b'\\' => f.write_str("\\\\")?,
+                        b'\n' | b'\r' | b'\t' | b' '..=b'|' | b'~' => {
+                            write!(f, "{}", byte as char)?
+                        }
+                        _ => write!(f, "\\x{:02x}", byte)?,
+                    }
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn unescape(escaped: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut rest = escaped.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match rest {
+            [b'\\', tail @ ..] => {
+                bytes.push(b'\\');
+                rest = tail;
+            }
+            [b'x', high, low, tail @ ..] => {
+                let high = (*high as char).to_digit(16)?;
+                let low = (*low as char).to_digit(16)?;
+                bytes.push((high << 4 | low) as u8);
+                rest = tail;
+            }
+            _ => return None,
+        }
+    }
+    Some(bytes)
+}
+
+struct Cursor<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn error(&self, what: &str) -> String {
+        format!("Patch line {}: {}", self.line, what)
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.rest.starts_with(ch) {
+            self.rest = &self.rest[ch.len_utf8()..];
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, ch: char) -> Result<(), String> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", ch)))
+        }
+    }
+
+    fn until(&mut self, ch: char) -> Result<&'a str, String> {
+        let end = self
+            .rest
+            .find(ch)
+            .ok_or_else(|| self.error(&format!("missing '{}'", ch)))?;
+        let taken = &self.rest[..end];
+        self.rest = &self.rest[end + ch.len_utf8()..];
+        self.line += taken.matches('\n').count();
+        Ok(taken)
+    }
+
+    fn number(&mut self, open: char, close: char) -> Result<u32, String> {
+        self.expect(open)?;
+        let digits = self.until(close)?;
+        digits
+            .parse()
+            .map_err(|_| self.error(&format!("bad number '{}'", digits)))
+    }
+
+    fn sub_index(&mut self) -> Result<Option<u32>, String> {
+        if self.rest.starts_with('[') {
+            self.number('[', ']').map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn value(&mut self) -> Result<MsgLine, String> {
+        self.expect('{')?;
+        self.until('}')?;
+        let bytes = self.eat('b');
+        self.expect('{')?;
+        let value = self.until('}')?;
+        if !bytes {
+            return Ok(MsgLine::String(value.into()));
+        }
+        unescape(value)
+            .map(|bytes| MsgLine::Bytes(bytes.into()))
+            .ok_or_else(|| self.error("bad escape in byte value"))
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        let line_end = self.rest.find('\n').unwrap_or(self.rest.len());
+        if !self.rest[..line_end].trim().is_empty() {
+            return Err(self.error("trailing characters"));
+        }
+        self.rest = self.rest.get(line_end + 1..).unwrap_or("");
+        self.line += 1;
+        Ok(())
+    }
+}
+
+impl FromStr for Patch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cursor = Cursor { rest: s, line: 1 };
+        let mut ops = vec![];
+        while !cursor.rest.is_empty() {
+            let trimmed = cursor.rest.trim_start_matches([' ', '\t', '\r']);
+            if trimmed.starts_with('#') || trimmed.starts_with('\n') || trimmed.is_empty() {
+                cursor.rest = trimmed;
+                let line_end = cursor
+                    .rest
+                    .find('\n')
+                    .map_or(cursor.rest.len(), |end| end + 1);
+                cursor.rest = &cursor.rest[line_end..];
+                cursor.line += 1;
+                continue;
+            }
+            cursor.rest = trimmed;
+            let op = if cursor.eat('+') {
+                let index = cursor.number('{', '}')?;
+                let sub_index = cursor.sub_index()?;
+                let value = cursor.value()?;
+                PatchOp::Add {
+                    index,
+                    sub_index,
+                    value,
+                }
+            } else if cursor.eat('-') {
+                let index = cursor.number('{', '}')?;
+                let sub_index = cursor.sub_index()?;
+                PatchOp::Remove { index, sub_index }
+            } else if cursor.eat('~') {
+                let index = cursor.number('{', '}')?;
+                let sub_index = cursor.sub_index()?.unwrap_or(0);
+                let value = cursor.value()?;
+                PatchOp::Replace {
+                    index,
+                    sub_index,
+                    value,
+                }
+            } else if cursor.eat('>') {
+                let from = cursor.number('{', '}')?;
+                let to = cursor.number('{', '}')?;
+                PatchOp::Renumber { from, to }
+            } else {
+                return Err(cursor.error("expected '+', '-', '~' or '>'"));
+            };
+            cursor.end_of_line()?;
+            ops.push(op);
+        }
+        Ok(Patch { ops })
+    }
+}
+
+/// Values as TOML/JSON strings, or `{ bytes = [...] }` for byte values.
+#[cfg(feature = "serde")]
+mod value_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::MsgLine;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Text(String),
+        Bytes { bytes: Vec<u8> },
+    }
+
+    pub fn serialize<S: Serializer>(value: &MsgLine, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            MsgLine::String(string) => Value::Text(string.to_string()),
+            MsgLine::Bytes(bytes) => Value::Bytes {
+                bytes: bytes.to_vec(),
+            },
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<MsgLine, D::Error> {
+        Ok(match Value::deserialize(deserializer)? {
+            Value::Text(text) => MsgLine::String(text.into()),
+            Value::Bytes { bytes } => MsgLine::Bytes(bytes.into()),
+        })
+    }
+}
+
+#[cfg(feature = "toml-schema")]
+impl Patch {
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|err| err.to_string())
+    }
+}
+
+impl MsgDictionary {
+    /// Applies a textual patch, see [`Patch`]. Either every operation applies or none.
+    pub fn apply_patch(&mut self, patch: &str) -> Result<(), String> {
+        let patch: Patch = patch.parse()?;
+        self.apply_ops(&patch.ops)
+    }
+
+    /// Applies `ops` in order. Either every operation applies or none.
+    pub fn apply_ops(&mut self, ops: &[PatchOp]) -> Result<(), String> {
+        let mut undo = vec![];
+        let res = ops
+            .iter()
+            .try_for_each(|op| self.apply_patch_op(op, &mut undo));
+        if res.is_err() {
+            for step in undo.into_iter().rev() {
+                match step {
+                    Undo::Added(key) => {
+                        self.index_to_line.remove(&key);
+                    }
+                    Undo::Removed(key, entry) => self.restore_entry(key, entry),
+                    Undo::Replaced(key, value) => {
+                        self.index_to_line.insert(key, value);
+                    }
+                    Undo::Moved { from, to } => {
+                        if let Some(entry) = self.remove_entry(to) {
+                            self.restore_entry(from, entry);
+                        }
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    fn apply_patch_op(&mut self, op: &PatchOp, undo: &mut Vec<Undo>) -> Result<(), String> {
+        match *op {
+            PatchOp::Add {
+                index,
+                sub_index,
+                ref value,
+            } => {
+                let sub_index = match sub_index {
+                    Some(sub_index) => sub_index,
+                    None => match self.stored_max_sub_index(index) {
+                        None => 0,
+                        Some(last) => last
+                            .checked_add(1)
+                            .ok_or_else(|| format!("No free sub-index left for {{{}}}", index))?,
+                    },
+                };
+                let key = (index, sub_index);
+                if self.index_to_line.get(&key).is_some() {
+                    return Err(format!("{{{}}}[{}] already exists", index, sub_index));
+                }
+                self.index_to_line.insert(key, value.clone());
+                undo.push(Undo::Added(key));
+            }
+            PatchOp::Remove { index, sub_index } => {
+                let keys: Vec<_> = match sub_index {
+                    Some(sub_index) => vec![(index, sub_index)],
+                    None => self
+                        .index_to_line
+                        .range(index)
+                        .map(|(&key, _value)| key)
+                        .collect(),
+                };
+                if keys.is_empty() {
+                    return Err(format!("{{{}}} doesn't exist", index));
+                }
+                for key in keys {
+                    let old = self
+                        .remove_entry(key)
+                        .ok_or_else(|| format!("{{{}}}[{}] doesn't exist", key.0, key.1))?;
+                    undo.push(Undo::Removed(key, old));
+                }
+            }
+            PatchOp::Replace {
+                index,
+                sub_index,
+                ref value,
+            } => {
+                let key = (index, sub_index);
+                if self.index_to_line.get(&key).is_none() {
+                    return Err(format!("{{{}}}[{}] doesn't exist", index, sub_index));
+                }
+                if let Some(old) = self.index_to_line.insert(key, value.clone()) {
+                    undo.push(Undo::Replaced(key, old));
+                }
+            }
+            PatchOp::Renumber { from, to } => {
+                if from == to {
+                    return Ok(());
+                }
+                if self.index_to_line.range(to).next().is_some() {
+                    return Err(format!("{{{}}} already exists", to));
+                }
+                let keys: Vec<_> = self
+                    .index_to_line
+                    .range(from)
+                    .map(|(&key, _value)| key)
+                    .collect();
+                if keys.is_empty() {
+                    return Err(format!("{{{}}} doesn't exist", from));
+                }
+                for (index, sub_index) in keys {
+                    if let Some(entry) = self.remove_entry((index, sub_index)) {
+                        self.restore_entry((to, sub_index), entry);
+                        undo.push(Undo::Moved {
+                            from: (index, sub_index),
+                            to: (to, sub_index),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How to revert one step of [`MsgDictionary::apply_ops`].
+enum Undo {
+    Added((u32, u32)),
+    Removed((u32, u32), RemovedEntry),
+    Replaced((u32, u32), MsgLine),
+    Moved { from: (u32, u32), to: (u32, u32) },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn diff_patch_roundtrip() {
+        let old = parse_msg(b"{10}{}{Same}\n{15}{}{Old}\n{15}{}{Gone}").unwrap();
+        let new = parse_msg(b"{10}{}{Same}\n{15}{}{New\nlines}\n{30}{}{Added}").unwrap();
+        let patch = emit_patch(&old.diff(&new));
+        assert_eq!(
+            patch,
+            "~{15}[0]{}{New\nlines}\n-{15}[1]\n+{30}[0]{}{Added}\n"
+        );
+        let mut patched = parse_msg(b"{10}{}{Same}\n{15}{}{Old}\n{15}{}{Gone}").unwrap();
+        patched.apply_patch(&patch).unwrap();
+        assert_eq!(patched, new);
+    }
+
+    #[test]
+    fn short_forms_and_comments() {
+        let mut dict = parse_msg(b"{10}{}{a}\n{20}{}{b}\n{20}{}{c}").unwrap();
+        dict.apply_patch("# hotfix\n+{10}{}{a2}\n\n-{20}\n~{10}{}{A}\n")
+            .unwrap();
+        assert_eq!(
+            dict.get_all_strings(10).collect::<Vec<_>>(),
+            vec![(0, "A"), (1, "a2")]
+        );
+        assert_eq!(dict.count(20), 0);
+    }
+
+    #[test]
+    fn failed_patch_changes_nothing() {
+        let mut dict = parse_msg(b"{10}{}{a}").unwrap();
+        let err = dict.apply_patch("~{10}{}{b}\n-{99}[0]\n").unwrap_err();
+        assert_eq!(err, "{99}[0] doesn't exist");
+        assert_eq!(dict.get_first_string(10), Some("a"));
+        assert!("+{x}{}{a}".parse::<Patch>().is_err());
+    }
+
+    #[test]
+    fn renumber_ops() {
+        let mut dict = parse_msg(b"{10}{}{a}\n{10}{}{b}\n{20}{}{c}").unwrap();
+        let patch: Patch = ">{10}{30}\n".parse().unwrap();
+        assert_eq!(patch.ops, vec![PatchOp::Renumber { from: 10, to: 30 }]);
+        assert_eq!(patch.to_string(), ">{10}{30}\n");
+        dict.apply_ops(&patch.ops).unwrap();
+        assert_eq!(
+            dict.get_all_strings(30).collect::<Vec<_>>(),
+            vec![(0, "a"), (1, "b")]
+        );
+        assert_eq!(dict.count(10), 0);
+
+        let err = dict
+            .apply_ops(&[
+                PatchOp::Renumber { from: 30, to: 40 },
+                PatchOp::Renumber { from: 20, to: 40 },
+            ])
+            .unwrap_err();
+        assert_eq!(err, "{40} already exists");
+        assert_eq!(dict.count(30), 2);
+        assert_eq!(dict.count(40), 0);
+    }
+
+    #[test]
+    fn renumber_moves_metadata() {
+        let mut dict = parse_msg(b"{10}{}{a}\n{20}{}{b}").unwrap();
+        dict.set_comment(10, 0, "note").unwrap();
+        dict.add_tag(10, 0, "ui");
+        dict.deprecate(10, "old");
+        dict.apply_ops(&[PatchOp::Renumber { from: 10, to: 30 }])
+            .unwrap();
+        assert_eq!(dict.comment(30, 0), Some("note"));
+        assert_eq!(dict.tags(30, 0).collect::<Vec<_>>(), vec!["ui"]);
+        assert_eq!(dict.deprecation(30), Some("old"));
+        assert_eq!(dict.comment(10, 0), None);
+        assert_eq!(dict.deprecation(10), None);
+
+        let err = dict
+            .apply_ops(&[
+                PatchOp::Remove {
+                    index: 30,
+                    sub_index: None,
+                },
+                PatchOp::Renumber { from: 20, to: 20 },
+                PatchOp::Remove {
+                    index: 99,
+                    sub_index: None,
+                },
+            ])
+            .unwrap_err();
+        assert_eq!(err, "{99} doesn't exist");
+        assert_eq!(dict.comment(30, 0), Some("note"));
+        assert_eq!(dict.deprecation(30), Some("old"));
+    }
+
+    #[test]
+    fn cp1251_values() {
+        // Привет -> Пока, with a backslash kept as is.
+        let old = parse_msg(b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2}\n{2}{}{a}").unwrap();
+        let new = parse_msg(b"{1}{}{\xcf\xee\xea\xe0 \\n}\n{2}{}{a}\n{3}{}{\xc4\xe0}").unwrap();
+        let patch = emit_patch(&old.diff(&new));
+        assert_eq!(
+            patch,
+            "~{1}[0]{}b{\\xcf\\xee\\xea\\xe0 \\\\n}\n+{3}[0]{}b{\\xc4\\xe0}\n"
+        );
+        let mut patched = parse_msg(b"{1}{}{\xcf\xf0\xe8\xe2\xe5\xf2}\n{2}{}{a}").unwrap();
+        patched.apply_patch(&patch).unwrap();
+        assert_eq!(patched, new);
+        assert!("~{1}{}b{\\xzz}".parse::<Patch>().is_err());
+    }
+}
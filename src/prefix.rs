@@ -0,0 +1,53 @@
+use super::{
+    LineDecoder, MsgDictionary, ParseError, ParseMetrics, SecondaryKeyPolicy, collect_entries,
+    scanner,
+};
+
+/// Result of [`parse_msg_prefix`].
+#[derive(Debug, PartialEq)]
+pub struct PrefixParse<'a> {
+    /// Entries of the well-formed prefix.
+    pub dict: MsgDictionary,
+    /// Byte offset where the unparsed remainder starts.
+    pub offset: usize,
+    /// Input the parser couldn't consume, empty if it parsed everything.
+    pub rest: &'a [u8],
+}
+
+/// Parses as many lines of `input` as form valid MSG text and returns the rest
+/// instead of failing, so callers can recover on their own or detect garbage
+/// appended to a file.
+///
+/// Still fails on an entry that starts but is malformed.
+pub fn parse_msg_prefix(input: &[u8], decoder: LineDecoder) -> Result<PrefixParse<'_>, ParseError> {
+    let (rest, msg) = scanner::tokenize_bytes_prefix(input)?;
+    let offset = input.len() - rest.len();
+    let (dict, _failures) = collect_entries(
+        input,
+        msg,
+        MsgDictionary::new(),
+        SecondaryKeyPolicy::Error,
+        |bytes| Ok::<_, std::convert::Infallible>(decoder.decode(bytes)),
+        decoder,
+        &mut ParseMetrics::default(),
+    )?;
+    Ok(PrefixParse { dict, offset, rest })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn trailing_garbage() {
+        let input = b"{1}{}{a}\n{2}{}{b}\nPK\x03\x04junk";
+        let parsed = parse_msg_prefix(input, LineDecoder::Utf8Strict).unwrap();
+        assert_eq!(parsed.dict, parse_msg(b"{1}{}{a}\n{2}{}{b}").unwrap());
+        assert_eq!(parsed.offset, 18);
+        assert_eq!(parsed.rest, &b"PK\x03\x04junk"[..]);
+
+        let clean = parse_msg_prefix(b"{1}{}{a}\n", LineDecoder::Utf8Strict).unwrap();
+        assert!(clean.rest.is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+use super::MsgDictionary;
+
+/// Read access to message text, for server code that shouldn't care where text comes from.
+pub trait MsgProvider {
+    /// First value of `index`.
+    fn get_str(&self, index: u32) -> Option<&str> {
+        self.get_str_nth(index, 0)
+    }
+
+    /// Value number `skip` of `index`, counting from the first one.
+    fn get_str_nth(&self, index: u32, skip: u32) -> Option<&str>;
+
+    /// Number of values stored under `index`.
+    fn count(&self, index: u32) -> u32;
+
+    fn has(&self, index: u32) -> bool {
+        self.count(index) > 0
+    }
+
+    /// One of the values of `index`, picked by `roll` (e.g. a random number).
+    fn get_random(&self, index: u32, roll: u32) -> Option<&str> {
+        match self.count(index) {
+            0 => None,
+            count => self.get_str_nth(index, roll % count),
+        }
+    }
+}
+
+impl MsgProvider for MsgDictionary {
+    fn get_str(&self, index: u32) -> Option<&str> {
+        self.get_first_string(index)
+    }
+
+    fn get_str_nth(&self, index: u32, skip: u32) -> Option<&str> {
+        let (_key, value) = self.resolved_range(index).nth(skip as usize)?;
+        value.string()
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        MsgDictionary::count(self, index)
+    }
+}
+
+impl<P: MsgProvider + ?Sized> MsgProvider for &P {
+    fn get_str(&self, index: u32) -> Option<&str> {
+        (**self).get_str(index)
+    }
+
+    fn get_str_nth(&self, index: u32, skip: u32) -> Option<&str> {
+        (**self).get_str_nth(index, skip)
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        (**self).count(index)
+    }
+}
+
+/// Looks every index up in the first provider that has it, e.g. a translation
+/// falling back to the original language.
+#[derive(Debug, Default)]
+pub struct FallbackChain<P> {
+    providers: Vec<P>,
+}
+
+impl<P: MsgProvider> FallbackChain<P> {
+    pub fn new(providers: Vec<P>) -> Self {
+        Self { providers }
+    }
+
+    /// Adds a provider with the lowest priority.
+    pub fn push(&mut self, provider: P) {
+        self.providers.push(provider);
+    }
+
+    fn provider_for(&self, index: u32) -> Option<&P> {
+        self.providers.iter().find(|provider| provider.has(index))
+    }
+}
+
+impl<P: MsgProvider> MsgProvider for FallbackChain<P> {
+    fn get_str_nth(&self, index: u32, skip: u32) -> Option<&str> {
+        self.provider_for(index)?.get_str_nth(index, skip)
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        self.provider_for(index)
+            .map_or(0, |provider| provider.count(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn dictionary_provider() {
+        let dict = parse_msg(b"{1}{}{a}\n{2}{}{x}\n{2}{}{y}\n{2}{}{z}").unwrap();
+        assert_eq!(MsgProvider::get_str(&dict, 1), Some("a"));
+        assert_eq!(dict.get_str_nth(2, 2), Some("z"));
+        assert_eq!(dict.get_random(2, 4), Some("y"));
+        assert_eq!(dict.get_random(3, 4), None);
+        assert!(!dict.has(3));
+    }
+
+    #[test]
+    fn fallback_chain() {
+        let russ = parse_msg("{1}{}{Привет}".as_bytes()).unwrap();
+        let engl = parse_msg(b"{1}{}{Hello}\n{2}{}{Bye}\n{2}{}{Farewell}").unwrap();
+        let chain = FallbackChain::new(vec![&russ, &engl]);
+        assert_eq!(chain.get_str(1), Some("Привет"));
+        assert_eq!(chain.get_str(2), Some("Bye"));
+        assert_eq!(chain.count(2), 2);
+        assert_eq!(chain.get_str(3), None);
+    }
+}
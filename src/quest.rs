@@ -0,0 +1,59 @@
+use super::{DictView, MsgDictionary};
+
+/// Indices reserved per quest in FOQUEST.MSG.
+pub const QUEST_MUL: u32 = 1000;
+
+/// View over FOQUEST.MSG: quest `id` has its title at `id * 1000` and the text of
+/// stage `n` at `id * 1000 + n`.
+#[derive(Debug, Clone, Copy)]
+pub struct QuestMsg<'a> {
+    view: DictView<'a>,
+}
+
+impl<'a> QuestMsg<'a> {
+    pub fn new(dict: &'a MsgDictionary) -> Self {
+        Self {
+            view: DictView::new(dict).with_base_stride(0, QUEST_MUL),
+        }
+    }
+
+    pub fn title(&self, quest_id: u32) -> Option<&'a str> {
+        self.view.get(quest_id, 0)
+    }
+
+    /// Text of `stage`, counting from 1.
+    pub fn stage_text(&self, quest_id: u32, stage: u32) -> Option<&'a str> {
+        if stage == 0 {
+            return None;
+        }
+        self.view.get(quest_id, stage)
+    }
+
+    /// Ids of quests with a title, ascending.
+    pub fn quests(&self) -> impl Iterator<Item = u32> + 'a {
+        let view = self.view;
+        view.groups()
+            .filter(move |&quest_id| view.get_bytes(quest_id, 0).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn quest_lookups() {
+        let dict = parse_msg(
+            b"{2000}{}{Rats}\n{2001}{}{Kill rats}\n{2002}{}{Done}\n{5000}{}{Car}\n{5003}{}{Fuel}\n",
+        )
+        .unwrap();
+        let quests = QuestMsg::new(&dict);
+        assert_eq!(quests.quests().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(quests.title(2), Some("Rats"));
+        assert_eq!(quests.stage_text(2, 2), Some("Done"));
+        assert_eq!(quests.stage_text(5, 3), Some("Fuel"));
+        assert_eq!(quests.stage_text(2, 0), None);
+        assert_eq!(quests.title(u32::MAX), None);
+    }
+}
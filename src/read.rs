@@ -0,0 +1,98 @@
+use std::collections::btree_map::BTreeMap;
+
+use super::{MsgDictionary, MsgProvider};
+
+/// Object-safe read-only view, keyed by `(index, sub_index)`.
+///
+/// Take `&dyn MsgRead` where lookups should be mockable with [`StubMsg`].
+pub trait MsgRead {
+    fn get_string(&self, index: u32, sub_index: u32) -> Option<&str>;
+
+    fn get_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]>;
+
+    /// Number of values stored under `index`.
+    fn count(&self, index: u32) -> u32;
+}
+
+impl MsgRead for MsgDictionary {
+    fn get_string(&self, index: u32, sub_index: u32) -> Option<&str> {
+        self.lookup((index, sub_index), None)?.string()
+    }
+
+    fn get_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        Some(self.lookup((index, sub_index), None)?.bytes())
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        MsgDictionary::count(self, index)
+    }
+}
+
+/// In-memory [`MsgRead`] for tests.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StubMsg {
+    values: BTreeMap<(u32, u32), String>,
+}
+
+impl StubMsg {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a value to `index`.
+    pub fn with(mut self, index: u32, value: impl Into<String>) -> Self {
+        self.insert(index, value);
+        self
+    }
+
+    /// Appends a value to `index`.
+    pub fn insert(&mut self, index: u32, value: impl Into<String>) {
+        let sub_index = MsgRead::count(self, index);
+        self.values.insert((index, sub_index), value.into());
+    }
+}
+
+impl MsgRead for StubMsg {
+    fn get_string(&self, index: u32, sub_index: u32) -> Option<&str> {
+        self.values.get(&(index, sub_index)).map(String::as_str)
+    }
+
+    fn get_bytes(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        self.get_string(index, sub_index).map(str::as_bytes)
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        self.values.range((index, 0)..=(index, u32::MAX)).count() as u32
+    }
+}
+
+impl MsgProvider for StubMsg {
+    fn get_str_nth(&self, index: u32, skip: u32) -> Option<&str> {
+        self.get_string(index, skip)
+    }
+
+    fn count(&self, index: u32) -> u32 {
+        MsgRead::count(self, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    fn greeting(msg: &dyn MsgRead) -> String {
+        format!("{}!", msg.get_string(100, 0).unwrap_or("?"))
+    }
+
+    #[test]
+    fn stub_and_dictionary_are_interchangeable() {
+        let stub = StubMsg::new().with(100, "Hi").with(100, "Hey");
+        let dict = parse_msg(b"{100}{}{Hi}\n{100}{}{Hey}").unwrap();
+        assert_eq!(greeting(&stub), "Hi!");
+        assert_eq!(greeting(&dict), "Hi!");
+        assert_eq!(MsgRead::count(&stub, 100), 2);
+        assert_eq!(stub.get_bytes(100, 1), dict.get_bytes(100, 1));
+        assert_eq!(stub.get_random(100, 1), Some("Hey"));
+    }
+}
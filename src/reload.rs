@@ -0,0 +1,202 @@
+use std::{
+    collections::btree_map::BTreeMap,
+    fmt,
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use super::{LanguagePack, ManifestMismatch, ParseError};
+
+/// What changed in a [`ReloadablePack::reload`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReloadEvent {
+    /// Generation the change produced, starting from 1 after the initial load.
+    pub generation: u64,
+    /// Names of added, removed or modified files, sorted.
+    pub changed: Vec<String>,
+}
+
+type Subscriber = Arc<dyn Fn(&ReloadEvent) + Send + Sync>;
+
+/// Handle to a language directory that can be reloaded while readers hold
+/// earlier snapshots, numbering every change with a generation.
+pub struct ReloadablePack {
+    dir: PathBuf,
+    current: RwLock<(u64, Arc<LanguagePack>)>,
+    subscribers: Mutex<BTreeMap<u64, Subscriber>>,
+    next_subscriber: Mutex<u64>,
+}
+
+impl fmt::Debug for ReloadablePack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReloadablePack")
+            .field("dir", &self.dir)
+            .field("generation", &self.generation())
+            .finish()
+    }
+}
+
+impl ReloadablePack {
+    /// Loads `dir` as generation 0.
+    pub fn load(dir: impl Into<PathBuf>) -> Result<Self, ParseError> {
+        let dir = dir.into();
+        let pack = LanguagePack::load_dir(&dir)?;
+        Ok(Self {
+            dir,
+            current: RwLock::new((0, Arc::new(pack))),
+            subscribers: Mutex::new(BTreeMap::new()),
+            next_subscriber: Mutex::new(0),
+        })
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.current.read().unwrap_or_else(|err| err.into_inner()).0
+    }
+
+    /// Current generation and its pack.
+    pub fn snapshot(&self) -> (u64, Arc<LanguagePack>) {
+        let current = self.current.read().unwrap_or_else(|err| err.into_inner());
+        (current.0, current.1.clone())
+    }
+
+    /// Calls `callback` after every reload that changed files. Returns an id for
+    /// [`unsubscribe`](Self::unsubscribe).
+    pub fn subscribe(&self, callback: impl Fn(&ReloadEvent) + Send + Sync + 'static) -> u64 {
+        let mut next = self
+            .next_subscriber
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let id = *next;
+        *next += 1;
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(id, Arc::new(callback));
+        id
+    }
+
+    pub fn unsubscribe(&self, id: u64) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&id);
+    }
+
+    /// Reloads the directory. If any file changed, bumps the generation, notifies
+    /// subscribers and returns the event; otherwise keeps the snapshot and returns `None`.
+    pub fn reload(&self) -> Result<Option<ReloadEvent>, ParseError> {
+        let pack = LanguagePack::load_dir(&self.dir)?;
+        let event = {
+            let mut current = self.current.write().unwrap_or_else(|err| err.into_inner());
+            let mut changed: Vec<String> = pack
+                .verify(&current.1.manifest())
+                .into_iter()
+                .map(|mismatch| match mismatch {
+                    ManifestMismatch::Missing(name) | ManifestMismatch::Unexpected(name) => name,
+                    ManifestMismatch::Changed { name, .. } => name,
+                })
+                .collect();
+            if changed.is_empty() {
+                return Ok(None);
+            }
+            changed.sort();
+            *current = (current.0 + 1, Arc::new(pack));
+            ReloadEvent {
+                generation: current.0,
+                changed,
+            }
+        };
+        // Called without the lock, so callbacks may subscribe or unsubscribe.
+        let subscribers: Vec<Subscriber> = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        for subscriber in subscribers {
+            subscriber(&event);
+        }
+        Ok(Some(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn generations_and_notifications() {
+        let dir = std::env::temp_dir().join("fo_msg_format_reload");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("FOTEXT.MSG"), b"{1}{}{a}\n").unwrap();
+        fs::write(dir.join("FOGM.MSG"), b"{10}{}{map}\n").unwrap();
+
+        let pack = ReloadablePack::load(&dir).unwrap();
+        let (generation, before) = pack.snapshot();
+        assert_eq!(generation, 0);
+        let events = Arc::new(Mutex::new(vec![]));
+        let sink = events.clone();
+        let id = pack.subscribe(move |event| sink.lock().unwrap().push(event.clone()));
+
+        assert_eq!(pack.reload().unwrap(), None);
+        fs::write(dir.join("FOTEXT.MSG"), b"{1}{}{b}\n").unwrap();
+        fs::write(dir.join("FODLG.MSG"), b"").unwrap();
+        let event = pack.reload().unwrap().unwrap();
+        assert_eq!(
+            event,
+            ReloadEvent {
+                generation: 1,
+                changed: vec!["FODLG.MSG".into(), "FOTEXT.MSG".into()],
+            }
+        );
+        assert_eq!(
+            before.get("FOTEXT.MSG").unwrap().get_first_string(1),
+            Some("a")
+        );
+        assert_eq!(
+            pack.snapshot()
+                .1
+                .get("FOTEXT.MSG")
+                .unwrap()
+                .get_first_string(1),
+            Some("b")
+        );
+
+        pack.unsubscribe(id);
+        fs::remove_file(dir.join("FODLG.MSG")).unwrap();
+        assert_eq!(pack.reload().unwrap().unwrap().generation, 2);
+        assert_eq!(*events.lock().unwrap(), vec![event]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn callbacks_may_unsubscribe() {
+        let dir = std::env::temp_dir().join("fo_msg_format_reload_unsubscribe");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("FOTEXT.MSG"), b"{1}{}{a}\n").unwrap();
+
+        let pack = Arc::new(ReloadablePack::load(&dir).unwrap());
+        let calls = Arc::new(Mutex::new(0));
+        let (handle, count) = (Arc::downgrade(&pack), calls.clone());
+        let id = pack.subscribe(move |_event| {
+            *count.lock().unwrap() += 1;
+            if let Some(pack) = handle.upgrade() {
+                pack.unsubscribe(0);
+                pack.subscribe(|_event| {});
+            }
+        });
+        assert_eq!(id, 0);
+
+        fs::write(dir.join("FOTEXT.MSG"), b"{1}{}{b}\n").unwrap();
+        pack.reload().unwrap().unwrap();
+        fs::write(dir.join("FOTEXT.MSG"), b"{1}{}{c}\n").unwrap();
+        pack.reload().unwrap().unwrap();
+        assert_eq!(*calls.lock().unwrap(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
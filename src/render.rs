@@ -0,0 +1,137 @@
+use std::{
+    collections::{HashMap, btree_map::BTreeMap, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+};
+
+use super::MsgDictionary;
+
+/// Replaces `@lex name@` lexems in `template` with values from `args`, keeping
+/// unknown lexems as they are.
+pub fn render_lexems(template: &str, args: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("@lex ") {
+        let after = &rest[start + "@lex ".len()..];
+        let end = match after.find('@') {
+            Some(end) => end,
+            None => break,
+        };
+        let name = &after[..end];
+        out.push_str(&rest[..start]);
+        match args.iter().find(|&&(arg, _value)| arg == name) {
+            Some(&(_arg, value)) => out.push_str(value),
+            None => out.push_str(&rest[start..start + "@lex ".len() + end + 1]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Bounded least-recently-used cache of rendered values, keyed by index and a
+/// hash of the lexem arguments.
+#[derive(Debug)]
+pub struct RenderCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<(u32, u64), (u64, String)>,
+    /// Last use tick to key, oldest first.
+    recency: BTreeMap<u64, (u32, u64)>,
+}
+
+impl RenderCache {
+    /// `capacity` must not be 0.
+    pub fn new(capacity: usize) -> Self {
+        assert_ne!(capacity, 0, "RenderCache capacity must not be 0");
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    /// [`render_lexems`] over the first value of `index`, reusing earlier results
+    /// for the same arguments.
+    ///
+    /// Call [`clear`](Self::clear) after editing `dict`.
+    pub fn render(
+        &mut self,
+        dict: &MsgDictionary,
+        index: u32,
+        args: &[(&str, &str)],
+    ) -> Option<&str> {
+        let mut hasher = DefaultHasher::new();
+        args.hash(&mut hasher);
+        let key = (index, hasher.finish());
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some((used, _value)) = self.entries.get_mut(&key) {
+            self.recency.remove(used);
+            *used = tick;
+        } else {
+            let value = render_lexems(dict.get_first_string(index)?, args);
+            if self.entries.len() >= self.capacity {
+                if let Some((_tick, oldest)) = self.recency.pop_first() {
+                    self.entries.remove(&oldest);
+                }
+            }
+            self.entries.insert(key, (tick, value));
+        }
+        self.recency.insert(tick, key);
+        self.entries.get(&key).map(|(_tick, value)| value.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn lexems() {
+        assert_eq!(
+            render_lexems(
+                "@lex name@ hits for @lex dmg@, @lex unknown@",
+                &[("name", "Bob"), ("dmg", "7")]
+            ),
+            "Bob hits for 7, @lex unknown@"
+        );
+        assert_eq!(
+            render_lexems("broken @lex name", &[("name", "Bob")]),
+            "broken @lex name"
+        );
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let dict = parse_msg(b"{1}{}{Hi @lex name@}\n{2}{}{Bye}\n").unwrap();
+        let mut cache = RenderCache::new(2);
+        assert_eq!(cache.render(&dict, 1, &[("name", "Bob")]), Some("Hi Bob"));
+        assert_eq!(cache.render(&dict, 1, &[("name", "Ann")]), Some("Hi Ann"));
+        assert_eq!(cache.render(&dict, 1, &[("name", "Bob")]), Some("Hi Bob"));
+        assert_eq!(cache.render(&dict, 2, &[]), Some("Bye"));
+        assert_eq!(cache.len(), 2);
+        // "Ann" was used least recently.
+        assert!(
+            cache
+                .entries
+                .values()
+                .all(|(_tick, value)| value != "Hi Ann")
+        );
+        assert_eq!(cache.render(&dict, 3, &[]), None);
+    }
+}
@@ -0,0 +1,294 @@
+use super::{
+    LanguagePack, MsgDictionary, MsgLine,
+    case::{cp1251_bytes, cp1251_string},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReplaceOptions {
+    /// Match ignoring case (Unicode simple case folding per character).
+    pub case_insensitive: bool,
+    /// Only match where the needle isn't surrounded by letters, digits or `_`.
+    pub whole_word: bool,
+}
+
+/// A value changed by [`MsgDictionary::replace_all`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedKey {
+    pub index: u32,
+    pub sub_index: u32,
+    /// Number of replaced occurrences.
+    pub count: usize,
+    pub old: String,
+    pub new: String,
+}
+
+fn chars_eq(left: char, right: char, case_insensitive: bool) -> bool {
+    left == right || (case_insensitive && left.to_lowercase().eq(right.to_lowercase()))
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// End of `needle` if it matches `value` at byte `start`.
+fn match_at(value: &str, start: usize, needle: &str, options: ReplaceOptions) -> Option<usize> {
+    let mut chars = value[start..].char_indices();
+    for expected in needle.chars() {
+        let (_pos, ch) = chars.next()?;
+        if !chars_eq(ch, expected, options.case_insensitive) {
+            return None;
+        }
+    }
+    let end = chars.next().map_or(value.len(), |(pos, _ch)| start + pos);
+    if options.whole_word {
+        let before = value[..start].chars().next_back();
+        let after = value[end..].chars().next();
+        if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+            return None;
+        }
+    }
+    Some(end)
+}
+
+/// `value` with the replacements and their count.
+fn replace_in(
+    value: &str,
+    needle: &str,
+    replacement: &str,
+    options: ReplaceOptions,
+) -> (String, usize) {
+    let mut out = String::with_capacity(value.len());
+    let mut count = 0;
+    let mut copied_up_to = 0;
+    let mut pos = 0;
+    while pos < value.len() {
+        match match_at(value, pos, needle, options) {
+            Some(end) => {
+                out.push_str(&value[copied_up_to..pos]);
+                out.push_str(replacement);
+                count += 1;
+                copied_up_to = end;
+                pos = end;
+            }
+            None => pos += value[pos..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    out.push_str(&value[copied_up_to..]);
+    (out, count)
+}
+
+/// `new` stored like `old`: byte values are encoded back to CP1251, or become
+/// strings if `new` can't be.
+fn replaced(old: &MsgLine, new: &str) -> MsgLine {
+    match old {
+        MsgLine::Bytes(_) => cp1251_bytes(new).map_or_else(
+            || MsgLine::String(new.into()),
+            |bytes| MsgLine::Bytes(bytes.into()),
+        ),
+        MsgLine::String(_) => MsgLine::String(new.into()),
+    }
+}
+
+impl MsgDictionary {
+    /// Replaces `needle` with `replacement` in every value, returning what changed,
+    /// e.g. to rename an item everywhere it is mentioned.
+    ///
+    /// Byte values are matched decoded as CP1251 and written back encoded, or as
+    /// strings if the replacement has characters CP1251 lacks. Without the `cp1251`
+    /// feature only ASCII byte values are matched.
+    pub fn replace_all(
+        &mut self,
+        needle: &str,
+        replacement: &str,
+        options: ReplaceOptions,
+    ) -> Vec<ChangedKey> {
+        if needle.is_empty() {
+            return vec![];
+        }
+        let (changes, values): (Vec<_>, Vec<_>) = self
+            .index_to_line
+            .iter()
+            .filter_map(|(&(index, sub_index), value)| {
+                let old = cp1251_string(value)?;
+                let (new, count) = replace_in(&old, needle, replacement, options);
+                if count == 0 {
+                    return None;
+                }
+                let line = replaced(value, &new);
+                let change = ChangedKey {
+                    index,
+                    sub_index,
+                    count,
+                    old: old.into_owned(),
+                    new,
+                };
+                Some((change, line))
+            })
+            .unzip();
+        for (change, value) in changes.iter().zip(values) {
+            self.index_to_line
+                .insert((change.index, change.sub_index), value);
+        }
+        changes
+    }
+}
+
+#[cfg(feature = "regex")]
+impl MsgDictionary {
+    /// Rewrites every match of `regex` in values, `rewrite` may refer to capture
+    /// groups as `$1` or `$name` (see [`regex::Replacer`]).
+    ///
+    /// Byte values are handled like in [`replace_all`](Self::replace_all).
+    pub fn replace_regex(
+        &mut self,
+        regex: &regex::Regex,
+        rewrite: impl regex::Replacer,
+    ) -> Vec<ChangedKey> {
+        let changes = self.preview_regex(regex, rewrite);
+        for change in &changes {
+            let key = (change.index, change.sub_index);
+            let value = match self.index_to_line.get(&key) {
+                Some(old) => replaced(old, &change.new),
+                None => continue,
+            };
+            self.index_to_line.insert(key, value);
+        }
+        changes
+    }
+
+    /// Dry run of [`MsgDictionary::replace_regex`]: what would change, without changing it.
+    pub fn preview_regex(
+        &self,
+        regex: &regex::Regex,
+        mut rewrite: impl regex::Replacer,
+    ) -> Vec<ChangedKey> {
+        self.index_to_line
+            .iter()
+            .filter_map(|(&(index, sub_index), value)| {
+                let old = cp1251_string(value)?;
+                let count = regex.find_iter(&old).count();
+                if count == 0 {
+                    return None;
+                }
+                let new = regex.replace_all(&old, rewrite.by_ref()).into_owned();
+                Some(ChangedKey {
+                    index,
+                    sub_index,
+                    count,
+                    old: old.into_owned(),
+                    new,
+                })
+            })
+            .collect()
+    }
+}
+
+impl LanguagePack {
+    /// [`MsgDictionary::replace_all`] over every file, changes keyed by file name.
+    pub fn replace_all(
+        &mut self,
+        needle: &str,
+        replacement: &str,
+        options: ReplaceOptions,
+    ) -> Vec<(String, ChangedKey)> {
+        let mut changes = vec![];
+        for (name, dict) in self.iter_mut() {
+            for change in dict.replace_all(needle, replacement, options) {
+                changes.push((name.to_owned(), change));
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn replace_options() {
+        let (out, count) = replace_in(
+            "Vault 13, vault13, VAULT 13",
+            "vault",
+            "Bunker",
+            ReplaceOptions::default(),
+        );
+        assert_eq!((out.as_str(), count), ("Vault 13, Bunker13, VAULT 13", 1));
+        let options = ReplaceOptions {
+            case_insensitive: true,
+            whole_word: true,
+        };
+        let (out, count) = replace_in("Vault 13, vault13, VAULT 13", "vault", "Bunker", options);
+        assert_eq!((out.as_str(), count), ("Bunker 13, vault13, Bunker 13", 2));
+        let (out, _count) = replace_in("Ёж и ЁЖИК", "ёж", "Еж", options);
+        assert_eq!(out, "Еж и ЁЖИК");
+    }
+
+    #[test]
+    fn change_report() {
+        let mut pack = LanguagePack::new();
+        pack.insert(
+            "FOGAME.MSG",
+            parse_msg(b"{1}{}{Go to Den}\n{2}{}{Den, again}\n{3}{}{Golden}\n").unwrap(),
+        );
+        pack.insert("FODLG.MSG", parse_msg(b"{5}{}{No}\n").unwrap());
+        let options = ReplaceOptions {
+            whole_word: true,
+            ..ReplaceOptions::default()
+        };
+        let changes = pack.replace_all("Den", "The Den", options);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|(file, change)| (file.as_str(), change.index, change.count))
+                .collect::<Vec<_>>(),
+            vec![("FOGAME.MSG", 1, 1), ("FOGAME.MSG", 2, 1)]
+        );
+        assert_eq!(changes[1].1.old, "Den, again");
+        let dict = pack.get("FOGAME.MSG").unwrap();
+        assert_eq!(dict.get_first_string(1), Some("Go to The Den"));
+        assert_eq!(dict.get_first_string(3), Some("Golden"));
+    }
+
+    #[test]
+    fn cp1251_byte_values() {
+        // "Идти в Хаб" and "Хабы" in CP1251
+        let mut dict =
+            parse_msg(b"{1}{}{\xc8\xe4\xf2\xe8 \xe2 \xd5\xe0\xe1}\n{2}{}{\xd5\xe0\xe1\xfb}\n")
+                .unwrap();
+        let options = ReplaceOptions {
+            whole_word: true,
+            ..ReplaceOptions::default()
+        };
+        let changes = dict.replace_all("Хаб", "Город", options);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].old, "Идти в Хаб");
+        assert_eq!(changes[0].new, "Идти в Город");
+        assert_eq!(
+            dict.get_first_bytes(1),
+            Some(&b"\xc8\xe4\xf2\xe8 \xe2 \xc3\xee\xf0\xee\xe4"[..])
+        );
+
+        // No CP1251 for the replacement, so the value becomes a string.
+        dict.replace_all("Хабы", "漢", options);
+        assert_eq!(dict.get_first_string(2), Some("漢"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_rewrite() {
+        let mut dict = parse_msg(b"{1}{}{|0xFF0000 Danger|}\n{2}{}{Plain}\n").unwrap();
+        let regex = regex::Regex::new(r"\|0x([0-9A-F]{6}) ([^|]*)\|").unwrap();
+        let preview = dict.preview_regex(&regex, "<color=#$1>$2</color>");
+        assert_eq!(preview.len(), 1);
+        assert_eq!(dict.get_first_string(1), Some("|0xFF0000 Danger|"));
+
+        let changes = dict.replace_regex(&regex, "<color=#$1>$2</color>");
+        assert_eq!(changes, preview);
+        assert_eq!(
+            dict.get_first_string(1),
+            Some("<color=#FF0000>Danger</color>")
+        );
+    }
+}
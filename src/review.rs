@@ -0,0 +1,143 @@
+use super::{MsgDictionary, MsgLine, diff::text};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewKind {
+    /// The translation lacks the key.
+    Missing,
+    /// The translation has the base value verbatim, likely untranslated.
+    Untranslated,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewItem {
+    pub key: (u32, u32),
+    pub kind: ReviewKind,
+    pub base: String,
+}
+
+/// Steps through the keys of a translation that need attention, editing values
+/// in place. The model behind `fo_msg review` (`tui` feature).
+#[derive(Debug)]
+pub struct ReviewSession {
+    translated: MsgDictionary,
+    items: Vec<ReviewItem>,
+    position: usize,
+}
+
+impl ReviewSession {
+    pub fn new(base: &MsgDictionary, translated: MsgDictionary) -> Self {
+        let items = base
+            .index_to_line
+            .iter()
+            .filter_map(|(&key, base_value)| {
+                let kind = match translated.index_to_line.get(&key) {
+                    None => ReviewKind::Missing,
+                    Some(value) if value == base_value => ReviewKind::Untranslated,
+                    Some(_) => return None,
+                };
+                Some(ReviewItem {
+                    key,
+                    kind,
+                    base: text(base_value).into_owned(),
+                })
+            })
+            .collect();
+        Self {
+            translated,
+            items,
+            position: 0,
+        }
+    }
+
+    pub fn items(&self) -> &[ReviewItem] {
+        &self.items
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn current(&self) -> Option<&ReviewItem> {
+        self.items.get(self.position)
+    }
+
+    /// Current translated value, if any.
+    pub fn current_value(&self) -> Option<&str> {
+        let key = self.current()?.key;
+        self.translated.index_to_line.get(&key)?.string()
+    }
+
+    /// Moves to the next item, returns `false` at the end.
+    pub fn advance(&mut self) -> bool {
+        if self.position + 1 < self.items.len() {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn back(&mut self) -> bool {
+        if self.position > 0 {
+            self.position -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets the translated value of the current item.
+    pub fn edit(&mut self, value: &str) {
+        if let Some(item) = self.items.get(self.position) {
+            self.translated
+                .index_to_line
+                .insert(item.key, MsgLine::String(value.into()));
+        }
+    }
+
+    /// Translation with the edits so far.
+    pub fn translated(&self) -> &MsgDictionary {
+        &self.translated
+    }
+
+    pub fn into_translated(self) -> MsgDictionary {
+        self.translated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn review_queue() {
+        let base = parse_msg(b"{1}{}{Hello}\n{2}{}{Bye}\n{3}{}{Yes}\n").unwrap();
+        let translated = parse_msg("{1}{}{Привет}\n{2}{}{Bye}\n".as_bytes()).unwrap();
+        let mut session = ReviewSession::new(&base, translated);
+        let kinds: Vec<_> = session
+            .items()
+            .iter()
+            .map(|item| (item.key.0, item.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![(2, ReviewKind::Untranslated), (3, ReviewKind::Missing)]
+        );
+
+        assert_eq!(session.current_value(), Some("Bye"));
+        session.edit("Пока");
+        assert!(session.advance());
+        assert_eq!(session.current_value(), None);
+        session.edit("Да");
+        assert!(!session.advance());
+        assert!(session.back());
+        assert_eq!(session.translated().get_first_string(3), Some("Да"));
+
+        let result = session.into_translated();
+        assert_eq!(
+            result,
+            parse_msg("{1}{}{Привет}\n{2}{}{Пока}\n{3}{}{Да}\n".as_bytes()).unwrap()
+        );
+    }
+}
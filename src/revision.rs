@@ -0,0 +1,147 @@
+use std::{collections::btree_map::BTreeMap, fmt, str::FromStr};
+
+use super::{MsgDictionary, pack::fnv1a};
+
+/// Revision of a single key, see [`RevisionLog`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Revision {
+    /// Bumped every time the value changes.
+    pub number: u32,
+    pub fingerprint: u64,
+}
+
+/// Per-key revision counters tracked across successive states of a dictionary.
+///
+/// Keep one log for the source language and save a copy of it next to each
+/// translation; [`RevisionLog::stale_since`] then lists source keys that changed
+/// after the translation was made. Text form is one `index\tsub_index\tnumber\tfingerprint`
+/// line per key.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RevisionLog {
+    revisions: BTreeMap<(u32, u32), Revision>,
+}
+
+impl MsgDictionary {
+    /// FNV-1a hash of the value bytes.
+    pub fn fingerprint(&self, index: u32, sub_index: u32) -> Option<u64> {
+        let value = self.index_to_line.get(&(index, sub_index))?;
+        Some(fnv1a(value.bytes()))
+    }
+}
+
+impl RevisionLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, index: u32, sub_index: u32) -> Option<Revision> {
+        self.revisions.get(&(index, sub_index)).copied()
+    }
+
+    /// Records the current state of `dict`, returning keys that were added or changed.
+    ///
+    /// Keys missing from `dict` are dropped from the log.
+    pub fn update(&mut self, dict: &MsgDictionary) -> Vec<(u32, u32)> {
+        let mut changed = vec![];
+        let mut revisions = BTreeMap::new();
+        for (&key, value) in dict.index_to_line.iter() {
+            let fingerprint = fnv1a(value.bytes());
+            let revision = match self.revisions.get(&key) {
+                Some(old) if old.fingerprint == fingerprint => *old,
+                Some(old) => Revision {
+                    number: old.number + 1,
+                    fingerprint,
+                },
+                None => Revision {
+                    number: 1,
+                    fingerprint,
+                },
+            };
+            if self.revisions.get(&key) != Some(&revision) {
+                changed.push(key);
+            }
+            revisions.insert(key, revision);
+        }
+        self.revisions = revisions;
+        changed
+    }
+
+    /// Keys whose revision is newer here than in `older`, including keys `older` lacks.
+    pub fn stale_since(&self, older: &RevisionLog) -> Vec<(u32, u32)> {
+        self.revisions
+            .iter()
+            .filter(|(key, revision)| {
+                older
+                    .revisions
+                    .get(key)
+                    .is_none_or(|old| old.number < revision.number)
+            })
+            .map(|(&key, _revision)| key)
+            .collect()
+    }
+}
+
+impl fmt::Display for RevisionLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (&(index, sub_index), revision) in &self.revisions {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{:016x}",
+                index, sub_index, revision.number, revision.fingerprint
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RevisionLog {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut revisions = BTreeMap::new();
+        for (number, line) in s.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let bad_line = || format!("Malformed revision log line {}", number + 1);
+            let parts: Vec<_> = line.split('\t').collect();
+            if parts.len() != 4 {
+                return Err(bad_line());
+            }
+            let index = parts[0].parse().map_err(|_| bad_line())?;
+            let sub_index = parts[1].parse().map_err(|_| bad_line())?;
+            let revision = Revision {
+                number: parts[2].parse().map_err(|_| bad_line())?,
+                fingerprint: u64::from_str_radix(parts[3], 16).map_err(|_| bad_line())?,
+            };
+            revisions.insert((index, sub_index), revision);
+        }
+        Ok(RevisionLog { revisions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn tracks_changes() {
+        let mut log = RevisionLog::new();
+        let v1 = parse_msg(b"{1}{}{Hello}\n{2}{}{Bye}").unwrap();
+        assert_eq!(log.update(&v1), vec![(1, 0), (2, 0)]);
+        let translated_against = log.clone();
+
+        let v2 = parse_msg(b"{1}{}{Hello}\n{2}{}{Goodbye}\n{3}{}{New}").unwrap();
+        assert_eq!(log.update(&v2), vec![(2, 0), (3, 0)]);
+        assert_eq!(log.get(2, 0).unwrap().number, 2);
+        assert_eq!(log.stale_since(&translated_against), vec![(2, 0), (3, 0)]);
+    }
+
+    #[test]
+    fn text_roundtrip() {
+        let mut log = RevisionLog::new();
+        log.update(&parse_msg(b"{1}{}{Hello}\n{1}{}{World}").unwrap());
+        assert_eq!(log.to_string().parse::<RevisionLog>().unwrap(), log);
+    }
+}
@@ -0,0 +1,88 @@
+use super::{LineDecoder, ParseError, parse_msg_with};
+
+const CONTEXT_LEN: usize = 20;
+
+/// Outcome of [`verify_roundtrip`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundtripReport {
+    pub input_len: usize,
+    pub output_len: usize,
+    /// First place where the re-serialized output differs from the input.
+    pub divergence: Option<Divergence>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Byte offset of the first differing byte.
+    pub offset: usize,
+    /// 1-based line of `offset` in the input.
+    pub line: usize,
+    /// Input bytes starting at `offset`.
+    pub expected: Vec<u8>,
+    /// Output bytes starting at `offset`.
+    pub found: Vec<u8>,
+}
+
+impl RoundtripReport {
+    pub fn is_exact(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// Parses `input`, serializes it back and compares the result byte by byte.
+pub fn verify_roundtrip(input: &[u8]) -> Result<RoundtripReport, ParseError> {
+    let output = parse_msg_with(input, LineDecoder::RawBytes)?.to_msg_bytes();
+    Ok(compare(input, &output))
+}
+
+pub(crate) fn compare(input: &[u8], output: &[u8]) -> RoundtripReport {
+    let common = input
+        .iter()
+        .zip(output)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let divergence = if common == input.len() && common == output.len() {
+        None
+    } else {
+        let context = |bytes: &[u8]| bytes[common..].iter().copied().take(CONTEXT_LEN).collect();
+        Some(Divergence {
+            offset: common,
+            line: 1 + input[..common]
+                .iter()
+                .filter(|&&byte| byte == b'\n')
+                .count(),
+            expected: context(input),
+            found: context(output),
+        })
+    };
+    RoundtripReport {
+        input_len: input.len(),
+        output_len: output.len(),
+        divergence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_roundtrip() {
+        let report = verify_roundtrip(b"{10}{}{Global map}\n{15}{}{20car}\n").unwrap();
+        assert!(report.is_exact());
+    }
+
+    #[test]
+    fn reports_first_divergence() {
+        let report = verify_roundtrip(b"{10}{}{Global map}\n# comment\n{15}{}{20car}\n").unwrap();
+        assert_eq!(
+            report.divergence,
+            Some(Divergence {
+                offset: 19,
+                line: 2,
+                expected: b"# comment\n{15}{}{20c".to_vec(),
+                found: b"{15}{}{20car}\n".to_vec(),
+            })
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::Write;
+
+use super::{MsgDictionary, MsgLine};
+
+/// What [`MsgDictionary::sanitize`] does with control characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SanitizePolicy {
+    #[default]
+    Remove,
+    /// Replaces them with `\xNN` (`\u{NN}` for C1 controls in strings).
+    Escape,
+}
+
+/// Value changed by [`MsgDictionary::sanitize`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizedEntry {
+    pub index: u32,
+    pub sub_index: u32,
+    /// Number of characters (bytes for byte values) removed or escaped.
+    pub count: usize,
+}
+
+impl MsgDictionary {
+    /// Removes or escapes control characters other than tab and line breaks.
+    ///
+    /// Values kept as bytes are checked for C0 controls and DEL only, their
+    /// encoding is unknown. String values can't hold unpaired surrogates, those are
+    /// rejected or replaced when decoding.
+    pub fn sanitize(&mut self, policy: SanitizePolicy) -> Vec<SanitizedEntry> {
+        let dirty: Vec<_> = self
+            .index_to_line
+            .iter()
+            .filter_map(|(&key, value)| {
+                let (clean, count) = sanitize_line(value, policy)?;
+                Some((key, clean, count))
+            })
+            .collect();
+        let mut report = vec![];
+        for ((index, sub_index), clean, count) in dirty {
+            self.index_to_line.insert((index, sub_index), clean);
+            report.push(SanitizedEntry {
+                index,
+                sub_index,
+                count,
+            });
+        }
+        report
+    }
+}
+
+fn is_control(ch: char) -> bool {
+    ch.is_control() && !matches!(ch, '\t' | '\n' | '\r')
+}
+
+fn sanitize_line(value: &MsgLine, policy: SanitizePolicy) -> Option<(MsgLine, usize)> {
+    match value {
+        MsgLine::String(string) => {
+            let count = string.chars().filter(|&ch| is_control(ch)).count();
+            if count == 0 {
+                return None;
+            }
+            let mut clean = String::with_capacity(string.len());
+            for ch in string.chars() {
+                match (is_control(ch), policy) {
+                    (false, _) => clean.push(ch),
+                    (true, SanitizePolicy::Remove) => {}
+                    (true, SanitizePolicy::Escape) if ch.is_ascii() => {
+                        let _ = write!(clean, "\\x{:02x}", ch as u32);
+                    }
+                    (true, SanitizePolicy::Escape) => {
+                        let _ = write!(clean, "\\u{{{:x}}}", ch as u32);
+                    }
+                }
+            }
+            Some((MsgLine::String(clean.into()), count))
+        }
+        MsgLine::Bytes(bytes) => {
+            let is_control = |byte: u8| is_control(byte as char) && byte < 0x80;
+            let count = bytes.iter().filter(|&&byte| is_control(byte)).count();
+            if count == 0 {
+                return None;
+            }
+            let mut clean = Vec::with_capacity(bytes.len());
+            for &byte in bytes.iter() {
+                match (is_control(byte), policy) {
+                    (false, _) => clean.push(byte),
+                    (true, SanitizePolicy::Remove) => {}
+                    (true, SanitizePolicy::Escape) => {
+                        clean.extend_from_slice(format!("\\x{:02x}", byte).as_bytes())
+                    }
+                }
+            }
+            Some((MsgLine::Bytes(clean.into()), count))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LineDecoder, parse_msg_with};
+
+    #[test]
+    fn sanitize_values() {
+        let input = b"{1}{}{ok\ttab\nline}\n{2}{}{bell\x07 and\x1b}\n";
+        let mut dict = parse_msg_with(input, LineDecoder::Utf8Strict).unwrap();
+        dict.insert(3, MsgLine::Bytes(b"\xcf\x7f"[..].into()));
+        let report = dict.sanitize(SanitizePolicy::Remove);
+        assert_eq!(
+            report
+                .iter()
+                .map(|entry| (entry.index, entry.count))
+                .collect::<Vec<_>>(),
+            vec![(2, 2), (3, 1)]
+        );
+        assert_eq!(dict.get_first_string(1), Some("ok\ttab\nline"));
+        assert_eq!(dict.get_first_string(2), Some("bell and"));
+        assert_eq!(dict.get_first_bytes(3), Some(&b"\xcf"[..]));
+        assert!(dict.sanitize(SanitizePolicy::Remove).is_empty());
+    }
+
+    #[test]
+    fn escape_controls() {
+        let mut dict =
+            parse_msg_with("{1}{}{a\u{1}b\u{85}}".as_bytes(), LineDecoder::Utf8Strict).unwrap();
+        dict.sanitize(SanitizePolicy::Escape);
+        assert_eq!(dict.get_first_string(1), Some("a\\x01b\\u{85}"));
+    }
+}
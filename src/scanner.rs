@@ -0,0 +1,234 @@
+use std::fmt;
+
+use super::{Entry, Line, Msg, ParseError, lexer};
+
+/// Byte-input tokenizer, the hand-written scanner with the `fast-lexer` feature.
+///
+/// That and [`find_byte`] are all the feature changes; parse results are the same either way.
+pub(crate) fn tokenize_bytes(input: &[u8], exhaustive: bool) -> Result<Msg<&[u8]>, ParseError> {
+    if cfg!(feature = "fast-lexer") {
+        scan_msg(input, exhaustive)
+    } else {
+        let (rest, msg) =
+            lexer::tokenize_msg_prefix(input).map_err(|err| ParseError::syntax(input, err))?;
+        if exhaustive && !rest.is_empty() {
+            return Err(ParseError::non_exhaustive(input, input.len() - rest.len()));
+        }
+        Ok(msg)
+    }
+}
+
+/// Prefix counterpart of [`tokenize_bytes`].
+pub(crate) fn tokenize_bytes_prefix(input: &[u8]) -> Result<(&[u8], Msg<&[u8]>), ParseError> {
+    if cfg!(feature = "fast-lexer") {
+        scan_prefix(input).map_err(|err| ParseError::scan(input, err))
+    } else {
+        lexer::tokenize_msg_prefix(input).map_err(|err| ParseError::syntax(input, err))
+    }
+}
+
+/// Position of the first `needle` in `haystack`, with SIMD where `memchr` supports it
+/// (`fast-lexer` feature only).
+pub(crate) fn find_byte(needle: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(feature = "fast-lexer")]
+    {
+        memchr::memchr(needle, haystack)
+    }
+    #[cfg(not(feature = "fast-lexer"))]
+    {
+        haystack.iter().position(|&byte| byte == needle)
+    }
+}
+
+/// Scanner counterpart of [`tokenize_bytes`].
+pub(crate) fn scan_msg(input: &[u8], exhaustive: bool) -> Result<Msg<&[u8]>, ParseError> {
+    let (rest, msg) = scan_prefix(input).map_err(|err| ParseError::scan(input, err))?;
+    if exhaustive && !rest.is_empty() {
+        return Err(ParseError::non_exhaustive(input, input.len() - rest.len()));
+    }
+    Ok(msg)
+}
+
+/// Hand-written equivalent of [`lexer::tokenize_msg_prefix`] for byte input.
+fn scan_prefix(input: &[u8]) -> Result<(&[u8], Msg<&[u8]>), ScanError> {
+    let mut scanner = Scanner::new(input);
+    let mut lines = vec![];
+    loop {
+        lines.push(scanner.line()?);
+        if !scanner.newline() {
+            break;
+        }
+    }
+    Ok((&input[scanner.pos..], Msg { lines }))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ScanError {
+    pub offset: usize,
+    pub expected: &'static str,
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Expected {} at byte {}", self.expected, self.offset)
+    }
+}
+
+pub(crate) struct Scanner<'a> {
+    input: &'a [u8],
+    pub pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    pub fn rest(&self) -> &'a [u8] {
+        &self.input[self.pos..]
+    }
+
+    pub fn space0(&mut self) {
+        while let Some(b' ' | b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    pub fn newline(&mut self) -> bool {
+        let len = if self.rest().starts_with(b"\r\n") {
+            2
+        } else if self.rest().starts_with(b"\n") {
+            1
+        } else {
+            return false;
+        };
+        self.pos += len;
+        true
+    }
+
+    pub fn line(&mut self) -> Result<Line<&'a [u8]>, ScanError> {
+        if let Some(comment) = self.comment() {
+            return Ok(Line::Comment(comment));
+        }
+        self.space0();
+        if self.peek() == Some(b'{') {
+            self.entry().map(Line::Entry)
+        } else {
+            Ok(Line::Break)
+        }
+    }
+
+    /// `#` or `//` comment up to the line ending, leaving `pos` untouched if absent.
+    fn comment(&mut self) -> Option<&'a [u8]> {
+        let start = self.pos;
+        self.space0();
+        let marker = if self.rest().starts_with(b"#") {
+            1
+        } else if self.rest().starts_with(b"//") {
+            2
+        } else {
+            self.pos = start;
+            return None;
+        };
+        self.pos += marker;
+        self.space0();
+        let text_start = self.pos;
+        self.pos = match find_byte(b'\n', self.rest()) {
+            Some(len) if len > 0 && self.rest()[len - 1] == b'\r' => text_start + len - 1,
+            Some(len) => text_start + len,
+            None => self.input.len(),
+        };
+        Some(&self.input[text_start..self.pos])
+    }
+
+    fn entry(&mut self) -> Result<Entry<&'a [u8]>, ScanError> {
+        let index = self.curly(|scanner| scanner.number())?;
+        let secondary = self.curly(|scanner| Ok(scanner.not_closing_curly()))?;
+        let value = self.curly(|scanner| Ok(scanner.not_closing_curly()))?;
+        let comment = self.comment();
+        Ok(Entry {
+            index,
+            secondary,
+            value,
+            comment,
+        })
+    }
+
+    fn curly<T>(
+        &mut self,
+        inner: impl FnOnce(&mut Self) -> Result<T, ScanError>,
+    ) -> Result<T, ScanError> {
+        self.expect(b'{', "`{`")?;
+        let value = inner(self)?;
+        self.expect(b'}', "`}`")?;
+        Ok(value)
+    }
+
+    fn expect(&mut self, byte: u8, expected: &'static str) -> Result<(), ScanError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(ScanError {
+                offset: self.pos,
+                expected,
+            })
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, ScanError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.pos])
+            .ok()
+            .and_then(|digits| digits.parse().ok())
+            .ok_or(ScanError {
+                offset: start,
+                expected: "an index",
+            })
+    }
+
+    fn not_closing_curly(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        self.pos = find_byte(b'}', self.rest()).map_or(self.input.len(), |len| start + len);
+        &self.input[start..self.pos]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_nom_lexer() {
+        let samples: &[&[u8]] = &[
+            b"",
+            b"\n# Transit Name\r\n\n  // Map 0\n{10}{}{Global map}\n{15}{}{20car}  # car\n{15}{}{03 - A Way.ogg}",
+            b"{4294967295}{             zxc}{zxc              zxc}",
+            b"{2}{\n foo \n   \n}{\n\n\n   bar}\r\n",
+            b"{1}{}{a}\nnot an entry",
+            b"# a\rb\r\n{1}{}{a} // c\r\r\n#\n# last",
+        ];
+        for &sample in samples {
+            assert_eq!(
+                scan_prefix(sample).map_err(|err| err.to_string()),
+                lexer::tokenize_msg_prefix(sample)
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert!(scan_msg(b"{1}{}{unclosed\n", true).is_err());
+        assert!(scan_msg(b"{x}{}{a}", true).is_err());
+        assert!(scan_msg(b"{4294967296}{}{a}", true).is_err());
+        assert!(scan_msg(b"{1}{}{a}\njunk", true).is_err());
+        assert!(scan_msg(b"{1}{}{a}\njunk", false).is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::btree_map::BTreeMap;
+
+use super::MsgDictionary;
+
+/// Index ranges a message file is expected to use, e.g. FOGM map names at
+/// `(pid + 1) * 10` or FOOBJ descriptions at `pid * 100`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "toml-schema", derive(serde::Deserialize))]
+pub struct Schema {
+    #[cfg_attr(feature = "toml-schema", serde(default))]
+    pub ranges: Vec<IndexRange>,
+}
+
+/// Indices `start..end`, split into items of `stride` indices each. If `slots`
+/// is not empty, only the listed offsets inside an item are valid.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "toml-schema", derive(serde::Deserialize))]
+pub struct IndexRange {
+    pub name: String,
+    pub start: u32,
+    pub end: u32,
+    #[cfg_attr(feature = "toml-schema", serde(default = "default_stride"))]
+    pub stride: u32,
+    #[cfg_attr(feature = "toml-schema", serde(default))]
+    pub slots: BTreeMap<String, u32>,
+    /// Intentionally kept free, see [`Schema::gaps`].
+    #[cfg_attr(feature = "toml-schema", serde(default))]
+    pub reserved: bool,
+}
+
+#[cfg(feature = "toml-schema")]
+fn default_stride() -> u32 {
+    1
+}
+
+impl IndexRange {
+    pub fn new(name: &str, range: std::ops::Range<u32>) -> Self {
+        Self {
+            name: name.to_owned(),
+            start: range.start,
+            end: range.end,
+            stride: 1,
+            slots: BTreeMap::new(),
+            reserved: false,
+        }
+    }
+
+    /// Marks the range as intentionally unused.
+    pub fn reserved(mut self) -> Self {
+        self.reserved = true;
+        self
+    }
+
+    /// Splits the range into items of `stride` indices with named offsets.
+    pub fn with_slots(mut self, stride: u32, slots: &[(&str, u32)]) -> Self {
+        self.stride = stride;
+        self.slots = slots
+            .iter()
+            .map(|&(name, offset)| (name.to_owned(), offset))
+            .collect();
+        self
+    }
+
+    pub fn contains(&self, index: u32) -> bool {
+        if index < self.start || index >= self.end {
+            return false;
+        }
+        let offset = (index - self.start) % self.stride.max(1);
+        self.slots.is_empty() || self.slots.values().any(|&slot| slot == offset)
+    }
+
+    /// Index of `slot` of item number `item`.
+    pub fn index_of(&self, item: u32, slot: &str) -> Option<u32> {
+        let offset = *self.slots.get(slot)?;
+        let index = item
+            .checked_mul(self.stride)?
+            .checked_add(self.start)?
+            .checked_add(offset)?;
+        Some(index).filter(|&index| index < self.end)
+    }
+}
+
+impl Schema {
+    pub fn new(ranges: Vec<IndexRange>) -> Self {
+        Self { ranges }
+    }
+
+    #[cfg(feature = "toml-schema")]
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|err| err.to_string())
+    }
+
+    pub fn range(&self, name: &str) -> Option<&IndexRange> {
+        self.ranges.iter().find(|range| range.name == name)
+    }
+
+    /// Indices of `dict` outside every declared range.
+    pub fn validate(&self, dict: &MsgDictionary) -> Vec<u32> {
+        dict.indices()
+            .filter(|&index| !self.ranges.iter().any(|range| range.contains(index)))
+            .collect()
+    }
+
+    /// Typed access to the items of range `name` in `dict`.
+    pub fn view<'a>(&'a self, name: &str, dict: &'a MsgDictionary) -> Option<SchemaView<'a>> {
+        Some(SchemaView {
+            range: self.range(name)?,
+            dict,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaView<'a> {
+    range: &'a IndexRange,
+    dict: &'a MsgDictionary,
+}
+
+impl<'a> SchemaView<'a> {
+    /// First value of `slot` of item number `item`.
+    pub fn get(&self, item: u32, slot: &str) -> Option<&'a str> {
+        self.dict.get_first_string(self.range.index_of(item, slot)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    fn fogm() -> Schema {
+        Schema::new(vec![
+            IndexRange::new("system", 0..10),
+            IndexRange::new("maps", 10..100_000)
+                .with_slots(10, &[("name", 0), ("music", 5), ("transit", 8)]),
+        ])
+    }
+
+    #[test]
+    fn validate_ranges() {
+        let dict = parse_msg(b"{1}{}{A}\n{20}{}{Den}\n{23}{}{?}\n{25}{}{den.ogg}\n").unwrap();
+        assert_eq!(fogm().validate(&dict), vec![23]);
+    }
+
+    #[test]
+    fn typed_view() {
+        let dict = parse_msg(b"{20}{}{Den}\n{25}{}{den.ogg}\n").unwrap();
+        let schema = fogm();
+        let maps = schema.view("maps", &dict).unwrap();
+        assert_eq!(maps.get(1, "name"), Some("Den"));
+        assert_eq!(maps.get(1, "music"), Some("den.ogg"));
+        assert_eq!(maps.get(1, "transit"), None);
+        assert_eq!(maps.get(1, "unknown"), None);
+        assert!(schema.view("items", &dict).is_none());
+    }
+}
@@ -0,0 +1,118 @@
+use super::{ChangedKey, MsgDictionary, MsgLine};
+
+/// Name of the function [`ScriptTransform`] calls for every entry.
+const TRANSFORM_FN: &str = "transform";
+
+/// User-written [rhai](https://rhai.rs) transform run over every string value.
+///
+/// The script defines `fn transform(index, value)`, returning the new value as a
+/// string or `()` to leave the entry as it is:
+///
+/// ```text
+/// fn transform(index, value) {
+///     if index >= 1000 && index < 2000 {
+///         value.replace("...", "…");
+///         return value;
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ScriptTransform {
+    engine: rhai::Engine,
+    ast: rhai::AST,
+}
+
+impl ScriptTransform {
+    pub fn new(script: &str) -> Result<Self, String> {
+        let engine = rhai::Engine::new();
+        let ast = engine.compile(script).map_err(|err| err.to_string())?;
+        Ok(Self { engine, ast })
+    }
+
+    /// New value for an entry, `None` to keep it.
+    pub fn apply(&self, index: u32, value: &str) -> Result<Option<String>, String> {
+        let mut scope = rhai::Scope::new();
+        let result: rhai::Dynamic = self
+            .engine
+            .call_fn(
+                &mut scope,
+                &self.ast,
+                TRANSFORM_FN,
+                (index as i64, value.to_owned()),
+            )
+            .map_err(|err| format!("[{}] {}", index, err))?;
+        if result.is_unit() {
+            return Ok(None);
+        }
+        let type_name = result.type_name();
+        result.into_string().map(Some).map_err(|_| {
+            format!(
+                "[{}] transform returned {}, expected a string",
+                index, type_name
+            )
+        })
+    }
+}
+
+impl MsgDictionary {
+    /// Runs `script` over every string value. On error nothing is changed.
+    pub fn transform_with_script(
+        &mut self,
+        script: &ScriptTransform,
+    ) -> Result<Vec<ChangedKey>, String> {
+        let mut changes = vec![];
+        for (&(index, sub_index), value) in self.index_to_line.iter() {
+            let old = match value.string() {
+                Some(old) => old,
+                None => continue,
+            };
+            match script.apply(index, old)? {
+                Some(new) if new != old => changes.push(ChangedKey {
+                    index,
+                    sub_index,
+                    count: 1,
+                    old: old.to_owned(),
+                    new,
+                }),
+                _ => {}
+            }
+        }
+        for change in &changes {
+            self.index_to_line.insert(
+                (change.index, change.sub_index),
+                MsgLine::String(change.new.as_str().into()),
+            );
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn script_changes_values() {
+        let mut dict = parse_msg(b"{1}{}{keep}\n{1000}{}{Wait...}\n{1001}{}{ok}\n").unwrap();
+        let script = ScriptTransform::new(
+            r#"
+            fn transform(index, value) {
+                if index >= 1000 && value.contains("...") {
+                    value.replace("...", "!");
+                    return value;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let changes = dict.transform_with_script(&script).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(dict.get_first_string(1000), Some("Wait!"));
+        assert_eq!(dict.get_first_string(1), Some("keep"));
+
+        let broken = ScriptTransform::new("fn transform(index, value) { 42 }").unwrap();
+        assert!(dict.transform_with_script(&broken).is_err());
+        assert_eq!(dict.get_first_string(1), Some("keep"));
+    }
+}
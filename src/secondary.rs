@@ -0,0 +1,74 @@
+use super::MsgDictionary;
+
+/// What parsing does with an entry whose second braces aren't empty, e.g. `{100}{x}{text}`.
+///
+/// The game never reads the secondary key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SecondaryKeyPolicy {
+    /// Parse the entry, dropping the key.
+    Ignore,
+    /// Fail with [`crate::ParseError::Syntax`] pointing at the key.
+    #[default]
+    Error,
+    /// Parse the entry and keep the key, see [`MsgDictionary::secondary_key`].
+    Keep,
+}
+
+impl MsgDictionary {
+    /// Non-empty secondary key of an entry, kept by [`SecondaryKeyPolicy::Keep`] and
+    /// written back by [`MsgDictionary::write_to`].
+    pub fn secondary_key(&self, index: u32, sub_index: u32) -> Option<&[u8]> {
+        self.secondary_keys
+            .get(&(index, sub_index))
+            .map(|key| &**key)
+    }
+
+    /// Sets the secondary key of an entry, removing it if `key` is empty.
+    pub fn set_secondary_key(&mut self, index: u32, sub_index: u32, key: &[u8]) {
+        if key.is_empty() {
+            self.secondary_keys.remove(&(index, sub_index));
+        } else {
+            self.secondary_keys.insert((index, sub_index), key.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseError, ParseOptions, parse_msg, parse_msg_with_options};
+
+    #[test]
+    fn policies() {
+        let input = b"{1}{}{a}\n{2}{x}{b}\n";
+        match parse_msg(input) {
+            Err(ParseError::Syntax {
+                offset,
+                line,
+                column,
+                message,
+                help,
+            }) => {
+                assert_eq!((offset, line, column), (13, 2, 5));
+                assert_eq!(message, "Non-empty secondary key for index 2");
+                assert!(help.is_some());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let parse = |secondary_keys| {
+            let options = ParseOptions {
+                secondary_keys,
+                ..ParseOptions::default()
+            };
+            parse_msg_with_options(input, &options).unwrap()
+        };
+        let ignored = parse(SecondaryKeyPolicy::Ignore);
+        assert_eq!(ignored.get_first_string(2), Some("b"));
+        assert_eq!(ignored.secondary_key(2, 0), None);
+
+        let kept = parse(SecondaryKeyPolicy::Keep);
+        assert_eq!(kept.secondary_key(2, 0), Some(&b"x"[..]));
+        assert_eq!(kept.to_msg_bytes(), input);
+    }
+}
@@ -0,0 +1,199 @@
+use std::ops::{Range, RangeInclusive};
+
+use super::{
+    LineDecoder, LineEntry, MsgDocument, ParseError,
+    document::{DocLine, DocLineKind},
+    parse_lines,
+};
+
+/// Comment-delimited part of a MSG file, e.g. `# Map 0, Global, base 10`.
+///
+/// A section starts at a comment line opening the file or following a blank line,
+/// and runs up to the next one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Text of the opening comment, without the marker.
+    pub title: String,
+    /// 1-based lines of the section.
+    pub lines: Range<usize>,
+    /// Byte range of the section in the document's bytes.
+    pub span: Range<usize>,
+    /// Lowest and highest index of the section's entries.
+    pub indices: Option<RangeInclusive<u32>>,
+    /// Indices of entries with their positions in [`MsgDocument::lines`].
+    entries: Vec<(u32, usize)>,
+}
+
+impl MsgDocument {
+    /// Sections in file order. Entries before the first section aren't in any.
+    pub fn sections(&self) -> Vec<Section> {
+        sections(&self.lines)
+    }
+
+    /// Adds an entry into the section whose indices `index` falls among, after the
+    /// section's last entry with a lower or equal index, returning its key.
+    ///
+    /// Indices past every section go to the section ending below them, indices
+    /// before every section to the first one, and without sections to the end of the file.
+    pub fn insert_into_section(&mut self, index: u32, value: &[u8]) -> Result<(u32, u32), String> {
+        let sections = self.sections();
+        let sections: Vec<_> = sections
+            .iter()
+            .filter_map(|section| Some((section.indices.clone()?, section)))
+            .collect();
+        let section = sections
+            .iter()
+            .find(|(indices, _)| indices.contains(&index))
+            .or_else(|| {
+                sections
+                    .iter()
+                    .filter(|(indices, _)| *indices.end() < index)
+                    .max_by_key(|(indices, _)| *indices.end())
+            })
+            .or_else(|| sections.first())
+            .map(|(_, section)| section);
+        let at = match section {
+            Some(section) => section
+                .entries
+                .iter()
+                .filter(|(entry, _)| *entry <= index)
+                .map(|(_, line)| line + 1)
+                .max()
+                .unwrap_or(section.entries[0].1),
+            // Above the empty line after a final line ending.
+            None => match self.lines.last() {
+                Some(last)
+                    if self.lines.len() > 1
+                        && last.raw().is_empty()
+                        && last.ending().is_empty() =>
+                {
+                    self.lines.len() - 1
+                }
+                _ => self.lines.len(),
+            },
+        };
+        self.insert_entry(at, index, value)
+    }
+}
+
+fn sections<R: AsRef<[u8]>>(lines: &[DocLine<R>]) -> Vec<Section> {
+    let mut sections: Vec<Section> = vec![];
+    let mut line = 1;
+    let mut offset = 0;
+    let mut after_break = true;
+    for (position, doc_line) in lines.iter().enumerate() {
+        match doc_line.kind() {
+            DocLineKind::Comment(text) if after_break => {
+                if let Some(last) = sections.last_mut() {
+                    last.lines.end = line;
+                    last.span.end = offset;
+                }
+                sections.push(Section {
+                    title: String::from_utf8_lossy(&doc_line.raw()[text.clone()]).into_owned(),
+                    lines: line..line,
+                    span: offset..offset,
+                    indices: None,
+                    entries: vec![],
+                });
+            }
+            &DocLineKind::Entry { index, .. } => {
+                if let Some(last) = sections.last_mut() {
+                    last.indices = Some(match last.indices.take() {
+                        Some(indices) => {
+                            (*indices.start()).min(index)..=(*indices.end()).max(index)
+                        }
+                        None => index..=index,
+                    });
+                    last.entries.push((index, position));
+                }
+            }
+            _ => {}
+        }
+        after_break = matches!(doc_line.kind(), DocLineKind::Blank);
+        offset += doc_line.raw().len() + doc_line.ending().len();
+        line += doc_line
+            .raw()
+            .iter()
+            .chain(doc_line.ending())
+            .filter(|&&byte| byte == b'\n')
+            .count();
+    }
+    if let Some(last) = sections.last_mut() {
+        last.lines.end = line + 1;
+        last.span.end = offset;
+    }
+    sections
+}
+
+/// Entries of the first section titled `title`.
+pub fn section_entries(
+    input: &[u8],
+    title: &str,
+    decoder: LineDecoder,
+) -> Result<Vec<LineEntry>, ParseError> {
+    let section = MsgDocument::parse(input)?
+        .sections()
+        .into_iter()
+        .find(|section| section.title == title)
+        .ok_or_else(|| ParseError::Invalid(format!("No section titled {:?}", title)))?;
+    parse_lines(input, section.lines, decoder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &[u8] = b"{1}{}{intro}\n\n# Map 0, Global, base 10\n# music below\n{10}{}{Global map}\n{15}{}{20car}\n\n# Map 1, Den, base 20\n{20}{}{Den}\n{28}{}{Den transit}";
+
+    #[test]
+    fn lists_sections() {
+        let sections = MsgDocument::parse(INPUT).unwrap().sections();
+        let titles: Vec<_> = sections
+            .iter()
+            .map(|section| {
+                (
+                    section.title.as_str(),
+                    section.lines.clone(),
+                    section.indices.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("Map 0, Global, base 10", 3..8, Some(10..=15)),
+                ("Map 1, Den, base 20", 8..11, Some(20..=28)),
+            ]
+        );
+        assert_eq!(
+            &INPUT[sections[1].span.clone()],
+            b"# Map 1, Den, base 20\n{20}{}{Den}\n{28}{}{Den transit}"
+        );
+
+        let entries =
+            section_entries(INPUT, "Map 0, Global, base 10", LineDecoder::Utf8Strict).unwrap();
+        assert_eq!(
+            entries.iter().map(|entry| entry.index).collect::<Vec<_>>(),
+            vec![10, 15]
+        );
+        assert!(section_entries(INPUT, "Map 2", LineDecoder::Utf8Strict).is_err());
+    }
+
+    #[test]
+    fn inserts_where_indices_belong() {
+        let mut doc = MsgDocument::parse(INPUT).unwrap();
+        assert_eq!(doc.insert_into_section(12, b"new").unwrap(), (12, 0));
+        doc.insert_into_section(30, b"after").unwrap();
+        doc.insert_into_section(25, b"den").unwrap();
+        doc.insert_into_section(5, b"first").unwrap();
+        assert_eq!(
+            String::from_utf8(doc.to_bytes()).unwrap(),
+            "{1}{}{intro}\n\n# Map 0, Global, base 10\n# music below\n{5}{}{first}\n{10}{}{Global map}\n{12}{}{new}\n{15}{}{20car}\n\n# Map 1, Den, base 20\n{20}{}{Den}\n{25}{}{den}\n{28}{}{Den transit}\n{30}{}{after}"
+        );
+
+        let mut doc = MsgDocument::parse(b"{1}{}{a}\n").unwrap();
+        assert_eq!(doc.insert_into_section(1, b"b").unwrap(), (1, 1));
+        assert_eq!(doc.to_bytes(), b"{1}{}{a}\n{1}{}{b}\n");
+        assert!(doc.insert_into_section(2, b"}").is_err());
+    }
+}
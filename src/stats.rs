@@ -0,0 +1,89 @@
+use std::{collections::btree_map::BTreeMap, ops::Range};
+
+use super::{LanguagePack, MsgDictionary, MsgLine, display_width};
+
+/// Distribution of value lengths, see [`MsgDictionary::length_stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LengthStats {
+    /// Number of values per length bucket, keyed by the bucket's lowest length.
+    pub histogram: BTreeMap<usize, usize>,
+    /// The longest values with their lengths, longest first.
+    pub longest: Vec<((u32, u32), usize)>,
+}
+
+/// Display columns of string values, bytes of byte values.
+fn value_len(value: &MsgLine) -> usize {
+    match value {
+        MsgLine::String(string) => display_width(string),
+        MsgLine::Bytes(bytes) => bytes.len(),
+    }
+}
+
+impl MsgDictionary {
+    /// Length histogram with buckets `bucket_width` long and the `top` longest
+    /// values among indices in `range`, to find text likely to overflow dialog boxes.
+    pub fn length_stats(&self, range: Range<u32>, bucket_width: usize, top: usize) -> LengthStats {
+        let bucket_width = bucket_width.max(1);
+        let mut stats = LengthStats::default();
+        let mut lengths = vec![];
+        for (&key, value) in self.index_to_line.iter() {
+            if !range.contains(&key.0) {
+                continue;
+            }
+            let len = value_len(value);
+            *stats
+                .histogram
+                .entry(len / bucket_width * bucket_width)
+                .or_default() += 1;
+            lengths.push((key, len));
+        }
+        lengths.sort_by(|(left_key, left), (right_key, right)| {
+            right.cmp(left).then(left_key.cmp(right_key))
+        });
+        lengths.truncate(top);
+        stats.longest = lengths;
+        stats
+    }
+}
+
+impl LanguagePack {
+    /// [`MsgDictionary::length_stats`] of every file over all indices.
+    pub fn length_stats(&self, bucket_width: usize, top: usize) -> BTreeMap<String, LengthStats> {
+        self.iter()
+            .map(|(name, dict)| {
+                (
+                    name.to_owned(),
+                    dict.length_stats(0..u32::MAX, bucket_width, top),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn histogram_and_longest() {
+        let dict = parse_msg(
+            "{1}{}{ab}\n{2}{}{Привет}\n{3}{}{abcdefghijkl}\n{4}{}{abc}\n{100}{}{way too long to count}\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let stats = dict.length_stats(0..100, 5, 2);
+        assert_eq!(
+            stats.histogram.into_iter().collect::<Vec<_>>(),
+            vec![(0, 2), (5, 1), (10, 1)]
+        );
+        assert_eq!(stats.longest, vec![((3, 0), 12), ((2, 0), 6)]);
+
+        let mut pack = LanguagePack::new();
+        pack.insert("FOGAME.MSG", dict);
+        assert_eq!(
+            pack.length_stats(5, 1)["FOGAME.MSG"].longest,
+            vec![((100, 0), 21)]
+        );
+    }
+}
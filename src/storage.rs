@@ -0,0 +1,300 @@
+use std::{
+    collections::{
+        btree_map::BTreeMap,
+        btree_set::BTreeSet,
+        hash_map::{HashMap, RandomState},
+    },
+    fmt::Debug,
+    hash::BuildHasher,
+    mem::size_of,
+};
+
+use super::MsgLine;
+
+type Entries<'a> = Box<dyn DoubleEndedIterator<Item = (&'a (u32, u32), &'a MsgLine)> + 'a>;
+
+/// Backing store of a [`crate::MsgDictionary`], keyed by `(index, sub_index)`.
+///
+/// Costs of the provided storages, for `n` entries and `k` returned:
+///
+/// | | `BTreeMap` | [`SortedVecStorage`] | [`HashStorage`] |
+/// |---|---|---|---|
+/// | `get` | O(log n) | O(log n) | O(1) |
+/// | `range` | O(log n + k) | O(log n + k) | O(k) |
+/// | `iter` | O(n) | O(n) | O(n log n), sorting a copy of every key on each call |
+/// | `after` | O(log n + k) | O(log n + k) | as `iter` |
+/// | `insert`, `remove` | O(log n) | O(n) | O(1), plus O(log k) for the index's sub-indices |
+///
+/// Dictionary methods that walk every entry, such as writing, diffing or paging,
+/// go through `iter`, so prefer the tree or the sorted vector for those.
+pub trait MsgStorage: Debug + Send + Sync {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, key: &(u32, u32)) -> Option<&MsgLine>;
+
+    /// Every sub-entry of `index`, in sub-index order.
+    fn range(&self, index: u32) -> Entries<'_>;
+
+    /// Every entry, in key order.
+    fn iter(&self) -> Entries<'_>;
+
+    /// Entries with keys greater than `key`, in key order.
+    ///
+    /// Filters [`iter`](Self::iter) unless the storage can seek.
+    fn after(&self, key: (u32, u32)) -> Entries<'_> {
+        Box::new(self.iter().filter(move |&(&entry, _value)| entry > key))
+    }
+
+    fn insert(&mut self, key: (u32, u32), value: MsgLine) -> Option<MsgLine>;
+
+    fn remove(&mut self, key: &(u32, u32)) -> Option<MsgLine>;
+
+    /// Approximate heap bytes spent on keys, value headers and the structure itself.
+    fn heap_overhead(&self) -> usize;
+
+    /// Every entry, in key order.
+    fn into_entries(self: Box<Self>) -> Vec<((u32, u32), MsgLine)>;
+}
+
+const ENTRY_SIZE: usize = size_of::<((u32, u32), MsgLine)>();
+
+impl MsgStorage for BTreeMap<(u32, u32), MsgLine> {
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn get(&self, key: &(u32, u32)) -> Option<&MsgLine> {
+        BTreeMap::get(self, key)
+    }
+
+    fn range(&self, index: u32) -> Entries<'_> {
+        Box::new(BTreeMap::range(self, (index, 0)..=(index, u32::MAX)))
+    }
+
+    fn iter(&self) -> Entries<'_> {
+        Box::new(BTreeMap::iter(self))
+    }
+
+    fn after(&self, key: (u32, u32)) -> Entries<'_> {
+        Box::new(BTreeMap::range(
+            self,
+            (std::ops::Bound::Excluded(key), std::ops::Bound::Unbounded),
+        ))
+    }
+
+    fn insert(&mut self, key: (u32, u32), value: MsgLine) -> Option<MsgLine> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &(u32, u32)) -> Option<MsgLine> {
+        BTreeMap::remove(self, key)
+    }
+
+    /// Nodes are about two thirds full on average.
+    fn heap_overhead(&self) -> usize {
+        BTreeMap::len(self) * ENTRY_SIZE * 3 / 2
+    }
+
+    fn into_entries(self: Box<Self>) -> Vec<((u32, u32), MsgLine)> {
+        self.into_iter().collect()
+    }
+}
+
+/// Sorted vector with binary-search lookups: the smallest footprint, but O(n) insertions.
+#[derive(Debug, Default)]
+pub struct SortedVecStorage {
+    entries: Vec<((u32, u32), MsgLine)>,
+}
+
+impl SortedVecStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn position(&self, key: &(u32, u32)) -> Result<usize, usize> {
+        self.entries.binary_search_by_key(key, |(key, _value)| *key)
+    }
+}
+
+impl From<Vec<((u32, u32), MsgLine)>> for SortedVecStorage {
+    fn from(mut entries: Vec<((u32, u32), MsgLine)>) -> Self {
+        entries.sort_by_key(|(key, _value)| *key);
+        entries.dedup_by_key(|(key, _value)| *key);
+        entries.shrink_to_fit();
+        Self { entries }
+    }
+}
+
+impl MsgStorage for SortedVecStorage {
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn get(&self, key: &(u32, u32)) -> Option<&MsgLine> {
+        self.position(key).ok().map(|pos| &self.entries[pos].1)
+    }
+
+    fn range(&self, index: u32) -> Entries<'_> {
+        let start = self
+            .entries
+            .partition_point(|((key_index, _), _)| *key_index < index);
+        let end = self
+            .entries
+            .partition_point(|((key_index, _), _)| *key_index <= index);
+        Box::new(
+            self.entries[start..end]
+                .iter()
+                .map(|(key, value)| (key, value)),
+        )
+    }
+
+    fn iter(&self) -> Entries<'_> {
+        Box::new(self.entries.iter().map(|(key, value)| (key, value)))
+    }
+
+    fn after(&self, key: (u32, u32)) -> Entries<'_> {
+        let start = self
+            .entries
+            .partition_point(|(entry, _value)| *entry <= key);
+        Box::new(
+            self.entries[start..]
+                .iter()
+                .map(|(key, value)| (key, value)),
+        )
+    }
+
+    fn insert(&mut self, key: (u32, u32), value: MsgLine) -> Option<MsgLine> {
+        match self.position(&key) {
+            Ok(pos) => Some(std::mem::replace(&mut self.entries[pos].1, value)),
+            Err(pos) => {
+                self.entries.insert(pos, (key, value));
+                None
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &(u32, u32)) -> Option<MsgLine> {
+        let pos = self.position(key).ok()?;
+        Some(self.entries.remove(pos).1)
+    }
+
+    fn heap_overhead(&self) -> usize {
+        self.entries.capacity() * ENTRY_SIZE
+    }
+
+    fn into_entries(self: Box<Self>) -> Vec<((u32, u32), MsgLine)> {
+        self.entries
+    }
+}
+
+/// Hash map with O(1) lookups and per-index sets of sub-indices.
+///
+/// Iterating every entry sorts all keys on each call, see [`MsgStorage`].
+#[derive(Debug, Default)]
+pub struct HashStorage<S = RandomState> {
+    entries: HashMap<(u32, u32), MsgLine, S>,
+    sub_indices: HashMap<u32, BTreeSet<u32>, S>,
+}
+
+/// [`HashStorage`] with the `rustc-hash` Fx hasher.
+#[cfg(feature = "fxhash")]
+pub type FxHashStorage = HashStorage<std::hash::BuildHasherDefault<rustc_hash::FxHasher>>;
+
+impl<S: BuildHasher + Default> HashStorage<S> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::default(),
+            sub_indices: HashMap::default(),
+        }
+    }
+}
+
+impl<S: BuildHasher + Debug + Send + Sync> MsgStorage for HashStorage<S> {
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn get(&self, key: &(u32, u32)) -> Option<&MsgLine> {
+        self.entries.get(key)
+    }
+
+    fn range(&self, index: u32) -> Entries<'_> {
+        let entries = &self.entries;
+        let sub_indices = self.sub_indices.get(&index).into_iter().flatten();
+        Box::new(
+            sub_indices.filter_map(move |&sub_index| entries.get_key_value(&(index, sub_index))),
+        )
+    }
+
+    fn iter(&self) -> Entries<'_> {
+        let mut sorted: Vec<_> = self.entries.iter().collect();
+        sorted.sort_unstable_by_key(|(key, _value)| **key);
+        Box::new(sorted.into_iter())
+    }
+
+    fn insert(&mut self, key: (u32, u32), value: MsgLine) -> Option<MsgLine> {
+        self.sub_indices.entry(key.0).or_default().insert(key.1);
+        self.entries.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &(u32, u32)) -> Option<MsgLine> {
+        let value = self.entries.remove(key)?;
+        if let Some(subs) = self.sub_indices.get_mut(&key.0) {
+            subs.remove(&key.1);
+            if subs.is_empty() {
+                self.sub_indices.remove(&key.0);
+            }
+        }
+        Some(value)
+    }
+
+    fn heap_overhead(&self) -> usize {
+        self.entries.capacity() * (ENTRY_SIZE + 1)
+            + self.sub_indices.capacity() * (size_of::<(u32, BTreeSet<u32>)>() + 1)
+            + self.entries.len() * size_of::<u32>() * 3 / 2
+    }
+
+    fn into_entries(self: Box<Self>) -> Vec<((u32, u32), MsgLine)> {
+        let mut entries: Vec<_> = self.entries.into_iter().collect();
+        entries.sort_unstable_by_key(|(key, _value)| *key);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(mut storage: Box<dyn MsgStorage>) {
+        for &(key, value) in &[((2, 0), "c"), ((1, 1), "b"), ((1, 0), "a"), ((3, 0), "d")] {
+            assert_eq!(storage.insert(key, MsgLine::String(value.into())), None);
+        }
+        assert_eq!(storage.len(), 4);
+        assert_eq!(storage.get(&(1, 1)), Some(&MsgLine::String("b".into())));
+        let range: Vec<_> = storage.range(1).map(|(key, _value)| *key).collect();
+        assert_eq!(range, vec![(1, 0), (1, 1)]);
+        assert_eq!(storage.remove(&(2, 0)), Some(MsgLine::String("c".into())));
+        let keys: Vec<_> = storage.iter().map(|(key, _value)| *key).collect();
+        assert_eq!(keys, vec![(1, 0), (1, 1), (3, 0)]);
+        let after: Vec<_> = storage.after((1, 0)).map(|(key, _value)| *key).collect();
+        assert_eq!(after, vec![(1, 1), (3, 0)]);
+        assert_eq!(
+            storage.after((1, 5)).next().map(|(key, _value)| *key),
+            Some((3, 0))
+        );
+        assert_eq!(storage.after((3, 0)).count(), 0);
+        assert_eq!(storage.range(2).count(), 0);
+        assert_eq!(storage.into_entries().len(), 3);
+    }
+
+    #[test]
+    fn all_storages_agree() {
+        exercise(Box::new(BTreeMap::new()));
+        exercise(Box::new(SortedVecStorage::new()));
+        exercise(Box::new(HashStorage::<RandomState>::new()));
+    }
+}
@@ -0,0 +1,112 @@
+/// Hint on fixing the syntax error at `offset`, from a look at the line it is on.
+pub(crate) fn suggestion(input: &[u8], offset: usize) -> Option<String> {
+    let offset = offset.min(input.len());
+    // An unclosed value runs to the end of input, so look at the entry it started on.
+    let offset = if offset == input.len() {
+        input
+            .iter()
+            .rposition(|&byte| byte == b'{')
+            .unwrap_or(offset)
+    } else {
+        offset
+    };
+    let start = input[..offset]
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |pos| pos + 1);
+    let end = input[offset..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(input.len(), |len| offset + len);
+    let line = String::from_utf8_lossy(&input[start..end]);
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let rest = match line.strip_prefix('{') {
+        Some(rest) => rest,
+        None => return Some("start comments with '#' or '//', entries with '{'".into()),
+    };
+    let index_end = match rest.find('}') {
+        Some(index_end) => index_end,
+        None => return Some("missing closing '}' before end of line".into()),
+    };
+    let index = &rest[..index_end];
+    if index.is_empty() {
+        return Some("the index is empty, e.g. {100}{}{text}".into());
+    }
+    if let Some(ch) = index.chars().find(|ch| !ch.is_ascii_digit()) {
+        let hint = match ch {
+            'O' | 'o' => ", did you mean '0'?",
+            'l' | 'I' => ", did you mean '1'?",
+            _ => "",
+        };
+        return Some(format!(
+            "index contains non-digit character '{}'{}",
+            ch, hint
+        ));
+    }
+    if index.parse::<u32>().is_err() {
+        return Some(format!("index {} is larger than {}", index, u32::MAX));
+    }
+    let after_index = &rest[index_end + 1..];
+    if after_index.starts_with(char::is_whitespace) && after_index.trim_start().starts_with('{') {
+        return Some("remove whitespace between '}' and '{'".into());
+    }
+    let opening = line.matches('{').count();
+    let closing = line.matches('}').count();
+    if opening > closing {
+        return Some("missing closing '}' before end of line".into());
+    }
+    if opening < 3 {
+        return Some("an entry needs three brace groups, e.g. {100}{}{text}".into());
+    }
+    Some("text after an entry must be a comment starting with '#' or '//'".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Suggestion for an error at the first `at` in `input`, or at its end.
+    fn suggest(input: &str, at: &str) -> Option<String> {
+        suggestion(input.as_bytes(), input.find(at).unwrap_or(input.len()))
+    }
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(
+            suggest("{1}{}{a}\n{1O}{}{b}\n", "O").as_deref(),
+            Some("index contains non-digit character 'O', did you mean '0'?")
+        );
+        assert_eq!(
+            suggest("{1}{}{a}\n{2}{}{unclosed\n", "<end>").as_deref(),
+            Some("missing closing '}' before end of line")
+        );
+        assert_eq!(
+            suggest("{2} {}{b}\n", " ").as_deref(),
+            Some("remove whitespace between '}' and '{'")
+        );
+        assert_eq!(
+            suggest("{2}{}{b} trailing\n", "trailing").as_deref(),
+            Some("text after an entry must be a comment starting with '#' or '//'")
+        );
+        assert_eq!(
+            suggest("; comment\n", ";").as_deref(),
+            Some("start comments with '#' or '//', entries with '{'")
+        );
+        assert_eq!(
+            suggest("{2}{b}\n", "\n").as_deref(),
+            Some("an entry needs three brace groups, e.g. {100}{}{text}")
+        );
+    }
+
+    #[test]
+    fn parse_errors_carry_hints() {
+        let err = crate::parse_msg(b"{1}{}{a}\n{1O}{}{b}\n").unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with("\nhelp: index contains non-digit character 'O', did you mean '0'?")
+        );
+    }
+}
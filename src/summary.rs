@@ -0,0 +1,56 @@
+use std::collections::BTreeSet;
+
+use super::{Line, scanner::Scanner};
+
+/// Counts from [`scan_counts`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanSummary {
+    pub entries: usize,
+    pub distinct_indices: usize,
+    /// Comment lines, not counting comments after entries.
+    pub comments: usize,
+}
+
+/// Counts entries and comments of `input` without decoding or storing values,
+/// e.g. as a capacity hint or progress total before the real parse.
+///
+/// Stops at the first syntax error, counting only what came before it.
+pub fn scan_counts(input: &[u8]) -> ScanSummary {
+    let mut scanner = Scanner::new(input);
+    let mut summary = ScanSummary::default();
+    let mut indices = BTreeSet::new();
+    loop {
+        match scanner.line() {
+            Ok(Line::Entry(entry)) => {
+                summary.entries += 1;
+                indices.insert(entry.index);
+            }
+            Ok(Line::Comment(_comment)) => summary.comments += 1,
+            Ok(Line::Break) => {}
+            Err(_err) => break,
+        }
+        if !scanner.newline() {
+            break;
+        }
+    }
+    summary.distinct_indices = indices.len();
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts() {
+        let input = b"# header\n\n{10}{}{a}\n{15}{}{b} # note\n{15}{}{c}\n{20}{}{broken\n";
+        assert_eq!(
+            scan_counts(input),
+            ScanSummary {
+                entries: 3,
+                distinct_indices: 2,
+                comments: 1,
+            }
+        );
+    }
+}
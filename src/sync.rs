@@ -0,0 +1,124 @@
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::{InsertError, MsgDictionary, MsgLine};
+
+/// A dictionary shared between threads that can be edited at runtime, e.g. by
+/// admin commands renaming items while other threads keep serving lookups.
+///
+/// Lookups return owned values, hold [`SyncMsgDictionary::read`] to borrow
+/// several values under one lock.
+#[derive(Debug, Default)]
+pub struct SyncMsgDictionary {
+    inner: RwLock<MsgDictionary>,
+}
+
+impl SyncMsgDictionary {
+    pub fn new(dict: MsgDictionary) -> Self {
+        Self {
+            inner: RwLock::new(dict),
+        }
+    }
+
+    pub fn into_inner(self) -> MsgDictionary {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// A panic in another thread while editing doesn't lock readers out, the
+    /// dictionary is left as the panicking edit had made it.
+    pub fn read(&self) -> RwLockReadGuard<'_, MsgDictionary> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, MsgDictionary> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    pub fn get_first_string(&self, index: u32) -> Option<String> {
+        self.read().get_first_string(index).map(str::to_owned)
+    }
+
+    pub fn get_first_bytes(&self, index: u32) -> Option<Vec<u8>> {
+        self.read().get_first_bytes(index).map(<[u8]>::to_vec)
+    }
+
+    pub fn get_or_missing(&self, index: u32) -> String {
+        self.read().get_or_missing(index).into_owned()
+    }
+
+    pub fn count(&self, index: u32) -> u32 {
+        self.read().count(index)
+    }
+
+    pub fn try_insert(&self, index: u32, value: MsgLine) -> Result<u32, InsertError> {
+        self.write().try_insert(index, value)
+    }
+
+    /// Replaces every value of `index` with `value`.
+    pub fn set(&self, index: u32, value: MsgLine) {
+        let mut dict = self.write();
+        dict.remove_range(index..=index);
+        dict.insert(index, value);
+    }
+
+    /// Runs `edit` with exclusive access, for changes that must look atomic to readers.
+    pub fn update<T>(&self, edit: impl FnOnce(&mut MsgDictionary) -> T) -> T {
+        edit(&mut self.write())
+    }
+}
+
+impl From<MsgDictionary> for SyncMsgDictionary {
+    fn from(dict: MsgDictionary) -> Self {
+        Self::new(dict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn edit_while_reading() {
+        let dict = Arc::new(SyncMsgDictionary::from(
+            parse_msg(b"{100}{}{Knife}\n{100}{}{Old knife}\n").unwrap(),
+        ));
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let dict = dict.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        let name = dict.get_first_string(100).unwrap();
+                        assert!(name == "Knife" || name == "Blade", "{}", name);
+                    }
+                })
+            })
+            .collect();
+        dict.set(100, MsgLine::String("Blade".into()));
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(dict.get_first_string(100).as_deref(), Some("Blade"));
+        assert_eq!(dict.count(100), 1);
+        assert_eq!(dict.update(|dict| dict.len()), 1);
+    }
+
+    #[test]
+    fn set_last_index() {
+        let dict = SyncMsgDictionary::from(parse_msg(b"{4294967295}{}{Old}\n").unwrap());
+        dict.set(u32::MAX, MsgLine::String("New".into()));
+        assert_eq!(dict.get_first_string(u32::MAX).as_deref(), Some("New"));
+        assert_eq!(dict.count(u32::MAX), 1);
+    }
+}
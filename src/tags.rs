@@ -0,0 +1,151 @@
+use std::{collections::btree_set::BTreeSet, fmt::Write};
+
+use super::{MsgDictionary, MsgLine};
+
+/// Trailing comment part that tags an entry, e.g.
+/// `{100}{}{Hello}# tags: needs-review, ui`.
+pub(crate) const ANNOTATION: &str = "tags:";
+
+impl MsgDictionary {
+    /// Attaches `tag` to an entry. Tags are kept apart from values and written as an
+    /// annotation of the entry's comment or by [`tags_sidecar`](Self::tags_sidecar),
+    /// so they shouldn't hold commas, semicolons or whitespace.
+    pub fn add_tag(&mut self, index: u32, sub_index: u32, tag: &str) {
+        self.tags
+            .entry((index, sub_index))
+            .or_default()
+            .insert(tag.into());
+    }
+
+    pub fn remove_tag(&mut self, index: u32, sub_index: u32, tag: &str) {
+        if let Some(tags) = self.tags.get_mut(&(index, sub_index)) {
+            tags.remove(tag);
+            if tags.is_empty() {
+                self.tags.remove(&(index, sub_index));
+            }
+        }
+    }
+
+    /// Tags of an entry, sorted.
+    pub fn tags(&self, index: u32, sub_index: u32) -> impl Iterator<Item = &str> {
+        self.tags
+            .get(&(index, sub_index))
+            .into_iter()
+            .flatten()
+            .map(|tag| &**tag)
+    }
+
+    pub fn has_tag(&self, index: u32, sub_index: u32, tag: &str) -> bool {
+        self.tags
+            .get(&(index, sub_index))
+            .is_some_and(|tags| tags.contains(tag))
+    }
+
+    /// Entries carrying `tag`, in key order.
+    pub fn iter_tagged<'a>(
+        &'a self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = ((u32, u32), &'a MsgLine)> + 'a {
+        self.tags
+            .iter()
+            .filter(move |(_key, tags)| tags.contains(tag))
+            .filter_map(move |(key, _tags)| Some((*key, self.index_to_line.get(key)?)))
+    }
+
+    /// Tags as `index:sub_index tag,tag` lines, for storing next to the MSG file.
+    pub fn tags_sidecar(&self) -> String {
+        let mut out = String::new();
+        for ((index, sub_index), tags) in &self.tags {
+            let tags: Vec<&str> = tags.iter().map(|tag| &**tag).collect();
+            let _ = writeln!(out, "{}:{} {}", index, sub_index, tags.join(","));
+        }
+        out
+    }
+
+    /// Adds tags from [`tags_sidecar`](Self::tags_sidecar) output.
+    pub fn load_tags_sidecar(&mut self, sidecar: &str) -> Result<(), String> {
+        for (number, line) in sidecar.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let malformed = || format!("Malformed tags line {}: {}", number + 1, line);
+            let (key, tags) = line.split_once(' ').ok_or_else(malformed)?;
+            let (index, sub_index) = key.split_once(':').ok_or_else(malformed)?;
+            let index = index.parse().map_err(|_| malformed())?;
+            let sub_index = sub_index.parse().map_err(|_| malformed())?;
+            for tag in tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+                self.add_tag(index, sub_index, tag);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tags of a trailing comment holding the tags annotation.
+pub(crate) fn annotation(comment: &[u8]) -> Option<BTreeSet<Box<str>>> {
+    let comment = String::from_utf8_lossy(comment);
+    let tags = comment.trim().strip_prefix(ANNOTATION)?;
+    Some(
+        tags.split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(Into::into)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn tags_from_annotations_and_sidecar() {
+        let mut dict =
+            parse_msg(b"{1}{}{Hello}# tags: needs-review, ui\n{1}{}{Hi}\n{2}{}{Bye}# tags: ui\n")
+                .unwrap();
+        assert_eq!(
+            dict.tags(1, 0).collect::<Vec<_>>(),
+            vec!["needs-review", "ui"]
+        );
+        assert!(!dict.has_tag(1, 1, "ui"));
+        let keys = |dict: &MsgDictionary, tag| {
+            dict.iter_tagged(tag)
+                .map(|(key, _value)| key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(&dict, "ui"), vec![(1, 0), (2, 0)]);
+
+        dict.remove_tag(1, 0, "needs-review");
+        dict.add_tag(1, 1, "needs-review");
+        let sidecar = dict.tags_sidecar();
+        assert_eq!(sidecar, "1:0 ui\n1:1 needs-review\n2:0 ui\n");
+
+        let mut reloaded = parse_msg(b"{1}{}{Hello}\n{1}{}{Hi}\n{2}{}{Bye}\n").unwrap();
+        reloaded.load_tags_sidecar(&sidecar).unwrap();
+        assert_eq!(keys(&reloaded, "needs-review"), vec![(1, 1)]);
+        assert!(reloaded.load_tags_sidecar("1 ui").is_err());
+    }
+
+    #[test]
+    fn edited_tags_are_saved() {
+        let mut dict = parse_msg(b"{1}{}{Hello}# short; tags: ui\n{2}{}{Bye}\n").unwrap();
+        assert_eq!(dict.comment(1, 0), Some("short"));
+        dict.remove_tag(1, 0, "ui");
+        dict.add_tag(2, 0, "needs-review");
+        dict.add_tag(2, 0, "ui");
+        let written = dict.to_msg_bytes();
+        assert_eq!(
+            written,
+            b"{1}{}{Hello} # short\n{2}{}{Bye} # tags: needs-review, ui\n"
+        );
+        let reloaded = parse_msg(&written).unwrap();
+        assert_eq!(reloaded.tags(1, 0).count(), 0);
+        assert_eq!(
+            reloaded.tags(2, 0).collect::<Vec<_>>(),
+            vec!["needs-review", "ui"]
+        );
+        assert_eq!(reloaded.comment(2, 0), None);
+    }
+}
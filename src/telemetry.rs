@@ -0,0 +1,107 @@
+use std::{fmt, sync::Arc};
+
+use super::{MsgDictionary, MsgLine};
+
+/// A lookup worth reporting, see [`MsgDictionary::set_lookup_hook`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LookupEvent<'a> {
+    pub index: u32,
+    pub sub_index: u32,
+    /// Free-form description of the caller, e.g. a script or dialog name.
+    pub context: Option<&'a str>,
+    pub kind: LookupEventKind<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LookupEventKind<'a> {
+    /// The dictionary doesn't have the key.
+    Missing,
+    /// The key exists but its index is marked with [`MsgDictionary::deprecate`].
+    Deprecated { note: &'a str },
+}
+
+type HookFn = dyn Fn(&LookupEvent<'_>) + Send + Sync;
+
+#[derive(Clone)]
+pub(crate) struct LookupHook(Arc<HookFn>);
+
+impl fmt::Debug for LookupHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LookupHook")
+    }
+}
+
+impl MsgDictionary {
+    /// Calls `hook` every time a first-value lookup misses or reads a deprecated
+    /// index, so servers can log which strings a language pack lacks.
+    pub fn set_lookup_hook(&mut self, hook: impl Fn(&LookupEvent<'_>) + Send + Sync + 'static) {
+        self.lookup_hook = Some(LookupHook(Arc::new(hook)));
+    }
+
+    pub fn clear_lookup_hook(&mut self) {
+        self.lookup_hook = None;
+    }
+
+    /// Same as [`MsgDictionary::get_first_string`], passing `context` to the lookup hook.
+    pub fn get_first_string_in(&self, index: u32, context: &str) -> Option<&str> {
+        self.lookup((index, 0), Some(context))
+            .and_then(MsgLine::string)
+    }
+
+    pub(crate) fn lookup(&self, key: (u32, u32), context: Option<&str>) -> Option<&MsgLine> {
+        let index = if self.aliases.is_empty() {
+            key.0
+        } else {
+            self.resolve_alias(key.0)
+        };
+        let found = self.index_to_line.get(&(index, key.1));
+        if let Some(LookupHook(hook)) = &self.lookup_hook {
+            let kind = match found {
+                None => Some(LookupEventKind::Missing),
+                Some(_) => self
+                    .deprecation(key.0)
+                    .map(|note| LookupEventKind::Deprecated { note }),
+            };
+            if let Some(kind) = kind {
+                hook(&LookupEvent {
+                    index: key.0,
+                    sub_index: key.1,
+                    context,
+                    kind,
+                });
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::parse_msg;
+
+    #[test]
+    fn hook_sees_misses() {
+        let mut dict = parse_msg(b"{1}{}{Hello}").unwrap();
+        let missed = Arc::new(Mutex::new(vec![]));
+        let sink = missed.clone();
+        dict.set_lookup_hook(move |event| {
+            let context = event.context.map(str::to_owned);
+            sink.lock().unwrap().push((event.index, context));
+        });
+
+        assert_eq!(dict.get_first_string(1), Some("Hello"));
+        assert_eq!(dict.get_first_string(2), None);
+        assert_eq!(dict.get_first_string_in(3, "dialog 12"), None);
+        assert_eq!(dict.get_or_missing(4), "<error str 4>");
+        assert_eq!(
+            *missed.lock().unwrap(),
+            vec![(2, None), (3, Some("dialog 12".to_owned())), (4, None)]
+        );
+
+        dict.clear_lookup_hook();
+        dict.get_first_string(5);
+        assert_eq!(missed.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,163 @@
+use std::ops::Range;
+
+use super::scanner::find_byte;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    OpenBrace,
+    CloseBrace,
+    /// Digits of an entry's index.
+    Number,
+    /// Secondary key, value, comment text or stray text.
+    Text,
+    /// `#` or `//` starting a comment.
+    CommentMarker,
+    /// `\n` or `\r\n` outside of braces.
+    Newline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte range in the input.
+    pub span: Range<usize>,
+}
+
+/// Splits `input` into typed tokens, e.g. for syntax highlighting.
+///
+/// Never fails: malformed text still yields tokens, spaces between tokens are skipped.
+pub fn lex(input: &[u8]) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut push = |kind, span: Range<usize>| {
+        if !span.is_empty() {
+            tokens.push(Token { kind, span });
+        }
+    };
+    let line_end =
+        |from: usize| find_byte(b'\n', &input[from..]).map_or(input.len(), |pos| from + pos);
+    let mut pos = 0;
+    // Brace groups seen on the current line, the first one holds the index.
+    let mut group = 0;
+    while pos < input.len() {
+        match input[pos] {
+            b' ' | b'\t' => pos += 1,
+            b'\n' => {
+                push(TokenKind::Newline, pos..pos + 1);
+                pos += 1;
+                group = 0;
+            }
+            b'\r' if input.get(pos + 1) == Some(&b'\n') => {
+                push(TokenKind::Newline, pos..pos + 2);
+                pos += 2;
+                group = 0;
+            }
+            b'{' => {
+                push(TokenKind::OpenBrace, pos..pos + 1);
+                let start = pos + 1;
+                let end = find_byte(b'}', &input[start..]).map_or(input.len(), |len| start + len);
+                let content = &input[start..end];
+                let kind = if group == 0
+                    && !content.is_empty()
+                    && content.iter().all(u8::is_ascii_digit)
+                {
+                    TokenKind::Number
+                } else {
+                    TokenKind::Text
+                };
+                push(kind, start..end);
+                push(TokenKind::CloseBrace, end..(end + 1).min(input.len()));
+                pos = end + 1;
+                group += 1;
+            }
+            b'#' => {
+                push(TokenKind::CommentMarker, pos..pos + 1);
+                pos = comment_text(input, pos + 1, line_end(pos), &mut push);
+            }
+            b'/' if input.get(pos + 1) == Some(&b'/') => {
+                push(TokenKind::CommentMarker, pos..pos + 2);
+                pos = comment_text(input, pos + 2, line_end(pos), &mut push);
+            }
+            _ => {
+                let end = input[pos..line_end(pos)]
+                    .iter()
+                    .position(|&byte| byte == b'{' || byte == b'\r')
+                    .map_or(line_end(pos), |len| pos + len.max(1));
+                push(TokenKind::Text, pos..end);
+                pos = end;
+            }
+        }
+    }
+    tokens
+}
+
+/// Pushes comment text from `start` to the end of the line, returning where it ends.
+fn comment_text(
+    input: &[u8],
+    start: usize,
+    line_end: usize,
+    push: &mut impl FnMut(TokenKind, Range<usize>),
+) -> usize {
+    let start = start
+        + input[start..line_end]
+            .iter()
+            .take_while(|&&byte| byte == b' ' || byte == b'\t')
+            .count();
+    let end = if input[..line_end].ends_with(b"\r") {
+        line_end - 1
+    } else {
+        line_end
+    };
+    push(TokenKind::Text, start..end.max(start));
+    end.max(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &[u8]) -> Vec<(TokenKind, &[u8])> {
+        lex(input)
+            .into_iter()
+            .map(|token| (token.kind, &input[token.span]))
+            .collect()
+    }
+
+    #[test]
+    fn entry_and_comment_tokens() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(b"# title\r\n{10}{}{multi\nline} // note\n"),
+            vec![
+                (CommentMarker, &b"#"[..]),
+                (Text, b"title"),
+                (Newline, b"\r\n"),
+                (OpenBrace, b"{"),
+                (Number, b"10"),
+                (CloseBrace, b"}"),
+                (OpenBrace, b"{"),
+                (CloseBrace, b"}"),
+                (OpenBrace, b"{"),
+                (Text, b"multi\nline"),
+                (CloseBrace, b"}"),
+                (CommentMarker, b"//"),
+                (Text, b"note"),
+                (Newline, b"\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_input() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(b"junk {x}{"),
+            vec![
+                (Text, &b"junk "[..]),
+                (OpenBrace, b"{"),
+                (Text, b"x"),
+                (CloseBrace, b"}"),
+                (OpenBrace, b"{"),
+            ]
+        );
+    }
+}
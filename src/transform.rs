@@ -0,0 +1,141 @@
+use std::{fmt, sync::Arc};
+
+use super::MsgLine;
+
+/// BOM bytes as they show up after decoding UTF-8 files as CP1251 or CP1252.
+const BOM_MOJIBAKE: &[&str] = &["п»ї", "ï»¿"];
+
+type TransformFn = dyn Fn(&str) -> String + Send + Sync;
+
+/// Step of a [`TransformPipeline`].
+#[derive(Clone)]
+pub enum Transform {
+    /// Trims leading and trailing whitespace.
+    Trim,
+    /// Turns `\r\n` and lone `\r` into `\n`.
+    NormalizeNewlines,
+    /// Removes U+FEFF and mis-decoded BOMs at the start of a value.
+    StripBom,
+    /// Applied to string values only.
+    Custom(Arc<TransformFn>),
+}
+
+impl Transform {
+    pub fn custom(transform: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Transform::Custom(Arc::new(transform))
+    }
+
+    fn apply(&self, value: MsgLine) -> MsgLine {
+        match (self, value) {
+            (Transform::Trim, MsgLine::String(value)) => MsgLine::String(value.trim().into()),
+            (Transform::Trim, MsgLine::Bytes(value)) => MsgLine::Bytes(value.trim_ascii().into()),
+            (Transform::NormalizeNewlines, MsgLine::String(value)) => {
+                MsgLine::String(value.replace("\r\n", "\n").replace('\r', "\n").into())
+            }
+            (Transform::NormalizeNewlines, MsgLine::Bytes(value)) => {
+                let mut out = Vec::with_capacity(value.len());
+                let mut bytes = value.iter().copied().peekable();
+                while let Some(byte) = bytes.next() {
+                    if byte == b'\r' {
+                        bytes.next_if_eq(&b'\n');
+                        out.push(b'\n');
+                    } else {
+                        out.push(byte);
+                    }
+                }
+                MsgLine::Bytes(out.into())
+            }
+            (Transform::StripBom, MsgLine::String(value)) => {
+                let value = BOM_MOJIBAKE
+                    .iter()
+                    .copied()
+                    .chain(Some("\u{feff}"))
+                    .find_map(|bom| value.strip_prefix(bom))
+                    .unwrap_or(&value);
+                MsgLine::String(value.into())
+            }
+            (Transform::StripBom, MsgLine::Bytes(value)) => {
+                MsgLine::Bytes(value.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&value).into())
+            }
+            (Transform::Custom(transform), MsgLine::String(value)) => {
+                MsgLine::String(transform(&value).into())
+            }
+            (Transform::Custom(_transform), value) => value,
+        }
+    }
+}
+
+impl fmt::Debug for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transform::Trim => f.write_str("Trim"),
+            Transform::NormalizeNewlines => f.write_str("NormalizeNewlines"),
+            Transform::StripBom => f.write_str("StripBom"),
+            Transform::Custom(_transform) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// Transforms applied in order to every value while parsing, see
+/// [`crate::ParseOptions::transforms`].
+#[derive(Debug, Clone, Default)]
+pub struct TransformPipeline {
+    steps: Vec<Transform>,
+}
+
+impl TransformPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(mut self, transform: Transform) -> Self {
+        self.steps.push(transform);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// `value` after every step.
+    pub fn apply(&self, value: MsgLine) -> MsgLine {
+        self.steps
+            .iter()
+            .fold(value, |value, transform| transform.apply(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_msg_with_metrics};
+
+    #[test]
+    fn pipeline_at_parse_time() {
+        let options = ParseOptions {
+            transforms: TransformPipeline::new()
+                .then(Transform::StripBom)
+                .then(Transform::NormalizeNewlines)
+                .then(Transform::Trim)
+                .then(Transform::custom(|value| value.replace("...", "…"))),
+            ..ParseOptions::default()
+        };
+        let input = b"{1}{}{\xef\xbb\xbf Wait...\r\nwhat?\r}\n{2}{}{ok}\n{3}{}{ \xff\r\n}\n";
+        let (dict, metrics) = parse_msg_with_metrics(input, &options).unwrap();
+        assert_eq!(dict.get_first_string(1), Some("Wait…\nwhat?"));
+        assert_eq!(dict.get_first_string(2), Some("ok"));
+        assert_eq!(dict.get_first_bytes(3), Some(&b"\xff"[..]));
+        assert_eq!(metrics.transformed, 2);
+    }
+
+    #[test]
+    fn strip_bom_only_at_start() {
+        let strip = |value: &str| match Transform::StripBom.apply(MsgLine::String(value.into())) {
+            MsgLine::String(value) => value.to_string(),
+            MsgLine::Bytes(_) => unreachable!(),
+        };
+        assert_eq!(strip("\u{feff}a\u{feff}b"), "a\u{feff}b");
+        assert_eq!(strip("ï»¿a"), "a");
+        assert_eq!(strip("a"), "a");
+    }
+}
@@ -0,0 +1,107 @@
+use super::{MsgDictionary, MsgLine};
+
+/// Cyrillic to Latin transliteration scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranslitScheme {
+    /// GOST 7.79-2000 system B, reversible.
+    Gost,
+    /// The spelling players use in chats: `щ` as `sch`, soft and hard signs dropped.
+    #[default]
+    Informal,
+}
+
+const LETTERS: &str = "абвгдеёжзийклмнопрстуфхцчшщъыьэюя";
+
+const GOST: [&str; 33] = [
+    "a", "b", "v", "g", "d", "e", "yo", "zh", "z", "i", "j", "k", "l", "m", "n", "o", "p", "r",
+    "s", "t", "u", "f", "x", "cz", "ch", "sh", "shh", "``", "y`", "`", "e`", "yu", "ya",
+];
+
+const INFORMAL: [&str; 33] = [
+    "a", "b", "v", "g", "d", "e", "yo", "zh", "z", "i", "y", "k", "l", "m", "n", "o", "p", "r",
+    "s", "t", "u", "f", "kh", "ts", "ch", "sh", "sch", "", "y", "", "e", "yu", "ya",
+];
+
+/// ASCII-only version of `value`. Characters with no transliteration become `?`.
+pub fn transliterate(value: &str, scheme: TranslitScheme) -> String {
+    let table = match scheme {
+        TranslitScheme::Gost => &GOST,
+        TranslitScheme::Informal => &INFORMAL,
+    };
+    let mut out = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if ch.is_ascii() {
+            out.push(ch);
+            continue;
+        }
+        let lower = ch.to_lowercase().next().unwrap_or(ch);
+        let latin = match LETTERS.chars().position(|letter| letter == lower) {
+            Some(position) => table[position],
+            None => {
+                out.push('?');
+                continue;
+            }
+        };
+        if lower == ch {
+            out.push_str(latin);
+        } else {
+            let mut chars = latin.chars();
+            out.extend(chars.next().map(|first| first.to_ascii_uppercase()));
+            out.push_str(chars.as_str());
+        }
+    }
+    out
+}
+
+impl MsgDictionary {
+    /// Copy with every string value transliterated to ASCII, as fallback text for
+    /// clients without Cyrillic fonts.
+    ///
+    /// Byte values keep their ASCII bytes, others become `?`.
+    pub fn transliterated(&self, scheme: TranslitScheme) -> MsgDictionary {
+        let mut dict = MsgDictionary::new();
+        for (&key, value) in self.index_to_line.iter() {
+            let value = match value {
+                MsgLine::String(string) => transliterate(string, scheme),
+                MsgLine::Bytes(bytes) => bytes
+                    .iter()
+                    .map(|&byte| if byte.is_ascii() { byte as char } else { '?' })
+                    .collect(),
+            };
+            dict.index_to_line
+                .insert(key, MsgLine::String(value.into()));
+        }
+        dict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn schemes() {
+        assert_eq!(
+            transliterate("Щука и ёж", TranslitScheme::Gost),
+            "Shhuka i yozh"
+        );
+        assert_eq!(
+            transliterate("Щука и ёж", TranslitScheme::Informal),
+            "Schuka i yozh"
+        );
+        assert_eq!(
+            transliterate("Объект, цена: 5$", TranslitScheme::Informal),
+            "Obekt, tsena: 5$"
+        );
+        assert_eq!(transliterate("日本", TranslitScheme::Gost), "??");
+    }
+
+    #[test]
+    fn ascii_dictionary() {
+        let dict = parse_msg("{1}{}{Хлам}\n{2}{}{Junk}\n".as_bytes()).unwrap();
+        let ascii = dict.transliterated(TranslitScheme::Informal);
+        assert_eq!(ascii.get_first_string(1), Some("Khlam"));
+        assert_eq!(ascii.get_first_string(2), Some("Junk"));
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+
+use super::{Line, scanner::Scanner, suggest::suggestion};
+
+/// Syntax problem found by [`validate_msg`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Byte offset in the input.
+    pub offset: usize,
+    pub message: String,
+    /// How the problem might be fixed, e.g. "did you mean '0'?".
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (byte {})", self.message, self.offset)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", help: {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `input` parses, without building a dictionary or copying values.
+///
+/// Scanning resumes on the next line after an error, so all broken lines are reported.
+pub fn validate_msg(input: &[u8]) -> Result<(), Vec<Diagnostic>> {
+    let mut scanner = Scanner::new(input);
+    let mut diagnostics = vec![];
+    loop {
+        // A value spanning lines is likely missing its `}`.
+        let mut multiline_value = false;
+        match scanner.line() {
+            Ok(Line::Entry(entry)) => {
+                multiline_value = entry.value.contains(&b'\n');
+                if !entry.secondary.is_empty() {
+                    diagnostics.push(Diagnostic {
+                        offset: entry.secondary.as_ptr() as usize - input.as_ptr() as usize,
+                        message: format!("Non-empty secondary key for index {}", entry.index),
+                        suggestion: Some(
+                            "leave the second braces empty, e.g. {100}{}{text}".into(),
+                        ),
+                    });
+                }
+            }
+            Ok(_line) => {}
+            Err(err) => diagnostics.push(Diagnostic {
+                offset: err.offset,
+                message: format!("Expected {}", err.expected),
+                suggestion: suggestion(input, err.offset),
+            }),
+        }
+        if scanner.newline() {
+            continue;
+        }
+        if scanner.rest().is_empty() {
+            break;
+        }
+        scanner.space0();
+        let rest = scanner.rest();
+        if diagnostics
+            .last()
+            .is_none_or(|last| last.offset < scanner.pos)
+        {
+            let suggestion = if multiline_value {
+                Some("the previous entry's text spans several lines, is a '}' missing?".into())
+            } else {
+                suggestion(input, scanner.pos)
+            };
+            diagnostics.push(Diagnostic {
+                offset: scanner.pos,
+                message: "Unexpected text".into(),
+                suggestion,
+            });
+        }
+        // Skip the rest of the broken line.
+        match rest.iter().position(|&byte| byte == b'\n') {
+            Some(len) => scanner.pos += len + 1,
+            None => break,
+        }
+    }
+    if diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(diagnostics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_every_broken_line() {
+        assert_eq!(validate_msg(b"# ok\n{1}{}{a}\r\n\n{2}{}{b}"), Ok(()));
+        let diagnostics =
+            validate_msg(b"{1}{}{a}\n{x}{}{b}\n{3}{s}{c}\n{4}{}{d} junk\n{5}{}{e}\n{6}{}{open")
+                .unwrap_err();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.offset,
+                    diagnostic.message.as_str(),
+                    diagnostic.suggestion.as_deref().unwrap_or_default()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    10,
+                    "Expected an index",
+                    "index contains non-digit character 'x'"
+                ),
+                (
+                    22,
+                    "Non-empty secondary key for index 3",
+                    "leave the second braces empty, e.g. {100}{}{text}"
+                ),
+                (
+                    37,
+                    "Unexpected text",
+                    "text after an entry must be a comment starting with '#' or '//'"
+                ),
+                (61, "Expected `}`", "missing closing '}' before end of line"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_multiline_value() {
+        let diagnostics = validate_msg(
+            b"{1}{}{open
+{2}{}{b}
+",
+        )
+        .unwrap_err();
+        assert_eq!(
+            diagnostics[0].suggestion.as_deref(),
+            Some("the previous entry's text spans several lines, is a '}' missing?")
+        );
+    }
+}
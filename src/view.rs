@@ -0,0 +1,90 @@
+use super::MsgDictionary;
+
+/// Lookups by `(group, offset)` for the engine's `base + id * stride + slot` index
+/// conventions.
+#[derive(Debug, Clone, Copy)]
+pub struct DictView<'a> {
+    dict: &'a MsgDictionary,
+    base: u32,
+    stride: u32,
+}
+
+impl<'a> DictView<'a> {
+    /// Plain index lookups, base 0 and stride 1.
+    pub fn new(dict: &'a MsgDictionary) -> Self {
+        Self {
+            dict,
+            base: 0,
+            stride: 1,
+        }
+    }
+
+    /// `stride` must not be 0.
+    pub fn with_base_stride(mut self, base: u32, stride: u32) -> Self {
+        assert_ne!(stride, 0, "DictView stride must not be 0");
+        self.base = base;
+        self.stride = stride;
+        self
+    }
+
+    /// `base + group * stride + offset`, `None` if `offset` is outside the stride
+    /// or the index overflows.
+    pub fn index(&self, group: u32, offset: u32) -> Option<u32> {
+        if offset >= self.stride {
+            return None;
+        }
+        group
+            .checked_mul(self.stride)?
+            .checked_add(self.base)?
+            .checked_add(offset)
+    }
+
+    pub fn get(&self, group: u32, offset: u32) -> Option<&'a str> {
+        self.dict.get_first_string(self.index(group, offset)?)
+    }
+
+    pub fn get_bytes(&self, group: u32, offset: u32) -> Option<&'a [u8]> {
+        self.dict.get_first_bytes(self.index(group, offset)?)
+    }
+
+    /// All values at `(group, offset)`, in file order.
+    pub fn get_all(&self, group: u32, offset: u32) -> Vec<&'a str> {
+        self.index(group, offset).map_or_else(Vec::new, |index| {
+            self.dict
+                .get_all_strings(index)
+                .map(|(_sub_index, value)| value)
+                .collect()
+        })
+    }
+
+    /// Groups with at least one entry, ascending.
+    pub fn groups(&self) -> impl Iterator<Item = u32> + 'a {
+        let (base, stride) = (self.base, self.stride);
+        let mut last = None;
+        self.dict
+            .indices()
+            .filter(move |&index| index >= base)
+            .map(move |index| (index - base) / stride)
+            .filter(move |&group| last.replace(group) != Some(group))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    #[test]
+    fn base_stride_lookups() {
+        let dict =
+            parse_msg(b"{5}{}{below}\n{10}{}{a}\n{13}{}{b}\n{13}{}{c}\n{30}{}{d}\n").unwrap();
+        let view = DictView::new(&dict).with_base_stride(10, 10);
+        assert_eq!(view.get(0, 0), Some("a"));
+        assert_eq!(view.get_all(0, 3), vec!["b", "c"]);
+        assert_eq!(view.get(2, 0), Some("d"));
+        assert_eq!(view.get(0, 10), None);
+        assert_eq!(view.index(u32::MAX, 0), None);
+        assert_eq!(view.groups().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(DictView::new(&dict).get(5, 0), Some("below"));
+    }
+}
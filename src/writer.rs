@@ -0,0 +1,176 @@
+use std::io::{self, Write};
+
+use super::{MsgDictionary, MsgLine, Schema};
+
+/// Formatting for [`MsgDictionary::write_with`].
+///
+/// The format allows nothing between brace groups, so columns are aligned by
+/// zero-padding indices, which parse back to the same numbers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions<'a> {
+    /// Minimum digits of every index.
+    pub pad_width: usize,
+    /// Pads indices to the widest one in the file.
+    pub align: bool,
+    /// Writes a `# name` comment before the entries of each range.
+    pub headers: Option<&'a Schema>,
+}
+
+impl MsgDictionary {
+    /// Serializes entries in key order, one `{index}{}{value}` line each, followed
+    /// by the entry's [comment](MsgDictionary::comment) and its deprecation, tags and
+    /// review state annotations. Aliases come first, as `#= alias from -> to` lines.
+    ///
+    /// Values are written verbatim, so a value containing `}` won't parse back.
+    pub fn to_msg_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_to(&mut out)
+            .expect("writing to a Vec doesn't fail");
+        out
+    }
+
+    /// Same output as [`to_msg_bytes`](Self::to_msg_bytes), streamed entry by entry.
+    ///
+    /// Wrap unbuffered writers such as files in a [`io::BufWriter`].
+    pub fn write_to(&self, out: impl Write) -> io::Result<()> {
+        self.write_with(out, &WriteOptions::default())
+    }
+
+    /// Like [`write_to`](Self::write_to), formatted according to `options`.
+    pub fn write_with(&self, out: impl Write, options: &WriteOptions<'_>) -> io::Result<()> {
+        self.write_encoded_with(out, options, |_key, text, buf| {
+            buf.extend_from_slice(text.as_bytes())
+        })
+    }
+
+    /// [`write_with`](Self::write_with) passing every text it writes through `encode`
+    /// with the key of the entry it belongs to, which appends its bytes to a buffer.
+    /// Alias lines belong to `(from, 0)`, headers to the entry below them.
+    pub(crate) fn write_encoded_with(
+        &self,
+        mut out: impl Write,
+        options: &WriteOptions<'_>,
+        mut encode: impl FnMut((u32, u32), &str, &mut Vec<u8>),
+    ) -> io::Result<()> {
+        let mut buf = vec![];
+        let mut write_text = |out: &mut dyn Write, key: (u32, u32), text: &str| {
+            buf.clear();
+            encode(key, text, &mut buf);
+            out.write_all(&buf)
+        };
+        let mut width = options.pad_width;
+        if options.align {
+            let widest = self
+                .index_to_line
+                .iter()
+                .next_back()
+                .map_or(0, |(&(index, _), _)| index);
+            width = width.max(widest.to_string().len());
+        }
+        for (from, to) in self.aliases() {
+            write_text(&mut out, (from, 0), &format!("#= alias {} -> {}", from, to))?;
+            out.write_all(b"\n")?;
+        }
+        let mut header = None;
+        for (line, (&key, value)) in self.index_to_line.iter().enumerate() {
+            let (index, sub_index) = key;
+            if let Some(schema) = options.headers {
+                let range = schema
+                    .ranges
+                    .iter()
+                    .position(|range| range.start <= index && index < range.end);
+                if let Some(position) = range.filter(|&position| header != Some(position)) {
+                    if line > 0 {
+                        out.write_all(b"\n")?;
+                    }
+                    out.write_all(b"# ")?;
+                    write_text(&mut out, key, &schema.ranges[position].name)?;
+                    out.write_all(b"\n")?;
+                }
+                header = range;
+            }
+            write!(out, "{{{:0width$}}}{{", index, width = width)?;
+            out.write_all(self.secondary_key(index, sub_index).unwrap_or_default())?;
+            out.write_all(b"}{")?;
+            match value {
+                MsgLine::String(string) => write_text(&mut out, key, string)?,
+                MsgLine::Bytes(bytes) => out.write_all(bytes)?,
+            }
+            out.write_all(b"}")?;
+            let comment = self.comments.get(&(index, sub_index));
+            let annotations = self.annotations(index, sub_index);
+            if comment.is_some() || !annotations.is_empty() {
+                out.write_all(b" #")?;
+            }
+            if let Some(comment) = comment {
+                out.write_all(b" ")?;
+                match comment {
+                    MsgLine::String(string) => write_text(&mut out, key, string)?,
+                    MsgLine::Bytes(bytes) => out.write_all(bytes)?,
+                }
+            }
+            for (position, annotation) in annotations.iter().enumerate() {
+                out.write_all(if position > 0 || comment.is_some() {
+                    b"; "
+                } else {
+                    b" "
+                })?;
+                write_text(&mut out, key, annotation)?;
+            }
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MsgLine, parse_msg};
+
+    #[test]
+    fn write_entries() {
+        let mut dict = MsgDictionary::new();
+        dict.insert(15, MsgLine::String("20car".into()));
+        dict.insert(10, MsgLine::String("Global map".into()));
+        dict.insert(15, MsgLine::Bytes(b"\xff"[..].into()));
+        let bytes = dict.to_msg_bytes();
+        assert_eq!(bytes, b"{10}{}{Global map}\n{15}{}{20car}\n{15}{}{\xff}\n");
+        assert_eq!(parse_msg(&bytes).unwrap(), dict);
+
+        let mut streamed = io::Cursor::new(vec![]);
+        dict.write_to(&mut streamed).unwrap();
+        assert_eq!(streamed.into_inner(), bytes);
+    }
+
+    #[test]
+    fn write_options() {
+        use crate::IndexRange;
+
+        let dict = parse_msg(b"{5}{}{a}\n{10}{}{b}\n{120}{}{c}\n{200}{}{d}\n").unwrap();
+        let schema = Schema::new(vec![
+            IndexRange::new("Interface", 0..100),
+            IndexRange::new("Maps", 100..300),
+        ]);
+        let mut out = vec![];
+        let options = WriteOptions {
+            align: true,
+            headers: Some(&schema),
+            ..WriteOptions::default()
+        };
+        dict.write_with(&mut out, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "# Interface\n{005}{}{a}\n{010}{}{b}\n\n# Maps\n{120}{}{c}\n{200}{}{d}\n"
+        );
+        assert_eq!(parse_msg(&out).unwrap(), dict);
+
+        let mut out = vec![];
+        let options = WriteOptions {
+            pad_width: 4,
+            ..WriteOptions::default()
+        };
+        dict.write_with(&mut out, &options).unwrap();
+        assert!(out.starts_with(b"{0005}{}{a}\n"));
+    }
+}
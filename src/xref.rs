@@ -0,0 +1,262 @@
+use std::collections::btree_map::BTreeMap;
+
+use super::MsgDictionary;
+
+/// Engine functions whose second argument is a message index.
+const MSG_FUNCTIONS: &[&str] = &[
+    "GetMsgStr",
+    "GetMsgStrNumUpper",
+    "GetMsgStrNumLower",
+    "GetMsgStrCount",
+    "GetMsgStrSkip",
+    "IsMsgStr",
+];
+
+/// A message reference found in a script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub file: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// Constant the script used, `None` for numeric literals.
+    pub name: Option<String>,
+    /// Resolved index, `None` if the constant has no define.
+    pub index: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XrefReport {
+    /// References to indices the dictionary doesn't have.
+    pub missing: Vec<Reference>,
+    /// References to constants with no define.
+    pub undefined: Vec<Reference>,
+    /// Defines whose index the dictionary doesn't have.
+    pub dangling_defines: Vec<(String, u32)>,
+}
+
+/// Cross-references `.fos` scripts against message dictionaries.
+///
+/// Constants are recognized by `prefix` (`STR_` by default) and resolved through
+/// `#define NAME value` lines, e.g. from `_msgstr.fos`.
+#[derive(Debug, Clone)]
+pub struct ScriptXref {
+    prefix: String,
+    defines: BTreeMap<String, u32>,
+    references: Vec<Reference>,
+}
+
+impl Default for ScriptXref {
+    fn default() -> Self {
+        Self::new("STR_")
+    }
+}
+
+impl ScriptXref {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_owned(),
+            defines: BTreeMap::new(),
+            references: vec![],
+        }
+    }
+
+    /// Collects numeric `#define`s of constants with the prefix.
+    pub fn add_defines(&mut self, source: &str) {
+        for line in source.lines() {
+            let mut words = line.split_whitespace();
+            if words.next() != Some("#define") {
+                continue;
+            }
+            let (name, value) = match (words.next(), words.next()) {
+                (Some(name), Some(value)) => (name, value),
+                _ => continue,
+            };
+            let value = value.trim_start_matches('(').trim_end_matches(')');
+            if let (true, Ok(value)) = (name.starts_with(&self.prefix), value.parse()) {
+                self.defines.insert(name.to_owned(), value);
+            }
+        }
+    }
+
+    pub fn defines(&self) -> &BTreeMap<String, u32> {
+        &self.defines
+    }
+
+    /// Collects references from a script: constants with the prefix, and numeric
+    /// indices passed to `GetMsgStr` and friends.
+    ///
+    /// Uses the defines added so far.
+    pub fn scan(&mut self, file: &str, source: &str) {
+        let tokens = tokenize(source);
+        for (i, &(line, token)) in tokens.iter().enumerate() {
+            if token.starts_with(&self.prefix) && !is_define_name(&tokens, i) {
+                self.references.push(Reference {
+                    file: file.to_owned(),
+                    line,
+                    name: Some(token.to_owned()),
+                    index: self.defines.get(token).copied(),
+                });
+            } else if MSG_FUNCTIONS.contains(&token) {
+                // GetMsgStr ( msg , index )
+                let literal = match tokens.get(i + 1..i + 5) {
+                    Some(&[(_, "("), _, (_, ","), (_, literal)]) => literal,
+                    _ => continue,
+                };
+                if let Ok(index) = literal.parse() {
+                    self.references.push(Reference {
+                        file: file.to_owned(),
+                        line,
+                        name: None,
+                        index: Some(index),
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    pub fn check(&self, dict: &MsgDictionary) -> XrefReport {
+        let mut report = XrefReport::default();
+        for reference in &self.references {
+            match reference.index {
+                Some(index) if dict.count(index) == 0 => report.missing.push(reference.clone()),
+                Some(_) => {}
+                None => report.undefined.push(reference.clone()),
+            }
+        }
+        for (name, &index) in &self.defines {
+            if dict.count(index) == 0 {
+                report.dangling_defines.push((name.clone(), index));
+            }
+        }
+        report
+    }
+}
+
+fn is_define_name(tokens: &[(usize, &str)], i: usize) -> bool {
+    i > 0 && tokens[i - 1].1 == "#define"
+}
+
+/// Identifiers, numbers, `#define` and single punctuation characters with their
+/// line numbers. Comments and string literals are skipped.
+fn tokenize(source: &str) -> Vec<(usize, &str)> {
+    let bytes = source.as_bytes();
+    let mut tokens = vec![];
+    let mut line = 1;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        match bytes[pos] {
+            b'\n' => {
+                line += 1;
+                pos += 1;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos += 2;
+                while pos < bytes.len() && !bytes[pos..].starts_with(b"*/") {
+                    line += (bytes[pos] == b'\n') as usize;
+                    pos += 1;
+                }
+                pos += 2;
+            }
+            quote @ b'"' | quote @ b'\'' => {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos] != quote {
+                    line += (bytes[pos] == b'\n') as usize;
+                    pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                }
+                pos += 1;
+            }
+            b'#' | b'_' | b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => {
+                pos += 1;
+                while pos < bytes.len()
+                    && (bytes[pos] == b'_' || bytes[pos].is_ascii_alphanumeric())
+                {
+                    pos += 1;
+                }
+                tokens.push((line, &source[start..pos]));
+            }
+            byte if byte.is_ascii_whitespace() => pos += 1,
+            byte => {
+                pos += 1;
+                if byte.is_ascii() {
+                    tokens.push((line, &source[start..pos]));
+                } else {
+                    while pos < bytes.len() && !source.is_char_boundary(pos) {
+                        pos += 1;
+                    }
+                }
+            }
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msg;
+
+    const DEFINES: &str = "#define STR_HELLO (100)\n#define STR_BYE 101\n#define STR_OLD (102)\n";
+
+    const SCRIPT: &str = r#"
+void greet(Critter& cr)
+{
+    cr.Say(SAY_NORM, GetMsgStr(TEXTMSG_TEXT, STR_HELLO));
+    // STR_COMMENTED
+    cr.Say(SAY_NORM, "STR_QUOTED");
+    cr.Say(SAY_NORM, GetMsgStr(TEXTMSG_TEXT, 200));
+    cr.Say(SAY_NORM, GetMsgStr(TEXTMSG_TEXT, STR_TYPO));
+}
+"#;
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            tokenize("#define A (1) /* x\n */ f(\"s\");"),
+            vec![
+                (1, "#define"),
+                (1, "A"),
+                (1, "("),
+                (1, "1"),
+                (1, ")"),
+                (2, "f"),
+                (2, "("),
+                (2, ")"),
+                (2, ";")
+            ]
+        );
+    }
+
+    #[test]
+    fn check_script() {
+        let dict = parse_msg(b"{100}{}{Hello}\n{101}{}{Bye}\n").unwrap();
+        let mut xref = ScriptXref::default();
+        xref.add_defines(DEFINES);
+        xref.scan("_msgstr.fos", DEFINES);
+        xref.scan("greet.fos", SCRIPT);
+        assert_eq!(xref.references().len(), 3);
+
+        let report = xref.check(&dict);
+        assert_eq!(
+            report.missing,
+            vec![Reference {
+                file: "greet.fos".into(),
+                line: 7,
+                name: None,
+                index: Some(200),
+            }]
+        );
+        assert_eq!(report.undefined.len(), 1);
+        assert_eq!(report.undefined[0].name.as_deref(), Some("STR_TYPO"));
+        assert_eq!(report.dangling_defines, vec![("STR_OLD".to_owned(), 102)]);
+    }
+}